@@ -8,9 +8,9 @@ use cw20::{
     TokenInfoResponse,
 };
 use cw20_stake::msg::{
-    ClaimsResponse, ExecuteMsg, GetHooksResponse, InstantiateMsg, ListStakersResponse, QueryMsg,
-    StakedBalanceAtHeightResponse, StakedValueResponse, TotalStakedAtHeightResponse,
-    TotalValueResponse,
+    AcceptsStakesResponse, ClaimsResponse, ExecuteMsg, FailedHooksResponse, GetHooksResponse,
+    InstantiateMsg, ListStakersResponse, QueryMsg, StakedBalanceAtHeightResponse,
+    StakedValueResponse, TotalStakedAtHeightResponse, TotalValueResponse,
 };
 use cw20_stake::state::Config;
 
@@ -28,6 +28,7 @@ fn main() {
     export_schema(&schema_for!(StakedValueResponse), &out_dir);
     export_schema(&schema_for!(TotalValueResponse), &out_dir);
     export_schema(&schema_for!(GetHooksResponse), &out_dir);
+    export_schema(&schema_for!(FailedHooksResponse), &out_dir);
     export_schema(&schema_for!(ClaimsResponse), &out_dir);
     export_schema(&schema_for!(AllowanceResponse), &out_dir);
     export_schema(&schema_for!(BalanceResponse), &out_dir);
@@ -35,6 +36,7 @@ fn main() {
     export_schema(&schema_for!(AllAllowancesResponse), &out_dir);
     export_schema(&schema_for!(AllAccountsResponse), &out_dir);
     export_schema(&schema_for!(ListStakersResponse), &out_dir);
+    export_schema(&schema_for!(AcceptsStakesResponse), &out_dir);
 
     // Need to rename so it matches the TS pattern
     export_schema_with_title(&schema_for!(Config), &out_dir, "GetConfigResponse");
@@ -3,20 +3,13 @@ use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
     from_binary, from_slice, to_binary, to_vec, Addr, Binary, Deps, DepsMut, Empty, Env,
-    MessageInfo, Response, StdError, StdResult, Uint128,
+    MessageInfo, Reply, Response, StdError, StdResult, Uint128,
 };
 
 use cw20::Cw20ReceiveMsg;
 
-use crate::hooks::{stake_hook_msgs, unstake_hook_msgs};
-use crate::msg::{
-    ExecuteMsg, GetHooksResponse, InstantiateMsg, ListStakersResponse, MigrateMsg, QueryMsg,
-    ReceiveMsg, StakedBalanceAtHeightResponse, StakedValueResponse, StakerBalanceResponse,
-    TotalStakedAtHeightResponse, TotalValueResponse,
-};
-use crate::state::{
-    Config, BALANCE, CLAIMS, CONFIG, HOOKS, MAX_CLAIMS, STAKED_BALANCES, STAKED_TOTAL,
-};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg};
+use crate::state::{Config, CONFIG, MAX_CLAIMS};
 use crate::ContractError;
 use cw2::set_contract_version;
 pub use cw20_base::allowances::{
@@ -29,30 +22,13 @@ pub use cw20_base::contract::{
     query_token_info,
 };
 pub use cw20_base::enumerable::{query_all_accounts, query_all_allowances};
-use cw_controllers::ClaimsResponse;
 use cw_utils::Duration;
+use staking_core::hooks::HOOK_REPLY_ID_OFFSET;
+use staking_core::{staking, Cw20Asset, StakingAsset};
 
 pub(crate) const CONTRACT_NAME: &str = "crates.io:cw20-stake";
 pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-fn validate_duration(duration: Option<Duration>) -> Result<(), ContractError> {
-    if let Some(unstaking_duration) = duration {
-        match unstaking_duration {
-            Duration::Height(height) => {
-                if height == 0 {
-                    return Err(ContractError::InvalidUnstakingDuration {});
-                }
-            }
-            Duration::Time(time) => {
-                if time == 0 {
-                    return Err(ContractError::InvalidUnstakingDuration {});
-                }
-            }
-        }
-    }
-    Ok(())
-}
-
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -70,21 +46,16 @@ pub fn instantiate(
         None => None,
     };
 
-    validate_duration(msg.unstaking_duration)?;
+    staking::validate_duration(msg.unstaking_duration)?;
     let config = Config {
         owner,
         manager,
         token_address: deps.api.addr_validate(&msg.token_address)?,
         unstaking_duration: msg.unstaking_duration,
+        max_claims: None,
     };
     CONFIG.save(deps.storage, &config)?;
-
-    // Initialize state to zero. We do this instead of using
-    // `unwrap_or_default` where this is used as it protects us
-    // against a scenerio where state is cleared by a bad actor and
-    // `unwrap_or_default` carries on.
-    STAKED_TOTAL.save(deps.storage, &Uint128::zero(), env.block.height)?;
-    BALANCE.save(deps.storage, &Uint128::zero())?;
+    staking::instantiate(deps.storage, &env)?;
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
@@ -101,14 +72,33 @@ pub fn execute(
     match msg {
         ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
         ExecuteMsg::Unstake { amount } => execute_unstake(deps, env, info, amount),
-        ExecuteMsg::Claim {} => execute_claim(deps, env, info),
+        ExecuteMsg::Claim { limit } => execute_claim(deps, env, info, limit),
+        ExecuteMsg::CancelUnbonding { amount } => execute_cancel_unbonding(deps, env, info, amount),
         ExecuteMsg::UpdateConfig {
             owner,
             manager,
             duration,
-        } => execute_update_config(info, deps, owner, manager, duration),
+            max_claims,
+        } => execute_update_config(info, deps, owner, manager, duration, max_claims),
         ExecuteMsg::AddHook { addr } => execute_add_hook(deps, env, info, addr),
         ExecuteMsg::RemoveHook { addr } => execute_remove_hook(deps, env, info, addr),
+        ExecuteMsg::UpdateHookGasLimit { gas_limit } => {
+            execute_update_hook_gas_limit(deps, info, gas_limit)
+        }
+        ExecuteMsg::ReplayFailedHook { id } => execute_replay_failed_hook(deps, info, id),
+        ExecuteMsg::ClearFailedHook { id } => execute_clear_failed_hook(deps, info, id),
+        ExecuteMsg::SetAcceptStakes { enabled } => execute_set_accept_stakes(deps, info, enabled),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        id if id >= HOOK_REPLY_ID_OFFSET => Ok(staking::reply_hook(deps, &env, msg)?),
+        id => Err(ContractError::Std(StdError::generic_err(format!(
+            "unknown reply id: {}",
+            id
+        )))),
     }
 }
 
@@ -118,6 +108,7 @@ pub fn execute_update_config(
     new_owner: Option<String>,
     new_manager: Option<String>,
     duration: Option<Duration>,
+    max_claims: Option<u64>,
 ) -> Result<Response, ContractError> {
     let new_owner = new_owner
         .map(|new_owner| deps.api.addr_validate(&new_owner))
@@ -133,12 +124,14 @@ pub fn execute_update_config(
         return Err(ContractError::OnlyOwnerCanChangeOwner {});
     };
 
-    validate_duration(duration)?;
+    staking::validate_duration(duration)?;
+    staking::validate_max_claims(max_claims)?;
 
     config.owner = new_owner;
     config.manager = new_manager;
 
     config.unstaking_duration = duration;
+    config.max_claims = max_claims;
 
     CONFIG.save(deps.storage, &config)?;
     Ok(Response::new()
@@ -166,17 +159,12 @@ pub fn execute_receive(
     wrapper: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    if info.sender != config.token_address {
-        return Err(ContractError::InvalidToken {
-            received: info.sender,
-            expected: config.token_address,
-        });
-    }
+    let amount = Cw20Asset(config.token_address).amount_in(&info, Some(wrapper.amount))?;
     let msg: ReceiveMsg = from_binary(&wrapper.msg)?;
     let sender = deps.api.addr_validate(&wrapper.sender)?;
     match msg {
-        ReceiveMsg::Stake {} => execute_stake(deps, env, sender, wrapper.amount),
-        ReceiveMsg::Fund {} => execute_fund(deps, env, &sender, wrapper.amount),
+        ReceiveMsg::Stake { recipient } => execute_stake(deps, env, sender, recipient, amount),
+        ReceiveMsg::Fund {} => execute_fund(deps, env, &sender, amount),
     }
 }
 
@@ -184,43 +172,27 @@ pub fn execute_stake(
     deps: DepsMut,
     env: Env,
     sender: Addr,
+    recipient: Option<String>,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
-    let balance = BALANCE.load(deps.storage)?;
-    let staked_total = STAKED_TOTAL.load(deps.storage)?;
-    let amount_to_stake = if staked_total == Uint128::zero() || balance == Uint128::zero() {
-        amount
-    } else {
-        staked_total
-            .checked_mul(amount)
-            .map_err(StdError::overflow)?
-            .checked_div(balance)
-            .map_err(StdError::divide_by_zero)?
-    };
-    STAKED_BALANCES.update(
-        deps.storage,
-        &sender,
-        env.block.height,
-        |bal| -> StdResult<Uint128> { Ok(bal.unwrap_or_default().checked_add(amount_to_stake)?) },
-    )?;
-    STAKED_TOTAL.update(
-        deps.storage,
-        env.block.height,
-        |total| -> StdResult<Uint128> {
-            // Initialized during instantiate - OK to unwrap.
-            Ok(total.unwrap().checked_add(amount_to_stake)?)
-        },
-    )?;
-    BALANCE.save(
+    let recipient = recipient
+        .map(|recipient| deps.api.addr_validate(&recipient))
+        .transpose()?;
+    Ok(staking::execute_stake(
+        deps, &env, sender, recipient, amount,
+    )?)
+}
+
+pub fn execute_set_accept_stakes(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    Ok(staking::execute_set_accept_stakes(
         deps.storage,
-        &balance.checked_add(amount).map_err(StdError::overflow)?,
-    )?;
-    let hook_msgs = stake_hook_msgs(deps.storage, sender.clone(), amount_to_stake)?;
-    Ok(Response::new()
-        .add_submessages(hook_msgs)
-        .add_attribute("action", "stake")
-        .add_attribute("from", sender)
-        .add_attribute("amount", amount))
+        info.sender,
+        enabled,
+    )?)
 }
 
 pub fn execute_unstake(
@@ -230,99 +202,45 @@ pub fn execute_unstake(
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let balance = BALANCE.load(deps.storage)?;
-    let staked_total = STAKED_TOTAL.load(deps.storage)?;
-    let amount_to_claim = amount
-        .checked_mul(balance)
-        .map_err(StdError::overflow)?
-        .checked_div(staked_total)
-        .map_err(StdError::divide_by_zero)?;
-    STAKED_BALANCES.update(
-        deps.storage,
-        &info.sender,
-        env.block.height,
-        |bal| -> StdResult<Uint128> { Ok(bal.unwrap_or_default().checked_sub(amount)?) },
-    )?;
-    STAKED_TOTAL.update(
-        deps.storage,
-        env.block.height,
-        |total| -> StdResult<Uint128> {
-            // Initialized during instantiate - OK to unwrap.
-            Ok(total.unwrap().checked_sub(amount)?)
-        },
-    )?;
-    BALANCE.save(
-        deps.storage,
-        &balance
-            .checked_sub(amount_to_claim)
-            .map_err(StdError::overflow)?,
-    )?;
-    let hook_msgs = unstake_hook_msgs(deps.storage, info.sender.clone(), amount)?;
-    match config.unstaking_duration {
-        None => {
-            let cw_send_msg = cw20::Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount: amount_to_claim,
-            };
-            let wasm_msg = cosmwasm_std::WasmMsg::Execute {
-                contract_addr: config.token_address.to_string(),
-                msg: to_binary(&cw_send_msg)?,
-                funds: vec![],
-            };
-            Ok(Response::new()
-                .add_message(wasm_msg)
-                .add_submessages(hook_msgs)
-                .add_attribute("action", "unstake")
-                .add_attribute("from", info.sender)
-                .add_attribute("amount", amount)
-                .add_attribute("claim_duration", "None"))
-        }
-        Some(duration) => {
-            let outstanding_claims = CLAIMS.query_claims(deps.as_ref(), &info.sender)?.claims;
-            if outstanding_claims.len() >= MAX_CLAIMS as usize {
-                return Err(ContractError::TooManyClaims {});
-            }
-
-            CLAIMS.create_claim(
-                deps.storage,
-                &info.sender,
-                amount_to_claim,
-                duration.after(&env.block),
-            )?;
-            Ok(Response::new()
-                .add_attribute("action", "unstake")
-                .add_submessages(hook_msgs)
-                .add_attribute("from", info.sender)
-                .add_attribute("amount", amount)
-                .add_attribute("claim_duration", format!("{}", duration)))
-        }
-    }
+    Ok(staking::execute_unstake(
+        deps,
+        &env,
+        &Cw20Asset(config.token_address),
+        info.sender,
+        amount,
+        config.unstaking_duration,
+        config.max_claims.unwrap_or(MAX_CLAIMS),
+    )?)
 }
 
 pub fn execute_claim(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
+    limit: Option<u32>,
 ) -> Result<Response, ContractError> {
-    let release = CLAIMS.claim_tokens(deps.storage, &info.sender, &_env.block, None)?;
-    if release.is_zero() {
-        return Err(ContractError::NothingToClaim {});
-    }
     let config = CONFIG.load(deps.storage)?;
-    let cw_send_msg = cw20::Cw20ExecuteMsg::Transfer {
-        recipient: info.sender.to_string(),
-        amount: release,
-    };
-    let wasm_msg = cosmwasm_std::WasmMsg::Execute {
-        contract_addr: config.token_address.to_string(),
-        msg: to_binary(&cw_send_msg)?,
-        funds: vec![],
-    };
-    Ok(Response::new()
-        .add_message(wasm_msg)
-        .add_attribute("action", "claim")
-        .add_attribute("from", info.sender)
-        .add_attribute("amount", release))
+    Ok(staking::execute_claim(
+        deps,
+        &env,
+        &Cw20Asset(config.token_address),
+        info.sender,
+        limit,
+    )?)
+}
+
+pub fn execute_cancel_unbonding(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    Ok(staking::execute_cancel_unbonding(
+        deps,
+        &env,
+        info.sender,
+        amount,
+    )?)
 }
 
 pub fn execute_fund(
@@ -331,13 +249,7 @@ pub fn execute_fund(
     sender: &Addr,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
-    BALANCE.update(deps.storage, |balance| -> StdResult<_> {
-        balance.checked_add(amount).map_err(StdError::overflow)
-    })?;
-    Ok(Response::new()
-        .add_attribute("action", "fund")
-        .add_attribute("from", sender)
-        .add_attribute("amount", amount))
+    Ok(staking::execute_fund(deps.storage, sender.clone(), amount)?)
 }
 
 pub fn execute_add_hook(
@@ -351,10 +263,7 @@ pub fn execute_add_hook(
     if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
         return Err(ContractError::Unauthorized {});
     };
-    HOOKS.add_hook(deps.storage, addr.clone())?;
-    Ok(Response::new()
-        .add_attribute("action", "add_hook")
-        .add_attribute("hook", addr))
+    Ok(staking::execute_add_hook(deps.storage, addr)?)
 }
 
 pub fn execute_remove_hook(
@@ -368,131 +277,81 @@ pub fn execute_remove_hook(
     if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
         return Err(ContractError::Unauthorized {});
     };
-    HOOKS.remove_hook(deps.storage, addr.clone())?;
-    Ok(Response::new()
-        .add_attribute("action", "remove_hook")
-        .add_attribute("hook", addr))
+    Ok(staking::execute_remove_hook(deps.storage, addr)?)
+}
+
+pub fn execute_update_hook_gas_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    gas_limit: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    };
+    Ok(staking::execute_update_hook_gas_limit(
+        deps.storage,
+        gas_limit,
+    )?)
+}
+
+pub fn execute_replay_failed_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    };
+    Ok(staking::execute_replay_failed_hook(deps.storage, id)?)
+}
+
+pub fn execute_clear_failed_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    };
+    Ok(staking::execute_clear_failed_hook(deps.storage, id)?)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
-        QueryMsg::StakedBalanceAtHeight { address, height } => {
-            to_binary(&query_staked_balance_at_height(deps, env, address, height)?)
-        }
+        QueryMsg::StakedBalanceAtHeight { address, height } => to_binary(
+            &staking::query_staked_balance_at_height(deps, &env, address, height)?,
+        ),
         QueryMsg::TotalStakedAtHeight { height } => {
-            to_binary(&query_total_staked_at_height(deps, env, height)?)
+            to_binary(&staking::query_total_staked_at_height(deps, &env, height)?)
+        }
+        QueryMsg::StakedValue { address } => {
+            to_binary(&staking::query_staked_value(deps, address)?)
+        }
+        QueryMsg::TotalValue {} => to_binary(&staking::query_total_value(deps)?),
+        QueryMsg::Claims { address } => to_binary(&staking::query_claims(deps, address)?),
+        QueryMsg::GetHooks {} => to_binary(&staking::query_hooks(deps)?),
+        QueryMsg::FailedHooks { start_after, limit } => {
+            to_binary(&staking::query_failed_hooks(deps, start_after, limit)?)
         }
-        QueryMsg::StakedValue { address } => to_binary(&query_staked_value(deps, env, address)?),
-        QueryMsg::TotalValue {} => to_binary(&query_total_value(deps, env)?),
-        QueryMsg::Claims { address } => to_binary(&query_claims(deps, address)?),
-        QueryMsg::GetHooks {} => to_binary(&query_hooks(deps)?),
         QueryMsg::ListStakers { start_after, limit } => {
-            query_list_stakers(deps, start_after, limit)
+            to_binary(&staking::query_list_stakers(deps, start_after, limit)?)
+        }
+        QueryMsg::AcceptsStakes { address } => {
+            to_binary(&staking::query_accepts_stakes(deps, address)?)
         }
     }
 }
 
-pub fn query_staked_balance_at_height(
-    deps: Deps,
-    _env: Env,
-    address: String,
-    height: Option<u64>,
-) -> StdResult<StakedBalanceAtHeightResponse> {
-    let address = deps.api.addr_validate(&address)?;
-    let height = height.unwrap_or(_env.block.height);
-    let balance = STAKED_BALANCES
-        .may_load_at_height(deps.storage, &address, height)?
-        .unwrap_or_default();
-    Ok(StakedBalanceAtHeightResponse { balance, height })
-}
-
-pub fn query_total_staked_at_height(
-    deps: Deps,
-    _env: Env,
-    height: Option<u64>,
-) -> StdResult<TotalStakedAtHeightResponse> {
-    let height = height.unwrap_or(_env.block.height);
-    let total = STAKED_TOTAL
-        .may_load_at_height(deps.storage, height)?
-        .unwrap_or_default();
-    Ok(TotalStakedAtHeightResponse { total, height })
-}
-
-pub fn query_staked_value(
-    deps: Deps,
-    _env: Env,
-    address: String,
-) -> StdResult<StakedValueResponse> {
-    let address = deps.api.addr_validate(&address)?;
-    let balance = BALANCE.load(deps.storage).unwrap_or_default();
-    let staked = STAKED_BALANCES
-        .load(deps.storage, &address)
-        .unwrap_or_default();
-    let total = STAKED_TOTAL.load(deps.storage)?;
-    if balance == Uint128::zero() || staked == Uint128::zero() || total == Uint128::zero() {
-        Ok(StakedValueResponse {
-            value: Uint128::zero(),
-        })
-    } else {
-        let value = staked
-            .checked_mul(balance)
-            .map_err(StdError::overflow)?
-            .checked_div(total)
-            .map_err(StdError::divide_by_zero)?;
-        Ok(StakedValueResponse { value })
-    }
-}
-
-pub fn query_total_value(deps: Deps, _env: Env) -> StdResult<TotalValueResponse> {
-    let balance = BALANCE.load(deps.storage)?;
-    Ok(TotalValueResponse { total: balance })
-}
-
 pub fn query_config(deps: Deps) -> StdResult<Config> {
     let config = CONFIG.load(deps.storage)?;
     Ok(config)
 }
 
-pub fn query_claims(deps: Deps, address: String) -> StdResult<ClaimsResponse> {
-    CLAIMS.query_claims(deps, &deps.api.addr_validate(&address)?)
-}
-
-pub fn query_hooks(deps: Deps) -> StdResult<GetHooksResponse> {
-    Ok(GetHooksResponse {
-        hooks: HOOKS.query_hooks(deps)?.hooks,
-    })
-}
-
-pub fn query_list_stakers(
-    deps: Deps,
-    start_after: Option<String>,
-    limit: Option<u32>,
-) -> StdResult<Binary> {
-    let start_at = start_after
-        .map(|addr| deps.api.addr_validate(&addr))
-        .transpose()?;
-
-    let stakers = cw_paginate::paginate_snapshot_map(
-        deps,
-        &STAKED_BALANCES,
-        start_at.as_ref(),
-        limit,
-        cosmwasm_std::Order::Ascending,
-    )?;
-
-    let stakers = stakers
-        .into_iter()
-        .map(|(address, balance)| StakerBalanceResponse {
-            address: address.into_string(),
-            balance,
-        })
-        .collect();
-
-    to_binary(&ListStakersResponse { stakers })
-}
-
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     use serde::{Deserialize, Serialize};
@@ -521,6 +380,7 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, Co
                     .transpose()?,
                 token_address: beta_config.token_address,
                 unstaking_duration: beta_config.unstaking_duration,
+                max_claims: None,
             };
             deps.storage.set(b"config", &to_vec(&new_config)?);
             Ok(Response::default())
@@ -1,4 +1,6 @@
-use cosmwasm_std::{Addr, StdError};
+use cosmwasm_std::StdError;
+use cw_utils::PaymentError;
+use staking_core::StakingError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -7,20 +9,16 @@ pub enum ContractError {
     Std(#[from] StdError),
     #[error("{0}")]
     Cw20Error(#[from] cw20_base::ContractError),
-    #[error("Nothing to claim")]
-    NothingToClaim {},
-    #[error("Invalid token")]
-    InvalidToken { received: Addr, expected: Addr },
+    #[error("{0}")]
+    PaymentError(#[from] PaymentError),
     #[error("Unauthorized")]
     Unauthorized {},
-    #[error("Too many outstanding claims. Claim some tokens before unstaking more.")]
-    TooManyClaims {},
     #[error("No admin configured")]
     NoAdminConfigured {},
     #[error("{0}")]
     HookError(#[from] cw_controllers::HookError),
+    #[error("{0}")]
+    Staking(#[from] StakingError),
     #[error("Only owner can change owner")]
     OnlyOwnerCanChangeOwner {},
-    #[error("Invalid unstaking duration, unstaking duration cannot be 0")]
-    InvalidUnstakingDuration {},
 }
@@ -5,7 +5,11 @@ use serde::{Deserialize, Serialize};
 
 use cw_utils::Duration;
 
-pub use cw_controllers::ClaimsResponse;
+pub use staking_core::msg::{
+    AcceptsStakesResponse, ClaimsResponse, FailedHookResponse, FailedHooksResponse,
+    GetHooksResponse, ListStakersResponse, StakedBalanceAtHeightResponse, StakedValueResponse,
+    StakerBalanceResponse, TotalStakedAtHeightResponse, TotalValueResponse,
+};
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct InstantiateMsg {
@@ -24,11 +28,19 @@ pub enum ExecuteMsg {
     Unstake {
         amount: Uint128,
     },
-    Claim {},
+    // Claims at most `limit` matured claims, or all of them.
+    Claim {
+        limit: Option<u32>,
+    },
+    // Restakes unbonding claims which have not matured yet.
+    CancelUnbonding {
+        amount: Uint128,
+    },
     UpdateConfig {
         owner: Option<String>,
         manager: Option<String>,
         duration: Option<Duration>,
+        max_claims: Option<u64>,
     },
     AddHook {
         addr: String,
@@ -36,12 +48,27 @@ pub enum ExecuteMsg {
     RemoveHook {
         addr: String,
     },
+    UpdateHookGasLimit {
+        gas_limit: Option<u64>,
+    },
+    // Resends the message of a failed hook, removing it from the failure log if it succeeds.
+    ReplayFailedHook {
+        id: u64,
+    },
+    ClearFailedHook {
+        id: u64,
+    },
+    // Opts in or out of being staked for by other addresses.
+    SetAcceptStakes {
+        enabled: bool,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReceiveMsg {
-    Stake {},
+    // Stakes for `recipient` if given, otherwise for the sender.
+    Stake { recipient: Option<String> },
     Fund {},
 }
 
@@ -64,10 +91,17 @@ pub enum QueryMsg {
         address: String,
     },
     GetHooks {},
+    FailedHooks {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     ListStakers {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    AcceptsStakes {
+        address: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -76,48 +110,3 @@ pub enum MigrateMsg {
     FromBeta { manager: Option<String> },
     FromCompatible {},
 }
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct StakedBalanceAtHeightResponse {
-    pub balance: Uint128,
-    pub height: u64,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct TotalStakedAtHeightResponse {
-    pub total: Uint128,
-    pub height: u64,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct StakedValueResponse {
-    pub value: Uint128,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct TotalValueResponse {
-    pub total: Uint128,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct GetHooksResponse {
-    pub hooks: Vec<String>,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct ListStakersResponse {
-    pub stakers: Vec<StakerBalanceResponse>,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct StakerBalanceResponse {
-    pub address: String,
-    pub balance: Uint128,
-}
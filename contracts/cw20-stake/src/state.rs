@@ -1,42 +1,23 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
-use cw_controllers::Claims;
-use cw_controllers::Hooks;
-use cw_storage_plus::{Item, SnapshotItem, SnapshotMap, Strategy};
+use cosmwasm_std::Addr;
+use cw_storage_plus::Item;
 use cw_utils::Duration;
 
+pub use staking_core::state::{
+    FailedHook, BALANCE, CLAIMS, FAILED_HOOKS, FAILED_HOOK_COUNT, HOOKS, HOOK_GAS_LIMIT,
+    MAX_CLAIMS, STAKED_BALANCES, STAKED_TOTAL,
+};
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Config {
     pub owner: Option<Addr>,
     pub manager: Option<Addr>,
     pub token_address: Addr,
     pub unstaking_duration: Option<Duration>,
+    // Outstanding claims allowed per staker, `MAX_CLAIMS` if unset.
+    pub max_claims: Option<u64>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
-
-pub const STAKED_BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
-    "staked_balances",
-    "staked_balance__checkpoints",
-    "staked_balance__changelog",
-    Strategy::EveryBlock,
-);
-
-pub const STAKED_TOTAL: SnapshotItem<Uint128> = SnapshotItem::new(
-    "total_staked",
-    "total_staked__checkpoints",
-    "total_staked__changelog",
-    Strategy::EveryBlock,
-);
-
-/// The maximum number of claims that may be outstanding.
-pub const MAX_CLAIMS: u64 = 100;
-
-pub const CLAIMS: Claims = Claims::new("claims");
-
-pub const BALANCE: Item<Uint128> = Item::new("balance");
-
-// Hooks to contracts that will receive staking and unstaking messages
-pub const HOOKS: Hooks = Hooks::new("hooks");
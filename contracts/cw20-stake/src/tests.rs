@@ -2,16 +2,20 @@ use std::borrow::BorrowMut;
 
 use crate::contract::{migrate, CONTRACT_NAME, CONTRACT_VERSION};
 use crate::msg::{
-    ExecuteMsg, ListStakersResponse, MigrateMsg, QueryMsg, ReceiveMsg,
+    ExecuteMsg, FailedHooksResponse, ListStakersResponse, MigrateMsg, QueryMsg, ReceiveMsg,
     StakedBalanceAtHeightResponse, StakedValueResponse, StakerBalanceResponse,
     TotalStakedAtHeightResponse, TotalValueResponse,
 };
 use crate::state::{Config, MAX_CLAIMS};
 use crate::ContractError;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_slice, to_binary, Addr, Empty, MessageInfo, Storage, Uint128};
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response,
+    StdError, StdResult, Storage, Uint128,
+};
 use cw20::Cw20Coin;
 use cw_utils::Duration;
+use staking_core::StakingError;
 
 use cw_multi_test::{next_block, App, AppResponse, Contract, ContractWrapper, Executor};
 
@@ -32,10 +36,24 @@ pub fn contract_staking() -> Box<dyn Contract<Empty>> {
         crate::contract::instantiate,
         crate::contract::query,
     )
-    .with_migrate(crate::contract::migrate);
+    .with_migrate(crate::contract::migrate)
+    .with_reply(crate::contract::reply);
     Box::new(contract)
 }
 
+fn failing_hook_contract() -> Box<dyn Contract<Empty>> {
+    fn execute(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+        Err(StdError::generic_err("hook failed"))
+    }
+    fn instantiate(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+    fn query(_: Deps, _: Env, _: Empty) -> StdResult<Binary> {
+        to_binary(&Empty {})
+    }
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
 pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
     let contract = ContractWrapper::new(
         cw20_base::contract::execute,
@@ -175,7 +193,7 @@ fn stake_tokens(
     let msg = cw20::Cw20ExecuteMsg::Send {
         contract: staking_addr.to_string(),
         amount,
-        msg: to_binary(&ReceiveMsg::Stake {}).unwrap(),
+        msg: to_binary(&ReceiveMsg::Stake { recipient: None }).unwrap(),
     };
     app.execute_contract(info.sender, cw20_addr.clone(), &msg, &[])
 }
@@ -192,6 +210,7 @@ fn update_config(
         owner: owner.map(|a| a.to_string()),
         manager: manager.map(|a| a.to_string()),
         duration,
+        max_claims: None,
     };
     app.execute_contract(info.sender, staking_addr.clone(), &msg, &[])
 }
@@ -207,7 +226,7 @@ fn unstake_tokens(
 }
 
 fn claim_tokens(app: &mut App, staking_addr: &Addr, info: MessageInfo) -> AnyResult<AppResponse> {
-    let msg = ExecuteMsg::Claim {};
+    let msg = ExecuteMsg::Claim { limit: None };
     app.execute_contract(info.sender, staking_addr.clone(), &msg, &[])
 }
 
@@ -344,7 +363,10 @@ fn test_update_config() {
     .unwrap_err()
     .downcast()
     .unwrap();
-    assert_eq!(err, ContractError::InvalidUnstakingDuration {});
+    assert_eq!(
+        err,
+        ContractError::Staking(StakingError::InvalidUnstakingDuration {})
+    );
 
     // Remove owner
     let info = mock_info("owner2", &[]);
@@ -554,9 +576,11 @@ fn text_max_claims() {
     let info = mock_info(ADDR1, &[]);
     stake_tokens(&mut app, &staking_addr, &cw20_addr, info.clone(), amount1).unwrap();
 
-    // Create the max number of claims
+    // Create the max number of claims. Claims released in the same block are
+    // merged, so each one is made in its own block.
     for _ in 0..MAX_CLAIMS {
         unstake_tokens(&mut app, &staking_addr, info.clone(), Uint128::new(1)).unwrap();
+        app.update_block(next_block);
     }
 
     // Additional unstaking attempts ought to fail.
@@ -574,6 +598,82 @@ fn text_max_claims() {
     assert_eq!(get_balance(&app, &cw20_addr, ADDR1), amount1);
 }
 
+#[test]
+fn test_claim_merging_and_limit() {
+    let mut app = mock_app();
+    let amount1 = Uint128::from(100u128);
+    let initial_balances = vec![Cw20Coin {
+        address: ADDR1.to_string(),
+        amount: amount1,
+    }];
+    let (staking_addr, cw20_addr) =
+        setup_test_case(&mut app, initial_balances, Some(Duration::Height(2)));
+    let info = mock_info(ADDR1, &[]);
+    stake_tokens(&mut app, &staking_addr, &cw20_addr, info.clone(), amount1).unwrap();
+
+    // Unstakes in the same block release together and are merged
+    unstake_tokens(&mut app, &staking_addr, info.clone(), Uint128::new(1)).unwrap();
+    unstake_tokens(&mut app, &staking_addr, info.clone(), Uint128::new(2)).unwrap();
+    let claims = query_claims(&app, &staking_addr, ADDR1);
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0].amount, Uint128::new(3));
+
+    // The owner can lower the number of outstanding claims allowed
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked("owner"),
+            staking_addr.clone(),
+            &ExecuteMsg::UpdateConfig {
+                owner: Some("owner".to_string()),
+                manager: Some("manager".to_string()),
+                duration: Some(Duration::Height(2)),
+                max_claims: Some(0),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Staking(StakingError::InvalidMaxClaims {})
+    );
+    app.execute_contract(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateConfig {
+            owner: Some("owner".to_string()),
+            manager: Some("manager".to_string()),
+            duration: Some(Duration::Height(2)),
+            max_claims: Some(2),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_config(&app, &staking_addr).max_claims, Some(2));
+
+    app.update_block(next_block);
+    unstake_tokens(&mut app, &staking_addr, info.clone(), Uint128::new(4)).unwrap();
+    app.update_block(next_block);
+    let err: ContractError = unstake_tokens(&mut app, &staking_addr, info.clone(), Uint128::new(1))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Staking(StakingError::TooManyClaims {}));
+
+    // Both claims have matured, but only one is claimed at a time
+    app.update_block(next_block);
+    let msg = ExecuteMsg::Claim { limit: Some(1) };
+    app.execute_contract(info.sender.clone(), staking_addr.clone(), &msg, &[])
+        .unwrap();
+    assert_eq!(get_balance(&app, &cw20_addr, ADDR1), Uint128::new(3));
+    let claims = query_claims(&app, &staking_addr, ADDR1);
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0].amount, Uint128::new(4));
+    claim_tokens(&mut app, &staking_addr, info).unwrap();
+    assert_eq!(get_balance(&app, &cw20_addr, ADDR1), Uint128::new(7));
+}
+
 #[test]
 fn test_unstaking_with_claims() {
     let _deps = mock_dependencies();
@@ -629,7 +729,10 @@ fn test_unstaking_with_claims() {
         .unwrap_err()
         .downcast()
         .unwrap();
-    assert_eq!(_err, ContractError::NothingToClaim {});
+    assert_eq!(
+        _err,
+        ContractError::Staking(StakingError::NothingToClaim {})
+    );
 
     // Successful claim
     app.update_block(|b| b.height += unstaking_blocks);
@@ -1212,3 +1315,85 @@ pub fn test_migrate_update_version() {
     assert_eq!(version.version, CONTRACT_VERSION);
     assert_eq!(version.contract, CONTRACT_NAME);
 }
+
+#[test]
+fn test_failed_hooks() {
+    let mut app = mock_app();
+    let initial_balances = vec![Cw20Coin {
+        address: ADDR1.to_string(),
+        amount: Uint128::new(1000),
+    }];
+    let (staking_addr, cw20_addr) = setup_test_case(&mut app, initial_balances, None);
+    let hook_id = app.store_code(failing_hook_contract());
+    let hook_addr = app
+        .instantiate_contract(
+            hook_id,
+            Addr::unchecked("owner"),
+            &Empty {},
+            &[],
+            "hook",
+            None,
+        )
+        .unwrap();
+    app.execute_contract(
+        Addr::unchecked("manager"),
+        staking_addr.clone(),
+        &ExecuteMsg::AddHook {
+            addr: hook_addr.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Staking goes through despite the failing hook
+    let info = mock_info(ADDR1, &[]);
+    stake_tokens(&mut app, &staking_addr, &cw20_addr, info, Uint128::new(100)).unwrap();
+    app.update_block(next_block);
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR1),
+        Uint128::new(100)
+    );
+
+    let failed: FailedHooksResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::FailedHooks {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(failed.failed_hooks.len(), 1);
+    assert_eq!(failed.failed_hooks[0].hook, hook_addr.to_string());
+    assert!(failed.failed_hooks[0].error.contains("hook failed"));
+
+    // The broken hook can be removed along with its failure
+    app.execute_contract(
+        Addr::unchecked("manager"),
+        staking_addr.clone(),
+        &ExecuteMsg::RemoveHook {
+            addr: hook_addr.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::ClearFailedHook { id: 0 },
+        &[],
+    )
+    .unwrap();
+    let failed: FailedHooksResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::FailedHooks {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(failed.failed_hooks.is_empty());
+}
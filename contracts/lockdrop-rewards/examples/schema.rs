@@ -4,7 +4,8 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
 use lockdrop_rewards::msg::{
-    ExecuteMsg, InfoResponse, InstantiateMsg, PendingRewardsResponse, QueryMsg,
+    AprResponse, ClaimedRewardsResponse, ExecuteMsg, InfoResponse, InstantiateMsg,
+    PendingRewardsResponse, QueryMsg, RewardTotalsResponse, UndistributedResponse, VestingResponse,
 };
 
 fn main() {
@@ -18,6 +19,10 @@ fn main() {
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(InfoResponse), &out_dir);
     export_schema(&schema_for!(PendingRewardsResponse), &out_dir);
+    export_schema(&schema_for!(VestingResponse), &out_dir);
+    export_schema(&schema_for!(RewardTotalsResponse), &out_dir);
+    export_schema(&schema_for!(UndistributedResponse), &out_dir);
+    export_schema(&schema_for!(AprResponse), &out_dir);
 
     // Auto TS code generation expects the query return type as QueryNameResponse
     // Here we map query resonses to the correct name
@@ -26,4 +31,14 @@ fn main() {
         &out_dir,
         "GetPendingRewardsResponse",
     );
+    export_schema_with_title(
+        &schema_for!(VestingResponse),
+        &out_dir,
+        "GetVestingResponse",
+    );
+    export_schema_with_title(
+        &schema_for!(ClaimedRewardsResponse),
+        &out_dir,
+        "GetClaimedRewardsResponse",
+    );
 }
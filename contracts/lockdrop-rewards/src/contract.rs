@@ -1,10 +1,13 @@
 use crate::msg::{
-    ExecuteMsg, InfoResponse, InstantiateMsg, MigrateMsg, PendingRewardsResponse, QueryMsg,
-    ReceiveMsg,
+    AprResponse, ClaimedRewardsResponse, ExecuteMsg, InfoResponse, InstantiateMsg, MigrateMsg,
+    PendingRewardsResponse, QueryMsg, ReceiveMsg, RewardTotalsResponse, UndistributedResponse,
+    VestingResponse,
 };
 use crate::state::{
-    Config, RewardConfig, CONFIG, LAST_UPDATE_BLOCK, PENDING_REWARDS, REWARD_CONFIG,
-    REWARD_PER_TOKEN, USER_REWARD_PER_TOKEN,
+    Config, RewardConfig, RewardTotals, VestingConfig, VestingPosition, CLAIMED_REWARDS, CONFIG,
+    LAST_UPDATE_BLOCK, PENDING_REWARDS, REWARD_CONFIG, REWARD_PER_TOKEN, REWARD_TOTALS,
+    SWEEP_GRACE_PERIOD, UNLOCKED_REWARDS, USER_REWARD_PER_TOKEN, VESTING_CONFIG, VESTING_POSITIONS,
+    WITHDRAWN_REWARDS,
 };
 use crate::ContractError;
 use crate::ContractError::{
@@ -14,20 +17,29 @@ use crate::ContractError::{
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Empty, Env,
-    MessageInfo, Response, StdError, StdResult, Uint128, Uint256, WasmMsg,
+    from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Empty,
+    Env, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128, Uint256, WasmMsg,
 };
 use cw2::set_contract_version;
 use cw20::{Cw20ReceiveMsg, Denom};
-use cw20_stake::hooks::StakeChangedHookMsg;
+use staking_core::hooks::StakeChangedHookMsg;
 
 use cw20::Denom::Cw20;
-use std::cmp::min;
+use std::cmp::{max, min};
 use std::convert::TryInto;
 
 const CONTRACT_NAME: &str = "crates.io:lockdrop-rewards";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Roughly one block every six seconds.
+const DEFAULT_BLOCKS_PER_YEAR: u64 = 5_256_000;
+// Roughly a week of blocks.
+const DEFAULT_SWEEP_GRACE_PERIOD: u64 = 100_800;
+// Claims are merged into vesting positions starting every
+// `duration / MAX_VESTING_POSITIONS` blocks, bounding the positions still
+// vesting per staker.
+const MAX_VESTING_POSITIONS: u64 = 32;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -52,10 +64,22 @@ pub fn instantiate(
         return Err(ContractError::ZeroRewardDuration {});
     }
 
+    SWEEP_GRACE_PERIOD.save(
+        deps.storage,
+        &msg.sweep_grace_period.unwrap_or(DEFAULT_SWEEP_GRACE_PERIOD),
+    )?;
+
+    if let Some(vesting) = &msg.vesting {
+        if vesting.cliff > vesting.duration {
+            return Err(ContractError::InvalidVestingConfig {});
+        }
+        VESTING_CONFIG.save(deps.storage, vesting)?;
+    }
+
     // Verify contract provided is a staking contract
-    let _: cw20_stake::msg::TotalStakedAtHeightResponse = deps.querier.query_wasm_smart(
+    let _: staking_core::msg::TotalStakedAtHeightResponse = deps.querier.query_wasm_smart(
         &msg.staking_contract,
-        &cw20_stake::msg::QueryMsg::TotalStakedAtHeight { height: None },
+        &staking_core::msg::StakingQueryMsg::TotalStakedAtHeight { height: None },
     )?;
 
     let config = Config {
@@ -99,13 +123,42 @@ pub fn instantiate(
         )
         .add_attribute("reward_rate", reward_config.reward_rate)
         .add_attribute("period_finish", reward_config.period_finish.to_string())
-        .add_attribute("reward_duration", reward_config.reward_duration.to_string()))
+        .add_attribute("reward_duration", reward_config.reward_duration.to_string())
+        .add_attribute(
+            "vesting",
+            msg.vesting
+                .map(|v| format!("cliff: {}, duration: {}", v.cliff, v.duration))
+                .unwrap_or_else(|| "None".to_string()),
+        ))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     // Set contract to version to latest
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // Contracts deployed before reward totals were tracked count only the
+    // rewards still to be emitted in the current period as funded. Leftovers
+    // from earlier periods can not be swept, but nothing owed to stakers can
+    // be either.
+    if REWARD_TOTALS.may_load(deps.storage)?.is_none() {
+        if let Some(reward_config) = REWARD_CONFIG.may_load(deps.storage)? {
+            let last_update_block = LAST_UPDATE_BLOCK
+                .may_load(deps.storage)?
+                .unwrap_or_default();
+            let blocks_left = reward_config
+                .period_finish
+                .saturating_sub(last_update_block);
+            let totals = RewardTotals {
+                funded: reward_config
+                    .reward_rate
+                    .checked_mul(Uint128::from(blocks_left))
+                    .map_err(StdError::overflow)?,
+                ..RewardTotals::default()
+            };
+            REWARD_TOTALS.save(deps.storage, &totals)?;
+        }
+    }
     Ok(Response::default())
 }
 
@@ -119,6 +172,11 @@ pub fn execute(
     match msg {
         ExecuteMsg::StakeChangeHook(msg) => execute_stake_changed(deps, env, info, msg),
         ExecuteMsg::Claim {} => execute_claim(deps, env, info),
+        ExecuteMsg::ClaimFor { address, recipient } => {
+            execute_claim_for(deps, env, info, address, recipient)
+        }
+        ExecuteMsg::WithdrawVested {} => execute_withdraw_vested(deps, env, info),
+        ExecuteMsg::Sweep { recipient } => execute_sweep(deps, env, info, recipient),
         ExecuteMsg::Fund {} => execute_fund_native(deps, env, info),
         ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
         ExecuteMsg::UpdateRewardDuration { new_duration } => {
@@ -197,6 +255,10 @@ pub fn execute_fund(
 
     REWARD_CONFIG.save(deps.storage, &new_reward_config)?;
     LAST_UPDATE_BLOCK.save(deps.storage, &env.block.height)?;
+    update_reward_totals(deps.storage, |totals| {
+        totals.funded = totals.funded.checked_add(amount)?;
+        Ok(())
+    })?;
 
     Ok(Response::new()
         .add_attribute("action", "fund")
@@ -239,26 +301,251 @@ pub fn execute_unstake(
 }
 
 pub fn execute_claim(
-    mut deps: DepsMut,
+    deps: DepsMut,
     env: Env,
     info: MessageInfo,
 ) -> Result<Response<Empty>, ContractError> {
-    update_rewards(&mut deps, &env, &info.sender)?;
+    claim_rewards(deps, env, info.sender.clone(), info.sender)
+}
+
+/// Claims the pending rewards of `address` on their behalf, which only the
+/// staking contract may do. Rewards are delivered to `address` unless a
+/// `recipient` is given, which the staking contract uses to restake them.
+pub fn execute_claim_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    recipient: Option<String>,
+) -> Result<Response<Empty>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.staking_contract {
+        return Err(ContractError::Unauthorized {});
+    };
+    let address = deps.api.addr_validate(&address)?;
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => address.clone(),
+    };
+    claim_rewards(deps, env, address, recipient)
+}
+
+fn claim_rewards(
+    mut deps: DepsMut,
+    env: Env,
+    staker: Addr,
+    recipient: Addr,
+) -> Result<Response<Empty>, ContractError> {
+    update_rewards(&mut deps, &env, &staker)?;
     let rewards = PENDING_REWARDS
-        .load(deps.storage, info.sender.clone())
+        .load(deps.storage, staker.clone())
         .map_err(|_| NoRewardsClaimable {})?;
     if rewards == Uint128::zero() {
         return Err(ContractError::NoRewardsClaimable {});
     }
-    PENDING_REWARDS.save(deps.storage, info.sender.clone(), &Uint128::zero())?;
+    PENDING_REWARDS.save(deps.storage, staker.clone(), &Uint128::zero())?;
+    CLAIMED_REWARDS.update::<_, StdError>(deps.storage, staker.clone(), |claimed| {
+        Ok(claimed.unwrap_or_default().checked_add(rewards)?)
+    })?;
+    update_reward_totals(deps.storage, |totals| {
+        totals.claimed = totals.claimed.checked_add(rewards)?;
+        Ok(())
+    })?;
+
+    // With vesting enabled claimed rewards are locked up in a position and
+    // released through `WithdrawVested`. Rewards redirected by the staking
+    // contract are paid out in full; the lockdrop only redirects claims for
+    // compounding, which it refuses while vesting is configured.
+    if staker == recipient {
+        if let Some(vesting) = VESTING_CONFIG.may_load(deps.storage)? {
+            add_vesting_position(deps.storage, &vesting, &staker, env.block.height, rewards)?;
+            return Ok(Response::new()
+                .add_attribute("action", "claim")
+                .add_attribute("recipient", recipient)
+                .add_attribute("amount", rewards)
+                .add_attribute("vesting", "true"));
+        }
+    }
+
     let config = CONFIG.load(deps.storage)?;
-    let transfer_msg = get_transfer_msg(info.sender, rewards, config.reward_token)?;
+    let transfer_msg = get_transfer_msg(recipient.clone(), rewards, config.reward_token)?;
     Ok(Response::new()
         .add_message(transfer_msg)
         .add_attribute("action", "claim")
+        .add_attribute("staker", staker)
+        .add_attribute("recipient", recipient)
         .add_attribute("amount", rewards))
 }
 
+pub fn execute_withdraw_vested(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<Empty>, ContractError> {
+    let vesting = match VESTING_CONFIG.may_load(deps.storage)? {
+        Some(vesting) => vesting,
+        None => return Err(ContractError::NoVestedRewards {}),
+    };
+    let positions = load_vesting_positions(deps.storage, &info.sender)?;
+
+    let mut amount = UNLOCKED_REWARDS
+        .may_load(deps.storage, info.sender.clone())?
+        .unwrap_or_default();
+    UNLOCKED_REWARDS.remove(deps.storage, info.sender.clone());
+    for (claimed_at, mut position) in positions {
+        let vested = vested_amount(&vesting, claimed_at, &position, env.block.height);
+        let withdrawable = vested
+            .checked_sub(position.withdrawn)
+            .map_err(StdError::overflow)?;
+        if withdrawable.is_zero() {
+            continue;
+        }
+        amount = amount
+            .checked_add(withdrawable)
+            .map_err(StdError::overflow)?;
+        position.withdrawn = vested;
+        let key = (info.sender.clone(), claimed_at);
+        if position.withdrawn == position.amount {
+            VESTING_POSITIONS.remove(deps.storage, key);
+        } else {
+            VESTING_POSITIONS.save(deps.storage, key, &position)?;
+        }
+    }
+    if amount.is_zero() {
+        return Err(ContractError::NoVestedRewards {});
+    }
+
+    WITHDRAWN_REWARDS.update::<_, StdError>(deps.storage, info.sender.clone(), |withdrawn| {
+        Ok(withdrawn.unwrap_or_default().checked_add(amount)?)
+    })?;
+    let config = CONFIG.load(deps.storage)?;
+    let transfer_msg = get_transfer_msg(info.sender, amount, config.reward_token)?;
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_attribute("action", "withdraw_vested")
+        .add_attribute("amount", amount))
+}
+
+/// Sends rewards which were funded but never accrued to any staker to
+/// `recipient`. Rewards already accrued to stakers, claimed or not, are never
+/// swept as they are counted as distributed. Unclaimed rewards are owed to
+/// their stakers, so they stay claimable rather than becoming sweepable.
+pub fn execute_sweep(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+) -> Result<Response<Empty>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if Some(info.sender) != config.owner {
+        return Err(ContractError::Unauthorized {});
+    };
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    let sweepable_at = get_sweepable_at(deps.storage)?;
+    if env.block.height < sweepable_at {
+        return Err(ContractError::SweepNotAvailable { sweepable_at });
+    }
+
+    update_reward_per_token(&mut deps, &env)?;
+    let totals = REWARD_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+    let amount = get_undistributed(&totals)?;
+    if amount.is_zero() {
+        return Err(ContractError::NothingToSweep {});
+    }
+    update_reward_totals(deps.storage, |totals| {
+        totals.swept = totals.swept.checked_add(amount)?;
+        Ok(())
+    })?;
+
+    let transfer_msg = get_transfer_msg(recipient.clone(), amount, config.reward_token)?;
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_attribute("action", "sweep")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount))
+}
+
+fn get_sweepable_at(storage: &dyn Storage) -> StdResult<u64> {
+    let reward_config = REWARD_CONFIG.load(storage)?;
+    let grace_period = SWEEP_GRACE_PERIOD
+        .may_load(storage)?
+        .unwrap_or(DEFAULT_SWEEP_GRACE_PERIOD);
+    Ok(reward_config.period_finish + grace_period)
+}
+
+fn get_undistributed(totals: &RewardTotals) -> StdResult<Uint128> {
+    Ok(totals
+        .funded
+        .checked_sub(totals.distributed)?
+        .checked_sub(totals.swept)?)
+}
+
+/// Locks `amount` up in the staker's vesting position starting at the next
+/// multiple of the position length, so claims within one length share a
+/// position and vest at most one length late. Positions fully vested at
+/// `height` are folded into `UNLOCKED_REWARDS`.
+fn add_vesting_position(
+    storage: &mut dyn Storage,
+    vesting: &VestingConfig,
+    addr: &Addr,
+    height: u64,
+    amount: Uint128,
+) -> StdResult<()> {
+    let length = max(1, vesting.duration / MAX_VESTING_POSITIONS);
+    let start = (height + length - 1) / length * length;
+    VESTING_POSITIONS.update::<_, StdError>(storage, (addr.clone(), start), |position| {
+        let mut position = position.unwrap_or(VestingPosition {
+            amount: Uint128::zero(),
+            withdrawn: Uint128::zero(),
+        });
+        position.amount = position.amount.checked_add(amount)?;
+        Ok(position)
+    })?;
+
+    let mut unlocked = Uint128::zero();
+    for (claimed_at, position) in load_vesting_positions(storage, addr)? {
+        if vested_amount(vesting, claimed_at, &position, height) == position.amount {
+            unlocked = unlocked.checked_add(position.amount.checked_sub(position.withdrawn)?)?;
+            VESTING_POSITIONS.remove(storage, (addr.clone(), claimed_at));
+        }
+    }
+    if !unlocked.is_zero() {
+        UNLOCKED_REWARDS.update::<_, StdError>(storage, addr.clone(), |total| {
+            Ok(total.unwrap_or_default().checked_add(unlocked)?)
+        })?;
+    }
+    Ok(())
+}
+
+fn load_vesting_positions(
+    storage: &dyn Storage,
+    addr: &Addr,
+) -> StdResult<Vec<(u64, VestingPosition)>> {
+    VESTING_POSITIONS
+        .prefix(addr.clone())
+        .range(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+/// Amount of a position unlocked at `height`. Nothing unlocks before the
+/// cliff, after which the position unlocks linearly over the duration.
+fn vested_amount(
+    vesting: &VestingConfig,
+    claimed_at: u64,
+    position: &VestingPosition,
+    height: u64,
+) -> Uint128 {
+    let elapsed = height.saturating_sub(claimed_at);
+    if elapsed < vesting.cliff {
+        Uint128::zero()
+    } else if elapsed >= vesting.duration {
+        position.amount
+    } else {
+        position.amount.multiply_ratio(elapsed, vesting.duration)
+    }
+}
+
 pub fn get_transfer_msg(recipient: Addr, amount: Uint128, denom: Denom) -> StdResult<CosmosMsg> {
     match denom {
         Denom::Native(denom) => Ok(BankMsg::Send {
@@ -283,8 +570,7 @@ pub fn get_transfer_msg(recipient: Addr, amount: Uint128, denom: Denom) -> StdRe
 
 pub fn update_rewards(deps: &mut DepsMut, env: &Env, addr: &Addr) -> StdResult<()> {
     let config = CONFIG.load(deps.storage)?;
-    let reward_per_token = get_reward_per_token(deps.as_ref(), env, &config.staking_contract)?;
-    REWARD_PER_TOKEN.save(deps.storage, &reward_per_token)?;
+    let reward_per_token = update_reward_per_token(deps, env)?;
 
     let earned_rewards = get_rewards_earned(
         deps.as_ref(),
@@ -298,9 +584,25 @@ pub fn update_rewards(deps: &mut DepsMut, env: &Env, addr: &Addr) -> StdResult<(
     })?;
 
     USER_REWARD_PER_TOKEN.save(deps.storage, addr.clone(), &reward_per_token)?;
+    Ok(())
+}
+
+/// Brings the global reward per token and distributed total up to date.
+fn update_reward_per_token(deps: &mut DepsMut, env: &Env) -> StdResult<Uint256> {
+    let config = CONFIG.load(deps.storage)?;
+    let reward_per_token = get_reward_per_token(deps.as_ref(), env, &config.staking_contract)?;
+    REWARD_PER_TOKEN.save(deps.storage, &reward_per_token)?;
+
+    // Rewards only accrue to stakers while something is staked
+    let distributed = get_rewards_distributed(deps.as_ref(), env, &config.staking_contract)?;
+    update_reward_totals(deps.storage, |totals| {
+        totals.distributed = totals.distributed.checked_add(distributed)?;
+        Ok(())
+    })?;
+
     let last_time_reward_applicable = get_last_time_reward_applicable(deps.as_ref(), env)?;
     LAST_UPDATE_BLOCK.save(deps.storage, &last_time_reward_applicable)?;
-    Ok(())
+    Ok(reward_per_token)
 }
 
 pub fn get_reward_per_token(deps: Deps, env: &Env, staking_contract: &Addr) -> StdResult<Uint256> {
@@ -327,6 +629,30 @@ pub fn get_reward_per_token(deps: Deps, env: &Env, staking_contract: &Addr) -> S
     Ok(prev_reward_per_token + additional_reward_per_token)
 }
 
+/// Rewards accrued to all stakers since the last update.
+fn get_rewards_distributed(deps: Deps, env: &Env, staking_contract: &Addr) -> StdResult<Uint128> {
+    let total_staked = get_total_staked(deps, staking_contract)?;
+    if total_staked.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    let reward_config = REWARD_CONFIG.load(deps.storage)?;
+    let last_time_reward_applicable = get_last_time_reward_applicable(deps, env)?;
+    let last_update_block = LAST_UPDATE_BLOCK.load(deps.storage).unwrap_or_default();
+    Ok(reward_config.reward_rate.checked_mul(Uint128::from(
+        last_time_reward_applicable - last_update_block,
+    ))?)
+}
+
+fn update_reward_totals<F>(storage: &mut dyn Storage, action: F) -> StdResult<RewardTotals>
+where
+    F: FnOnce(&mut RewardTotals) -> StdResult<()>,
+{
+    let mut totals = REWARD_TOTALS.may_load(storage)?.unwrap_or_default();
+    action(&mut totals)?;
+    REWARD_TOTALS.save(storage, &totals)?;
+    Ok(totals)
+}
+
 pub fn get_rewards_earned(
     deps: Deps,
     _env: &Env,
@@ -351,19 +677,23 @@ fn get_last_time_reward_applicable(deps: Deps, env: &Env) -> StdResult<u64> {
     Ok(min(env.block.height, reward_config.period_finish))
 }
 
+// With no height the staking contract reads its snapshots at the current
+// height, which hold the values from before any change made in this block, so
+// the hook for a stake change already accrues the elapsed interval at the
+// pre-change amounts. No rewards accrue within a block, so this is exact.
 fn get_total_staked(deps: Deps, contract_addr: &Addr) -> StdResult<Uint128> {
-    let msg = cw20_stake::msg::QueryMsg::TotalStakedAtHeight { height: None };
-    let resp: cw20_stake::msg::TotalStakedAtHeightResponse =
+    let msg = staking_core::msg::StakingQueryMsg::TotalStakedAtHeight { height: None };
+    let resp: staking_core::msg::TotalStakedAtHeightResponse =
         deps.querier.query_wasm_smart(contract_addr, &msg)?;
     Ok(resp.total)
 }
 
 fn get_staked_balance(deps: Deps, contract_addr: &Addr, addr: &Addr) -> StdResult<Uint128> {
-    let msg = cw20_stake::msg::QueryMsg::StakedBalanceAtHeight {
+    let msg = staking_core::msg::StakingQueryMsg::StakedBalanceAtHeight {
         address: addr.into(),
         height: None,
     };
-    let resp: cw20_stake::msg::StakedBalanceAtHeightResponse =
+    let resp: staking_core::msg::StakedBalanceAtHeightResponse =
         deps.querier.query_wasm_smart(contract_addr, &msg)?;
     Ok(resp.balance)
 }
@@ -473,13 +803,137 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetPendingRewards { address } => {
             Ok(to_binary(&query_pending_rewards(deps, env, address)?)?)
         }
+        QueryMsg::GetVesting { address } => Ok(to_binary(&query_vesting(deps, env, address)?)?),
+        QueryMsg::GetClaimedRewards { address } => {
+            Ok(to_binary(&query_claimed_rewards(deps, address)?)?)
+        }
+        QueryMsg::RewardTotals {} => Ok(to_binary(&query_reward_totals(deps, env)?)?),
+        QueryMsg::Undistributed {} => Ok(to_binary(&query_undistributed(deps, env)?)?),
+        QueryMsg::Apr {
+            price_hint,
+            blocks_per_year,
+        } => Ok(to_binary(&query_apr(
+            deps,
+            env,
+            price_hint,
+            blocks_per_year,
+        )?)?),
     }
 }
 
 pub fn query_info(deps: Deps, _env: Env) -> StdResult<InfoResponse> {
     let config = CONFIG.load(deps.storage)?;
     let reward = REWARD_CONFIG.load(deps.storage)?;
-    Ok(InfoResponse { config, reward })
+    let vesting = VESTING_CONFIG.may_load(deps.storage)?;
+    Ok(InfoResponse {
+        config,
+        reward,
+        vesting,
+    })
+}
+
+pub fn query_claimed_rewards(deps: Deps, addr: String) -> StdResult<ClaimedRewardsResponse> {
+    let addr = deps.api.addr_validate(&addr)?;
+    let config = CONFIG.load(deps.storage)?;
+    let claimed = CLAIMED_REWARDS
+        .may_load(deps.storage, addr.clone())?
+        .unwrap_or_default();
+    Ok(ClaimedRewardsResponse {
+        address: addr.into_string(),
+        claimed,
+        denom: config.reward_token,
+    })
+}
+
+pub fn query_reward_totals(deps: Deps, env: Env) -> StdResult<RewardTotalsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut totals = REWARD_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+    // include rewards accrued since the last update
+    totals.distributed = totals.distributed.checked_add(get_rewards_distributed(
+        deps,
+        &env,
+        &config.staking_contract,
+    )?)?;
+    Ok(RewardTotalsResponse {
+        totals,
+        denom: config.reward_token,
+    })
+}
+
+pub fn query_undistributed(deps: Deps, env: Env) -> StdResult<UndistributedResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let totals = query_reward_totals(deps, env)?.totals;
+    Ok(UndistributedResponse {
+        amount: get_undistributed(&totals)?,
+        denom: config.reward_token,
+        sweepable_at: get_sweepable_at(deps.storage)?,
+    })
+}
+
+pub fn query_apr(
+    deps: Deps,
+    env: Env,
+    price_hint: Option<Decimal>,
+    blocks_per_year: Option<u64>,
+) -> StdResult<AprResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let reward_config = REWARD_CONFIG.load(deps.storage)?;
+    let total_staked = get_total_staked(deps, &config.staking_contract)?;
+    let blocks_per_year = blocks_per_year.unwrap_or(DEFAULT_BLOCKS_PER_YEAR);
+
+    let reward_rate = if env.block.height < reward_config.period_finish {
+        reward_config.reward_rate
+    } else {
+        Uint128::zero()
+    };
+    let (emission_per_staked_token, yearly_emission_per_staked_token) = if total_staked.is_zero() {
+        (Decimal::zero(), Decimal::zero())
+    } else {
+        let yearly_rewards = reward_rate.checked_mul(Uint128::from(blocks_per_year))?;
+        (
+            Decimal::from_ratio(reward_rate, total_staked),
+            Decimal::from_ratio(yearly_rewards, total_staked),
+        )
+    };
+    let apr = price_hint
+        .map(|price| yearly_emission_per_staked_token.checked_mul(price))
+        .transpose()?;
+
+    Ok(AprResponse {
+        reward_rate,
+        total_staked,
+        emission_per_staked_token,
+        blocks_per_year,
+        apr,
+    })
+}
+
+pub fn query_vesting(deps: Deps, env: Env, addr: String) -> StdResult<VestingResponse> {
+    let addr = deps.api.addr_validate(&addr)?;
+    let config = CONFIG.load(deps.storage)?;
+    let withdrawn = WITHDRAWN_REWARDS
+        .may_load(deps.storage, addr.clone())?
+        .unwrap_or_default();
+
+    let mut vested = UNLOCKED_REWARDS
+        .may_load(deps.storage, addr.clone())?
+        .unwrap_or_default();
+    let mut unvested = Uint128::zero();
+    if let Some(vesting) = VESTING_CONFIG.may_load(deps.storage)? {
+        for (claimed_at, position) in load_vesting_positions(deps.storage, &addr)? {
+            let position_vested = vested_amount(&vesting, claimed_at, &position, env.block.height);
+            vested += position_vested.checked_sub(position.withdrawn)?;
+            unvested += position.amount.checked_sub(position_vested)?;
+        }
+    }
+
+    Ok(VestingResponse {
+        address: addr.into_string(),
+        vested,
+        unvested,
+        withdrawn,
+        denom: config.reward_token,
+    })
 }
 
 pub fn query_pending_rewards(
@@ -515,7 +969,10 @@ mod tests {
     use std::borrow::BorrowMut;
 
     use crate::{
-        contract::{migrate, CONTRACT_NAME, CONTRACT_VERSION},
+        contract::{
+            add_vesting_position, get_undistributed, load_vesting_positions, migrate,
+            CONTRACT_NAME, CONTRACT_VERSION, MAX_VESTING_POSITIONS,
+        },
         msg::MigrateMsg,
         ContractError,
     };
@@ -523,14 +980,21 @@ mod tests {
     use cosmwasm_std::{
         coin,
         testing::{mock_dependencies, mock_env},
-        to_binary, Addr, Empty, Uint128,
+        to_binary, Addr, Decimal, Empty, Uint128,
     };
     use cw20::{Cw20Coin, Cw20ExecuteMsg, Denom};
     use cw_utils::Duration;
 
     use cw_multi_test::{next_block, App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
 
-    use crate::msg::{ExecuteMsg, InfoResponse, PendingRewardsResponse, QueryMsg, ReceiveMsg};
+    use crate::msg::{
+        AprResponse, ClaimedRewardsResponse, ExecuteMsg, InfoResponse, PendingRewardsResponse,
+        QueryMsg, ReceiveMsg, RewardTotalsResponse, UndistributedResponse, VestingResponse,
+    };
+    use crate::state::{
+        RewardConfig, RewardTotals, VestingConfig, LAST_UPDATE_BLOCK, REWARD_CONFIG, REWARD_TOTALS,
+        UNLOCKED_REWARDS,
+    };
 
     const OWNER: &str = "owner";
     const MANAGER: &str = "manager";
@@ -552,7 +1016,8 @@ mod tests {
             cw20_stake::contract::execute,
             cw20_stake::contract::instantiate,
             cw20_stake::contract::query,
-        );
+        )
+        .with_reply(cw20_stake::contract::reply);
         Box::new(contract)
     }
 
@@ -617,7 +1082,7 @@ mod tests {
         let msg = cw20::Cw20ExecuteMsg::Send {
             contract: staking_addr.to_string(),
             amount: Uint128::new(amount),
-            msg: to_binary(&cw20_stake::msg::ReceiveMsg::Stake {}).unwrap(),
+            msg: to_binary(&cw20_stake::msg::ReceiveMsg::Stake { recipient: None }).unwrap(),
         };
         app.execute_contract(Addr::unchecked(sender), cw20_addr.clone(), &msg, &[])
             .unwrap();
@@ -664,6 +1129,8 @@ mod tests {
             staking_contract: staking_contract.clone().into_string(),
             reward_token,
             reward_duration: 100000,
+            vesting: None,
+            sweep_grace_period: None,
         };
         let reward_addr = app
             .instantiate_contract(reward_code_id, owner, &msg, &[], "reward", None)
@@ -712,6 +1179,28 @@ mod tests {
         assert_eq!(res.pending_rewards, Uint128::new(expected));
     }
 
+    fn assert_vesting(
+        app: &App,
+        reward_addr: &Addr,
+        address: &str,
+        vested: u128,
+        unvested: u128,
+        withdrawn: u128,
+    ) {
+        let res: VestingResponse = app
+            .wrap()
+            .query_wasm_smart(
+                reward_addr,
+                &QueryMsg::GetVesting {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.vested, Uint128::new(vested));
+        assert_eq!(res.unvested, Uint128::new(unvested));
+        assert_eq!(res.withdrawn, Uint128::new(withdrawn));
+    }
+
     fn claim_rewards(app: &mut App, reward_addr: Addr, address: &str) {
         let msg = ExecuteMsg::Claim {};
         app.borrow_mut()
@@ -764,6 +1253,8 @@ mod tests {
             staking_contract: staking_addr.to_string(),
             reward_token,
             reward_duration: 0,
+            vesting: None,
+            sweep_grace_period: None,
         };
         let err: ContractError = app
             .instantiate_contract(reward_code_id, owner, &msg, &[], "reward", None)
@@ -2204,12 +2695,674 @@ mod tests {
     }
 
     #[test]
-    pub fn test_migrate_update_version() {
-        let mut deps = mock_dependencies();
-        cw2::set_contract_version(&mut deps.storage, "my-contract", "old-version").unwrap();
-        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
-        let version = cw2::get_contract_version(&deps.storage).unwrap();
-        assert_eq!(version.version, CONTRACT_VERSION);
-        assert_eq!(version.contract, CONTRACT_NAME);
+    fn test_claim_for() {
+        let mut app = mock_app();
+        let admin = Addr::unchecked(OWNER);
+        app.borrow_mut().update_block(|b| b.height = 0);
+        let initial_balances = vec![
+            Cw20Coin {
+                address: ADDR1.to_string(),
+                amount: Uint128::new(100),
+            },
+            Cw20Coin {
+                address: ADDR2.to_string(),
+                amount: Uint128::new(100),
+            },
+        ];
+        let denom = "utest".to_string();
+        let (staking_addr, _) = setup_staking_contract(&mut app, initial_balances);
+        let reward_funding = vec![coin(100000000, denom.clone())];
+        app.sudo(SudoMsg::Bank({
+            BankSudo::Mint {
+                to_address: admin.to_string(),
+                amount: reward_funding.clone(),
+            }
+        }))
+        .unwrap();
+        let reward_addr = setup_reward_contract(
+            &mut app,
+            staking_addr.clone(),
+            Denom::Native(denom.clone()),
+            admin.clone(),
+            Addr::unchecked(MANAGER),
+        );
+
+        app.borrow_mut().update_block(|b| b.height = 1000);
+
+        let _res = app
+            .borrow_mut()
+            .execute_contract(
+                admin,
+                reward_addr.clone(),
+                &ExecuteMsg::Fund {},
+                &reward_funding,
+            )
+            .unwrap();
+
+        app.borrow_mut().update_block(next_block);
+        assert_pending_rewards(&mut app, &reward_addr, ADDR1, 500);
+
+        let claim_for_msg = ExecuteMsg::ClaimFor {
+            address: ADDR1.to_string(),
+            recipient: None,
+        };
+
+        // Only the staking contract can claim on behalf of a staker
+        let err: ContractError = app
+            .borrow_mut()
+            .execute_contract(
+                Addr::unchecked(ADDR2),
+                reward_addr.clone(),
+                &claim_for_msg,
+                &[],
+            )
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Rewards are delivered to the staker, not the staking contract
+        let _res = app
+            .borrow_mut()
+            .execute_contract(
+                staking_addr.clone(),
+                reward_addr.clone(),
+                &claim_for_msg,
+                &[],
+            )
+            .unwrap();
+        assert_eq!(get_balance_native(&app, ADDR1, &denom), Uint128::new(500));
+        assert_eq!(
+            get_balance_native(&app, &staking_addr, &denom),
+            Uint128::zero()
+        );
+        assert_pending_rewards(&mut app, &reward_addr, ADDR1, 0);
+        assert_pending_rewards(&mut app, &reward_addr, ADDR2, 500);
+
+        // Nothing left to claim
+        let err: ContractError = app
+            .borrow_mut()
+            .execute_contract(
+                staking_addr.clone(),
+                reward_addr.clone(),
+                &claim_for_msg,
+                &[],
+            )
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(err, ContractError::NoRewardsClaimable {});
+
+        // The staking contract can redirect rewards to itself to restake them
+        let _res = app
+            .borrow_mut()
+            .execute_contract(
+                staking_addr.clone(),
+                reward_addr.clone(),
+                &ExecuteMsg::ClaimFor {
+                    address: ADDR2.to_string(),
+                    recipient: Some(staking_addr.to_string()),
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(get_balance_native(&app, ADDR2, &denom), Uint128::zero());
+        assert_eq!(
+            get_balance_native(&app, &staking_addr, &denom),
+            Uint128::new(500)
+        );
+        assert_pending_rewards(&mut app, &reward_addr, ADDR2, 0);
+    }
+
+    #[test]
+    fn test_vesting() {
+        let mut app = mock_app();
+        let admin = Addr::unchecked(OWNER);
+        app.borrow_mut().update_block(|b| b.height = 0);
+        let initial_balances = vec![
+            Cw20Coin {
+                address: ADDR1.to_string(),
+                amount: Uint128::new(100),
+            },
+            Cw20Coin {
+                address: ADDR2.to_string(),
+                amount: Uint128::new(100),
+            },
+        ];
+        let denom = "utest".to_string();
+        let (staking_addr, _) = setup_staking_contract(&mut app, initial_balances);
+        let reward_funding = vec![coin(100000000, denom.clone())];
+        app.sudo(SudoMsg::Bank({
+            BankSudo::Mint {
+                to_address: admin.to_string(),
+                amount: reward_funding.clone(),
+            }
+        }))
+        .unwrap();
+
+        let reward_code_id = app.store_code(contract_rewards());
+        let mut msg = crate::msg::InstantiateMsg {
+            owner: Some(OWNER.to_string()),
+            manager: Some(MANAGER.to_string()),
+            staking_contract: staking_addr.to_string(),
+            reward_token: Denom::Native(denom.clone()),
+            reward_duration: 100000,
+            vesting: Some(VestingConfig {
+                cliff: 101,
+                duration: 100,
+            }),
+            sweep_grace_period: None,
+        };
+        let err: ContractError = app
+            .instantiate_contract(reward_code_id, admin.clone(), &msg, &[], "reward", None)
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(err, ContractError::InvalidVestingConfig {});
+
+        msg.vesting = Some(VestingConfig {
+            cliff: 10,
+            duration: 100,
+        });
+        let reward_addr = app
+            .instantiate_contract(reward_code_id, admin.clone(), &msg, &[], "reward", None)
+            .unwrap();
+        app.execute_contract(
+            admin.clone(),
+            staking_addr,
+            &cw20_stake::msg::ExecuteMsg::AddHook {
+                addr: reward_addr.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.borrow_mut().update_block(|b| b.height = 1000);
+        let _res = app
+            .borrow_mut()
+            .execute_contract(
+                admin,
+                reward_addr.clone(),
+                &ExecuteMsg::Fund {},
+                &reward_funding,
+            )
+            .unwrap();
+
+        // Claiming moves rewards into a vesting position instead of paying out
+        app.borrow_mut().update_block(|b| b.height = 1002);
+        assert_pending_rewards(&mut app, &reward_addr, ADDR1, 1000);
+        claim_rewards(&mut app, reward_addr.clone(), ADDR1);
+        assert_eq!(get_balance_native(&app, ADDR1, &denom), Uint128::zero());
+        assert_pending_rewards(&mut app, &reward_addr, ADDR1, 0);
+        assert_vesting(&app, &reward_addr, ADDR1, 0, 1000, 0);
+
+        // Nothing unlocks before the cliff
+        app.borrow_mut().update_block(|b| b.height = 1011);
+        let err: ContractError = app
+            .execute_contract(
+                Addr::unchecked(ADDR1),
+                reward_addr.clone(),
+                &ExecuteMsg::WithdrawVested {},
+                &[],
+            )
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(err, ContractError::NoVestedRewards {});
+
+        // At the cliff 10 of 100 blocks have elapsed
+        app.borrow_mut().update_block(|b| b.height = 1012);
+        assert_vesting(&app, &reward_addr, ADDR1, 100, 900, 0);
+        app.execute_contract(
+            Addr::unchecked(ADDR1),
+            reward_addr.clone(),
+            &ExecuteMsg::WithdrawVested {},
+            &[],
+        )
+        .unwrap();
+        assert_eq!(get_balance_native(&app, ADDR1, &denom), Uint128::new(100));
+        assert_vesting(&app, &reward_addr, ADDR1, 0, 900, 100);
+
+        // A second claim opens a new position with its own schedule
+        app.borrow_mut().update_block(|b| b.height = 1062);
+        assert_vesting(&app, &reward_addr, ADDR1, 500, 400, 100);
+        claim_rewards(&mut app, reward_addr.clone(), ADDR1);
+        assert_vesting(&app, &reward_addr, ADDR1, 500, 30400, 100);
+
+        // The first position is fully vested, the second 40% vested
+        app.borrow_mut().update_block(|b| b.height = 1102);
+        assert_vesting(&app, &reward_addr, ADDR1, 12900, 18000, 100);
+        app.execute_contract(
+            Addr::unchecked(ADDR1),
+            reward_addr.clone(),
+            &ExecuteMsg::WithdrawVested {},
+            &[],
+        )
+        .unwrap();
+        assert_eq!(get_balance_native(&app, ADDR1, &denom), Uint128::new(13000));
+        assert_vesting(&app, &reward_addr, ADDR1, 0, 18000, 13000);
+
+        // Other stakers are unaffected
+        assert_vesting(&app, &reward_addr, ADDR2, 0, 0, 0);
+        assert_pending_rewards(&mut app, &reward_addr, ADDR2, 51000);
+    }
+
+    #[test]
+    fn test_reward_totals_and_apr() {
+        let mut app = mock_app();
+        let admin = Addr::unchecked(OWNER);
+        app.borrow_mut().update_block(|b| b.height = 0);
+        let initial_balances = vec![
+            Cw20Coin {
+                address: ADDR1.to_string(),
+                amount: Uint128::new(100),
+            },
+            Cw20Coin {
+                address: ADDR2.to_string(),
+                amount: Uint128::new(100),
+            },
+        ];
+        let denom = "utest".to_string();
+        let (staking_addr, _) = setup_staking_contract(&mut app, initial_balances);
+        let reward_funding = vec![coin(100000000, denom.clone())];
+        app.sudo(SudoMsg::Bank({
+            BankSudo::Mint {
+                to_address: admin.to_string(),
+                amount: reward_funding.clone(),
+            }
+        }))
+        .unwrap();
+        let reward_addr = setup_reward_contract(
+            &mut app,
+            staking_addr,
+            Denom::Native(denom),
+            admin.clone(),
+            Addr::unchecked(MANAGER),
+        );
+
+        app.borrow_mut().update_block(|b| b.height = 1000);
+        let _res = app
+            .borrow_mut()
+            .execute_contract(
+                admin,
+                reward_addr.clone(),
+                &ExecuteMsg::Fund {},
+                &reward_funding,
+            )
+            .unwrap();
+
+        app.borrow_mut().update_block(|b| b.height = 1010);
+        claim_rewards(&mut app, reward_addr.clone(), ADDR1);
+
+        let res: RewardTotalsResponse = app
+            .wrap()
+            .query_wasm_smart(&reward_addr, &QueryMsg::RewardTotals {})
+            .unwrap();
+        assert_eq!(res.totals.funded, Uint128::new(100000000));
+        assert_eq!(res.totals.distributed, Uint128::new(10000));
+        assert_eq!(res.totals.claimed, Uint128::new(5000));
+
+        let res: ClaimedRewardsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &reward_addr,
+                &QueryMsg::GetClaimedRewards {
+                    address: ADDR1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.claimed, Uint128::new(5000));
+
+        // 1000 per block over 200 staked tokens
+        let res: AprResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &reward_addr,
+                &QueryMsg::Apr {
+                    price_hint: Some(Decimal::percent(200)),
+                    blocks_per_year: Some(100),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.reward_rate, Uint128::new(1000));
+        assert_eq!(res.total_staked, Uint128::new(200));
+        assert_eq!(
+            res.emission_per_staked_token,
+            Decimal::from_ratio(5u128, 1u128)
+        );
+        assert_eq!(res.apr, Some(Decimal::from_ratio(1000u128, 1u128)));
+
+        // Everything is distributed once the period ends and nothing is emitted after
+        app.borrow_mut().update_block(|b| b.height = 200000);
+        let res: RewardTotalsResponse = app
+            .wrap()
+            .query_wasm_smart(&reward_addr, &QueryMsg::RewardTotals {})
+            .unwrap();
+        assert_eq!(res.totals.distributed, Uint128::new(100000000));
+
+        let res: AprResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &reward_addr,
+                &QueryMsg::Apr {
+                    price_hint: None,
+                    blocks_per_year: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.reward_rate, Uint128::zero());
+        assert_eq!(res.emission_per_staked_token, Decimal::zero());
+        assert_eq!(res.apr, None);
+    }
+
+    #[test]
+    fn test_sweep() {
+        let mut app = mock_app();
+        let admin = Addr::unchecked(OWNER);
+        app.borrow_mut().update_block(|b| b.height = 0);
+        let initial_balances = vec![
+            Cw20Coin {
+                address: ADDR1.to_string(),
+                amount: Uint128::new(100),
+            },
+            Cw20Coin {
+                address: ADDR2.to_string(),
+                amount: Uint128::new(100),
+            },
+        ];
+        let denom = "utest".to_string();
+        let (staking_addr, _) = setup_staking_contract(&mut app, initial_balances);
+        // The reward rate rounds down, leaving 999 which is never distributed
+        let reward_funding = vec![coin(100000999, denom.clone())];
+        app.sudo(SudoMsg::Bank({
+            BankSudo::Mint {
+                to_address: admin.to_string(),
+                amount: reward_funding.clone(),
+            }
+        }))
+        .unwrap();
+        let reward_addr = setup_reward_contract(
+            &mut app,
+            staking_addr,
+            Denom::Native(denom.clone()),
+            admin.clone(),
+            Addr::unchecked(MANAGER),
+        );
+
+        app.borrow_mut().update_block(|b| b.height = 1000);
+        app.borrow_mut()
+            .execute_contract(
+                admin.clone(),
+                reward_addr.clone(),
+                &ExecuteMsg::Fund {},
+                &reward_funding,
+            )
+            .unwrap();
+
+        app.borrow_mut().update_block(|b| b.height = 50000);
+        claim_rewards(&mut app, reward_addr.clone(), ADDR1);
+
+        let sweep = ExecuteMsg::Sweep {
+            recipient: OWNER.to_string(),
+        };
+        let err: ContractError = app
+            .execute_contract(admin.clone(), reward_addr.clone(), &sweep, &[])
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(
+            err,
+            ContractError::SweepNotAvailable {
+                sweepable_at: 201800
+            }
+        );
+
+        app.borrow_mut().update_block(|b| b.height = 201800);
+        let res: UndistributedResponse = app
+            .wrap()
+            .query_wasm_smart(&reward_addr, &QueryMsg::Undistributed {})
+            .unwrap();
+        assert_eq!(res.amount, Uint128::new(999));
+        assert_eq!(res.sweepable_at, 201800);
+
+        let err: ContractError = app
+            .execute_contract(Addr::unchecked(ADDR1), reward_addr.clone(), &sweep, &[])
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        app.execute_contract(admin.clone(), reward_addr.clone(), &sweep, &[])
+            .unwrap();
+        assert_eq!(get_balance_native(&app, OWNER, &denom), Uint128::new(999));
+
+        // Accrued rewards are untouched, claimed or not
+        assert_pending_rewards(&mut app, &reward_addr, ADDR1, 25500000);
+        assert_pending_rewards(&mut app, &reward_addr, ADDR2, 50000000);
+
+        let err: ContractError = app
+            .execute_contract(admin, reward_addr.clone(), &sweep, &[])
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(err, ContractError::NothingToSweep {});
+
+        claim_rewards(&mut app, reward_addr.clone(), ADDR1);
+        claim_rewards(&mut app, reward_addr.clone(), ADDR2);
+        assert_eq!(
+            get_balance_native(&app, ADDR1, &denom),
+            Uint128::new(50000000)
+        );
+        assert_eq!(
+            get_balance_native(&app, ADDR2, &denom),
+            Uint128::new(50000000)
+        );
+        assert_eq!(
+            get_balance_native(&app, &reward_addr, &denom),
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn test_interleaved_stakes_accrue_exactly() {
+        // Replays pseudo-random interleavings of stakes, unstakes and claims,
+        // including several in the same block, against a model which accrues
+        // each block's emission pro rata over the balances staked during it.
+        const SCALE: u128 = 1_000_000_000_000;
+        const STEPS: u64 = 60;
+        let stakers = [ADDR1, ADDR2, ADDR3];
+        let denom = "utest".to_string();
+
+        for seed in 1..=8u64 {
+            let mut rng = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            let mut next = || {
+                rng ^= rng << 13;
+                rng ^= rng >> 7;
+                rng ^= rng << 17;
+                rng
+            };
+
+            let mut app = mock_app();
+            app.borrow_mut().update_block(|b| b.height = 0);
+            let initial_balances = stakers
+                .iter()
+                .map(|address| Cw20Coin {
+                    address: address.to_string(),
+                    amount: Uint128::new(1000),
+                })
+                .collect();
+            let cw20_addr = instantiate_cw20(&mut app, initial_balances);
+            let staking_addr = instantiate_staking(&mut app, cw20_addr.clone(), None);
+            let reward_funding = vec![coin(100000000, denom.clone())];
+            app.sudo(SudoMsg::Bank({
+                BankSudo::Mint {
+                    to_address: OWNER.to_string(),
+                    amount: reward_funding.clone(),
+                }
+            }))
+            .unwrap();
+            let reward_addr = setup_reward_contract(
+                &mut app,
+                staking_addr.clone(),
+                Denom::Native(denom.clone()),
+                Addr::unchecked(OWNER),
+                Addr::unchecked(MANAGER),
+            );
+            app.borrow_mut().update_block(|b| b.height = 1000);
+            app.execute_contract(
+                Addr::unchecked(OWNER),
+                reward_addr.clone(),
+                &ExecuteMsg::Fund {},
+                &reward_funding,
+            )
+            .unwrap();
+
+            let mut staked = [0u128; 3];
+            let mut expected = [0u128; 3];
+            let mut distributed = 0u128;
+            for _ in 0..STEPS {
+                let i = (next() % 3) as usize;
+                match next() % 4 {
+                    0 if staked[i] < 1000 => {
+                        let amount = 1 + (next() as u128) % (1000 - staked[i]);
+                        stake_tokens(&mut app, &staking_addr, &cw20_addr, stakers[i], amount);
+                        staked[i] += amount;
+                    }
+                    1 if staked[i] > 0 => {
+                        let amount = 1 + (next() as u128) % staked[i];
+                        unstake_tokens(&mut app, &staking_addr, stakers[i], amount);
+                        staked[i] -= amount;
+                    }
+                    2 => {
+                        let res: PendingRewardsResponse = app
+                            .wrap()
+                            .query_wasm_smart(
+                                &reward_addr,
+                                &QueryMsg::GetPendingRewards {
+                                    address: stakers[i].to_string(),
+                                },
+                            )
+                            .unwrap();
+                        if !res.pending_rewards.is_zero() {
+                            claim_rewards(&mut app, reward_addr.clone(), stakers[i]);
+                        }
+                    }
+                    _ => {}
+                }
+
+                let blocks = next() % 3;
+                let total: u128 = staked.iter().sum();
+                if total > 0 {
+                    let emitted = 1000 * blocks as u128;
+                    distributed += emitted;
+                    for (share, balance) in expected.iter_mut().zip(staked.iter()) {
+                        *share += emitted * balance * SCALE / total;
+                    }
+                }
+                app.update_block(|b| b.height += blocks);
+            }
+
+            let mut earned_total = 0u128;
+            for (i, staker) in stakers.iter().enumerate() {
+                let res: PendingRewardsResponse = app
+                    .wrap()
+                    .query_wasm_smart(
+                        &reward_addr,
+                        &QueryMsg::GetPendingRewards {
+                            address: staker.to_string(),
+                        },
+                    )
+                    .unwrap();
+                let claimed = get_balance_native(&app, *staker, &denom).u128();
+                let earned = res.pending_rewards.u128() + claimed;
+                let share = expected[i] / SCALE;
+                // Each update may round the staker's share down by one
+                assert!(
+                    earned <= share + 1 && earned + STEPS as u128 >= share,
+                    "seed {}: {} earned {} but expected {}",
+                    seed,
+                    staker,
+                    earned,
+                    share
+                );
+                earned_total += earned;
+            }
+            assert!(earned_total <= distributed);
+
+            let res: RewardTotalsResponse = app
+                .wrap()
+                .query_wasm_smart(&reward_addr, &QueryMsg::RewardTotals {})
+                .unwrap();
+            assert_eq!(res.totals.distributed, Uint128::new(distributed));
+        }
+    }
+
+    #[test]
+    pub fn test_migrate_update_version() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(&mut deps.storage, "my-contract", "old-version").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let version = cw2::get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+        assert_eq!(version.contract, CONTRACT_NAME);
+    }
+
+    #[test]
+    fn test_vesting_positions_bounded() {
+        let mut deps = mock_dependencies();
+        let vesting = VestingConfig {
+            cliff: 0,
+            duration: 320,
+        };
+        let addr = Addr::unchecked(ADDR1);
+        for height in 1..=1000 {
+            add_vesting_position(&mut deps.storage, &vesting, &addr, height, Uint128::new(1))
+                .unwrap();
+        }
+
+        // Claims share a position every 10 blocks, and positions fully vested
+        // by the last claim are folded into the unlocked rewards
+        let positions = load_vesting_positions(&deps.storage, &addr).unwrap();
+        assert_eq!(positions.len() as u64, MAX_VESTING_POSITIONS);
+        assert_eq!(positions[0].0, 690);
+        assert_eq!(positions[31].0, 1000);
+        assert_eq!(positions[31].1.amount, Uint128::new(10));
+        assert_eq!(
+            UNLOCKED_REWARDS.load(&deps.storage, addr).unwrap(),
+            Uint128::new(680)
+        );
+    }
+
+    #[test]
+    pub fn test_migrate_seeds_reward_totals() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(&mut deps.storage, "my-contract", "old-version").unwrap();
+        REWARD_CONFIG
+            .save(
+                &mut deps.storage,
+                &RewardConfig {
+                    period_finish: 1000,
+                    reward_rate: Uint128::new(10),
+                    reward_duration: 800,
+                },
+            )
+            .unwrap();
+        LAST_UPDATE_BLOCK.save(&mut deps.storage, &600).unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        // Only the 400 blocks not yet accrued count as funded
+        let totals = REWARD_TOTALS.load(&deps.storage).unwrap();
+        assert_eq!(
+            totals,
+            RewardTotals {
+                funded: Uint128::new(4000),
+                ..RewardTotals::default()
+            }
+        );
+        assert_eq!(get_undistributed(&totals).unwrap(), Uint128::new(4000));
+
+        // Totals already tracked are kept
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(REWARD_TOTALS.load(&deps.storage).unwrap(), totals);
     }
 }
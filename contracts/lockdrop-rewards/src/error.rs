@@ -21,4 +21,12 @@ pub enum ContractError {
     RewardRateLessThenOnePerBlock {},
     #[error("Reward duration can not be zero")]
     ZeroRewardDuration {},
+    #[error("Vesting cliff can not be longer than the vesting duration")]
+    InvalidVestingConfig {},
+    #[error("No vested rewards to withdraw")]
+    NoVestedRewards {},
+    #[error("Rewards can not be swept before block {sweepable_at}")]
+    SweepNotAvailable { sweepable_at: u64 },
+    #[error("No undistributed rewards to sweep")]
+    NothingToSweep {},
 }
@@ -1,10 +1,10 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
 use cw20::{Cw20ReceiveMsg, Denom};
-use cw20_stake::hooks::StakeChangedHookMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use staking_core::hooks::StakeChangedHookMsg;
 
-use crate::state::{Config, RewardConfig};
+use crate::state::{Config, RewardConfig, RewardTotals, VestingConfig};
 pub use cw_controllers::ClaimsResponse;
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
@@ -14,6 +14,8 @@ pub struct InstantiateMsg {
     pub staking_contract: String,
     pub reward_token: Denom,
     pub reward_duration: u64,
+    pub vesting: Option<VestingConfig>,
+    pub sweep_grace_period: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
@@ -24,11 +26,25 @@ pub struct MigrateMsg {}
 pub enum ExecuteMsg {
     StakeChangeHook(StakeChangedHookMsg),
     Claim {},
+    ClaimFor {
+        address: String,
+        recipient: Option<String>,
+    },
+    WithdrawVested {},
+    Sweep {
+        recipient: String,
+    },
     Receive(Cw20ReceiveMsg),
     Fund {},
-    UpdateRewardDuration { new_duration: u64 },
-    UpdateOwner { new_owner: Option<String> },
-    UpdateManager { new_manager: Option<String> },
+    UpdateRewardDuration {
+        new_duration: u64,
+    },
+    UpdateOwner {
+        new_owner: Option<String>,
+    },
+    UpdateManager {
+        new_manager: Option<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -41,13 +57,29 @@ pub enum ReceiveMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Info {},
-    GetPendingRewards { address: String },
+    GetPendingRewards {
+        address: String,
+    },
+    GetVesting {
+        address: String,
+    },
+    GetClaimedRewards {
+        address: String,
+    },
+    RewardTotals {},
+    Undistributed {},
+    Apr {
+        // Price of one reward token in staked tokens.
+        price_hint: Option<Decimal>,
+        blocks_per_year: Option<u64>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InfoResponse {
     pub config: Config,
     pub reward: RewardConfig,
+    pub vesting: Option<VestingConfig>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -57,3 +89,48 @@ pub struct PendingRewardsResponse {
     pub denom: Denom,
     pub last_update_block: u64,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingResponse {
+    pub address: String,
+    /// Claimed rewards which have unlocked and can be withdrawn.
+    pub vested: Uint128,
+    /// Claimed rewards which are still locked.
+    pub unvested: Uint128,
+    /// Rewards which have already been withdrawn.
+    pub withdrawn: Uint128,
+    pub denom: Denom,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimedRewardsResponse {
+    pub address: String,
+    pub claimed: Uint128,
+    pub denom: Denom,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardTotalsResponse {
+    pub totals: RewardTotals,
+    pub denom: Denom,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AprResponse {
+    /// Reward tokens emitted per block, zero once the reward period is over.
+    pub reward_rate: Uint128,
+    pub total_staked: Uint128,
+    /// Reward tokens emitted per staked token per block.
+    pub emission_per_staked_token: Decimal,
+    pub blocks_per_year: u64,
+    /// Only available when a `price_hint` is given.
+    pub apr: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UndistributedResponse {
+    /// Funded rewards which were never accrued to stakers.
+    pub amount: Uint128,
+    pub denom: Denom,
+    pub sweepable_at: u64,
+}
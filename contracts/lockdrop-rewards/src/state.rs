@@ -30,3 +30,44 @@ pub const LAST_UPDATE_BLOCK: Item<u64> = Item::new("last_update_block");
 pub const PENDING_REWARDS: Map<Addr, Uint128> = Map::new("pending_rewards");
 
 pub const USER_REWARD_PER_TOKEN: Map<Addr, Uint256> = Map::new("user_reward_per_token");
+
+/// Lifetime totals of rewards funded, accrued to stakers, claimed and swept.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq, JsonSchema, Debug)]
+pub struct RewardTotals {
+    pub funded: Uint128,
+    pub distributed: Uint128,
+    pub claimed: Uint128,
+    pub swept: Uint128,
+}
+pub const REWARD_TOTALS: Item<RewardTotals> = Item::new("reward_totals");
+
+pub const CLAIMED_REWARDS: Map<Addr, Uint128> = Map::new("claimed_rewards");
+
+/// Blocks after the end of a reward period before undistributed rewards can
+/// be swept by the owner.
+pub const SWEEP_GRACE_PERIOD: Item<u64> = Item::new("sweep_grace_period");
+
+/// Optional vesting schedule applied to claimed rewards. Both values are in
+/// blocks and measured from the block at which the rewards were claimed.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct VestingConfig {
+    pub cliff: u64,
+    pub duration: u64,
+}
+pub const VESTING_CONFIG: Item<VestingConfig> = Item::new("vesting_config");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct VestingPosition {
+    pub amount: Uint128,
+    pub withdrawn: Uint128,
+}
+
+/// Vesting positions keyed by owner and the block they vest from. Claims are
+/// merged into the position starting at or after the block of the claim.
+pub const VESTING_POSITIONS: Map<(Addr, u64), VestingPosition> = Map::new("vesting_positions");
+
+/// Fully vested rewards folded out of an owner's vesting positions, withdrawn
+/// along with them.
+pub const UNLOCKED_REWARDS: Map<Addr, Uint128> = Map::new("unlocked_rewards");
+
+pub const WITHDRAWN_REWARDS: Map<Addr, Uint128> = Map::new("withdrawn_rewards");
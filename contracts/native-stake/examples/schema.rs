@@ -8,9 +8,11 @@ use cw20::{
     TokenInfoResponse,
 };
 use native_stake::msg::{
-    ClaimsResponse, ExecuteMsg, GetHooksResponse, InstantiateMsg, ListStakersResponse, QueryMsg,
-    StakedBalanceAtHeightResponse, StakedValueResponse, TotalStakedAtHeightResponse,
-    TotalValueResponse,
+    AcceptsStakesResponse, BasketClaimsResponse, BasketResponse, BasketStakeResponse,
+    ClaimsResponse, DelegationResponse, ExecuteMsg, FailedHooksResponse, GetHooksResponse,
+    InfoResponse, InstantiateMsg, ListStakersResponse, QueryMsg, StakedBalanceAtHeightResponse,
+    StakedValueResponse, TotalPowerAtHeightResponse, TotalStakedAtHeightResponse,
+    TotalValueResponse, VotingPowerAtHeightResponse,
 };
 use native_stake::state::Config;
 
@@ -28,6 +30,7 @@ fn main() {
     export_schema(&schema_for!(StakedValueResponse), &out_dir);
     export_schema(&schema_for!(TotalValueResponse), &out_dir);
     export_schema(&schema_for!(GetHooksResponse), &out_dir);
+    export_schema(&schema_for!(FailedHooksResponse), &out_dir);
     export_schema(&schema_for!(ClaimsResponse), &out_dir);
     export_schema(&schema_for!(AllowanceResponse), &out_dir);
     export_schema(&schema_for!(BalanceResponse), &out_dir);
@@ -35,6 +38,14 @@ fn main() {
     export_schema(&schema_for!(AllAllowancesResponse), &out_dir);
     export_schema(&schema_for!(AllAccountsResponse), &out_dir);
     export_schema(&schema_for!(ListStakersResponse), &out_dir);
+    export_schema(&schema_for!(AcceptsStakesResponse), &out_dir);
+    export_schema(&schema_for!(VotingPowerAtHeightResponse), &out_dir);
+    export_schema(&schema_for!(DelegationResponse), &out_dir);
+    export_schema(&schema_for!(TotalPowerAtHeightResponse), &out_dir);
+    export_schema(&schema_for!(InfoResponse), &out_dir);
+    export_schema(&schema_for!(BasketResponse), &out_dir);
+    export_schema(&schema_for!(BasketStakeResponse), &out_dir);
+    export_schema(&schema_for!(BasketClaimsResponse), &out_dir);
 
     // Need to rename so it matches the TS pattern
     export_schema_with_title(&schema_for!(Config), &out_dir, "GetConfigResponse");
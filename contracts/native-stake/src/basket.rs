@@ -0,0 +1,464 @@
+use std::collections::{BTreeMap, HashSet};
+
+use cosmwasm_std::{
+    Addr, BankMsg, Coin, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError,
+    StdResult, Storage, SubMsg, Uint128,
+};
+use cw_controllers::{Claim, ClaimsResponse};
+use cw_utils::{Duration, PaymentError};
+use staking_core::staking::{burn_shares, ensure_accepts_stakes, mint_shares};
+use staking_core::StakingError;
+
+use crate::msg::{
+    BasketClaimsResponse, BasketDenom, BasketDenomResponse, BasketResponse, BasketStakeResponse,
+    DenomStakeResponse, StakedValueResponse, TotalValueResponse,
+};
+use crate::state::{
+    BasketClaim, BASKET, BASKET_BALANCES, BASKET_CLAIMS, BASKET_STAKED_BALANCES,
+    BASKET_STAKED_TOTALS,
+};
+use crate::ContractError;
+
+/// Whether the contract stakes a basket of denoms rather than a single one.
+pub fn is_basket(storage: &dyn Storage) -> bool {
+    BASKET
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .is_some()
+}
+
+pub fn instantiate(
+    storage: &mut dyn Storage,
+    denom: &str,
+    basket: Vec<BasketDenom>,
+) -> Result<(), ContractError> {
+    let mut seen = HashSet::new();
+    for basket_denom in &basket {
+        if basket_denom.weight.is_zero() || !seen.insert(basket_denom.denom.as_str()) {
+            return Err(ContractError::InvalidBasket {});
+        }
+    }
+    if !seen.contains(denom) {
+        return Err(ContractError::InvalidBasket {});
+    }
+    for basket_denom in basket {
+        BASKET.save(storage, &basket_denom.denom, &basket_denom.weight)?;
+        BASKET_BALANCES.save(storage, &basket_denom.denom, &Uint128::zero())?;
+        BASKET_STAKED_TOTALS.save(storage, &basket_denom.denom, &Uint128::zero())?;
+    }
+    Ok(())
+}
+
+fn load_weight(storage: &dyn Storage, denom: &str) -> Result<Decimal, ContractError> {
+    BASKET
+        .may_load(storage, denom)?
+        .ok_or_else(|| ContractError::InvalidStakeDenom {
+            denom: denom.to_string(),
+        })
+}
+
+/// Voting power of `staked` tokens of a denom with `weight`. Rounded down
+/// on the staker's whole balance, so staking in parts gives the same power
+/// as staking at once.
+fn weighted(staked: Uint128, weight: Decimal) -> Uint128 {
+    staked * weight
+}
+
+fn coins_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|coin| coin.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Credits `coins`, already held by the contract, to `staker` and returns
+/// the voting power gained along with the stake hook messages.
+fn stake_coins(
+    storage: &mut dyn Storage,
+    env: &Env,
+    staker: &Addr,
+    coins: &[Coin],
+) -> Result<(Uint128, Vec<SubMsg>), ContractError> {
+    let mut power = Uint128::zero();
+    for coin in coins {
+        let weight = load_weight(storage, &coin.denom)?;
+        let balance = BASKET_BALANCES.load(storage, &coin.denom)?;
+        let staked_total = BASKET_STAKED_TOTALS.load(storage, &coin.denom)?;
+        let amount_to_stake = if staked_total.is_zero() || balance.is_zero() {
+            coin.amount
+        } else {
+            staked_total
+                .checked_mul(coin.amount)
+                .map_err(StdError::overflow)?
+                .checked_div(balance)
+                .map_err(StdError::divide_by_zero)?
+        };
+        BASKET_BALANCES.save(
+            storage,
+            &coin.denom,
+            &balance
+                .checked_add(coin.amount)
+                .map_err(StdError::overflow)?,
+        )?;
+        BASKET_STAKED_TOTALS.save(
+            storage,
+            &coin.denom,
+            &staked_total
+                .checked_add(amount_to_stake)
+                .map_err(StdError::overflow)?,
+        )?;
+        let staked = BASKET_STAKED_BALANCES
+            .may_load(storage, (staker, &coin.denom))?
+            .unwrap_or_default();
+        let new_staked = staked
+            .checked_add(amount_to_stake)
+            .map_err(StdError::overflow)?;
+        BASKET_STAKED_BALANCES.save(storage, (staker, &coin.denom), &new_staked)?;
+        power += weighted(new_staked, weight) - weighted(staked, weight);
+    }
+
+    let hook_msgs = mint_shares(storage, env, staker, power)?;
+    Ok((power, hook_msgs))
+}
+
+pub fn execute_stake(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    if info.funds.is_empty() {
+        return Err(PaymentError::NoFunds {}.into());
+    }
+    ensure_accepts_stakes(deps.storage, &info.sender, &recipient)?;
+    let (power, hook_msgs) = stake_coins(deps.storage, env, &recipient, &info.funds)?;
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_attribute("action", "stake")
+        .add_attribute("from", info.sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", coins_to_string(&info.funds))
+        .add_attribute("power", power))
+}
+
+pub fn execute_unstake(
+    deps: DepsMut,
+    env: &Env,
+    staker: Addr,
+    denom: String,
+    amount: Uint128,
+    unstaking_duration: Option<Duration>,
+    max_claims: u64,
+) -> Result<Response, ContractError> {
+    let weight = load_weight(deps.storage, &denom)?;
+    let balance = BASKET_BALANCES.load(deps.storage, &denom)?;
+    let staked_total = BASKET_STAKED_TOTALS.load(deps.storage, &denom)?;
+    let amount_to_claim = amount
+        .checked_mul(balance)
+        .map_err(StdError::overflow)?
+        .checked_div(staked_total)
+        .map_err(|_e| ContractError::Staking(StakingError::InvalidUnstakeAmount {}))?;
+    let staked = BASKET_STAKED_BALANCES
+        .may_load(deps.storage, (&staker, &denom))?
+        .unwrap_or_default();
+    let new_staked = staked
+        .checked_sub(amount)
+        .map_err(|_e| ContractError::Staking(StakingError::InvalidUnstakeAmount {}))?;
+    BASKET_STAKED_BALANCES.save(deps.storage, (&staker, &denom), &new_staked)?;
+    BASKET_STAKED_TOTALS.save(
+        deps.storage,
+        &denom,
+        &staked_total
+            .checked_sub(amount)
+            .map_err(|_e| ContractError::Staking(StakingError::InvalidUnstakeAmount {}))?,
+    )?;
+    BASKET_BALANCES.save(
+        deps.storage,
+        &denom,
+        &balance
+            .checked_sub(amount_to_claim)
+            .map_err(|_e| ContractError::Staking(StakingError::InvalidUnstakeAmount {}))?,
+    )?;
+
+    let power = weighted(staked, weight) - weighted(new_staked, weight);
+    let hook_msgs = burn_shares(deps.storage, env, &staker, power)?;
+    let claim = Coin {
+        denom,
+        amount: amount_to_claim,
+    };
+    match unstaking_duration {
+        None => Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: staker.to_string(),
+                amount: vec![claim.clone()],
+            })
+            .add_submessages(hook_msgs)
+            .add_attribute("action", "unstake")
+            .add_attribute("from", staker)
+            .add_attribute("amount", claim.to_string())
+            .add_attribute("power", power)
+            .add_attribute("claim_duration", "None")),
+        Some(duration) => {
+            // Claims of a denom released at the same time are merged, as in
+            // single denom mode.
+            let release_at = duration.after(&env.block);
+            let mut claims = BASKET_CLAIMS
+                .may_load(deps.storage, &staker)?
+                .unwrap_or_default();
+            match claims
+                .iter_mut()
+                .find(|existing| existing.denom == claim.denom && existing.release_at == release_at)
+            {
+                Some(existing) => {
+                    existing.amount = existing
+                        .amount
+                        .checked_add(claim.amount)
+                        .map_err(StdError::overflow)?
+                }
+                None => {
+                    if claims.len() as u64 >= max_claims {
+                        return Err(ContractError::Staking(StakingError::TooManyClaims {}));
+                    }
+                    claims.push(BasketClaim {
+                        denom: claim.denom.clone(),
+                        amount: claim.amount,
+                        release_at,
+                    });
+                }
+            }
+            BASKET_CLAIMS.save(deps.storage, &staker, &claims)?;
+            Ok(Response::new()
+                .add_attribute("action", "unstake")
+                .add_submessages(hook_msgs)
+                .add_attribute("from", staker)
+                .add_attribute("amount", claim.to_string())
+                .add_attribute("power", power)
+                .add_attribute("claim_duration", format!("{}", duration)))
+        }
+    }
+}
+
+pub fn execute_claim(
+    deps: DepsMut,
+    env: &Env,
+    staker: Addr,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let mut remaining = limit.map(|limit| limit as usize).unwrap_or(usize::MAX);
+    let (released, pending): (Vec<_>, Vec<_>) = BASKET_CLAIMS
+        .may_load(deps.storage, &staker)?
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|claim| {
+            let release = remaining > 0 && claim.release_at.is_expired(&env.block);
+            if release {
+                remaining -= 1;
+            }
+            release
+        });
+    if released.is_empty() {
+        return Err(ContractError::Staking(StakingError::NothingToClaim {}));
+    }
+    if pending.is_empty() {
+        BASKET_CLAIMS.remove(deps.storage, &staker);
+    } else {
+        BASKET_CLAIMS.save(deps.storage, &staker, &pending)?;
+    }
+
+    // A bank send takes each denom once, sorted.
+    let mut amounts: BTreeMap<String, Uint128> = BTreeMap::new();
+    for claim in released {
+        let amount = amounts.entry(claim.denom).or_default();
+        *amount = amount
+            .checked_add(claim.amount)
+            .map_err(StdError::overflow)?;
+    }
+    let release: Vec<Coin> = amounts
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(denom, amount)| Coin { denom, amount })
+        .collect();
+    if release.is_empty() {
+        return Err(ContractError::Staking(StakingError::NothingToClaim {}));
+    }
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: staker.to_string(),
+            amount: release.clone(),
+        })
+        .add_attribute("action", "claim")
+        .add_attribute("from", staker)
+        .add_attribute("amount", coins_to_string(&release)))
+}
+
+/// Restakes `amount` of `staker`'s `denom` claims which have not matured
+/// yet, newest first, at the current exchange rate of `denom`.
+pub fn execute_cancel_unbonding(
+    deps: DepsMut,
+    env: &Env,
+    staker: Addr,
+    denom: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    load_weight(deps.storage, &denom)?;
+    let mut claims = BASKET_CLAIMS
+        .may_load(deps.storage, &staker)?
+        .unwrap_or_default();
+    let mut remaining = amount;
+    for claim in claims.iter_mut().rev() {
+        if remaining.is_zero() {
+            break;
+        }
+        if claim.denom != denom || claim.release_at.is_expired(&env.block) {
+            continue;
+        }
+        let cancelled = claim.amount.min(remaining);
+        claim.amount -= cancelled;
+        remaining -= cancelled;
+    }
+    if amount.is_zero() || !remaining.is_zero() {
+        return Err(ContractError::Staking(StakingError::InvalidCancelAmount {}));
+    }
+    claims.retain(|claim| !claim.amount.is_zero());
+    if claims.is_empty() {
+        BASKET_CLAIMS.remove(deps.storage, &staker);
+    } else {
+        BASKET_CLAIMS.save(deps.storage, &staker, &claims)?;
+    }
+
+    let restaked = Coin { denom, amount };
+    let (power, hook_msgs) = stake_coins(deps.storage, env, &staker, &[restaked.clone()])?;
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_attribute("action", "cancel_unbonding")
+        .add_attribute("from", staker)
+        .add_attribute("amount", restaked.to_string())
+        .add_attribute("power", power))
+}
+
+pub fn execute_fund(
+    storage: &mut dyn Storage,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if info.funds.is_empty() {
+        return Err(PaymentError::NoFunds {}.into());
+    }
+    for coin in &info.funds {
+        load_weight(storage, &coin.denom)?;
+        BASKET_BALANCES.update(storage, &coin.denom, |balance| -> StdResult<_> {
+            balance
+                .unwrap_or_default()
+                .checked_add(coin.amount)
+                .map_err(StdError::overflow)
+        })?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "fund")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", coins_to_string(&info.funds)))
+}
+
+pub fn query_basket(deps: Deps) -> StdResult<BasketResponse> {
+    let denoms = BASKET
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, weight) = item?;
+            Ok(BasketDenomResponse {
+                balance: BASKET_BALANCES.load(deps.storage, &denom)?,
+                staked_total: BASKET_STAKED_TOTALS.load(deps.storage, &denom)?,
+                denom,
+                weight,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(BasketResponse { denoms })
+}
+
+/// Tokens of `denom` that `staked` of it are worth.
+fn staked_value(storage: &dyn Storage, denom: &str, staked: Uint128) -> StdResult<Uint128> {
+    let staked_total = BASKET_STAKED_TOTALS.load(storage, denom)?;
+    if staked.is_zero() || staked_total.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    staked
+        .checked_mul(BASKET_BALANCES.load(storage, denom)?)
+        .map_err(StdError::overflow)?
+        .checked_div(staked_total)
+        .map_err(StdError::divide_by_zero)
+}
+
+pub fn query_basket_stake(deps: Deps, address: String) -> StdResult<BasketStakeResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let denoms = BASKET
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut stakes = vec![];
+    for denom in denoms {
+        let staked = BASKET_STAKED_BALANCES
+            .may_load(deps.storage, (&address, &denom))?
+            .unwrap_or_default();
+        if staked.is_zero() {
+            continue;
+        }
+        let value = staked_value(deps.storage, &denom, staked)?;
+        stakes.push(DenomStakeResponse {
+            denom,
+            staked,
+            value,
+        });
+    }
+    Ok(BasketStakeResponse { stakes })
+}
+
+/// Value of an address' stake summed over the basket, each denom weighted
+/// like its voting power.
+pub fn query_staked_value(deps: Deps, address: String) -> StdResult<StakedValueResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let mut value = Uint128::zero();
+    for item in BASKET.range(deps.storage, None, None, Order::Ascending) {
+        let (denom, weight) = item?;
+        let staked = BASKET_STAKED_BALANCES
+            .may_load(deps.storage, (&address, &denom))?
+            .unwrap_or_default();
+        value += weighted(staked_value(deps.storage, &denom, staked)?, weight);
+    }
+    Ok(StakedValueResponse { value })
+}
+
+/// Balance of the basket, each denom weighted like its voting power.
+pub fn query_total_value(deps: Deps) -> StdResult<TotalValueResponse> {
+    let mut total = Uint128::zero();
+    for item in BASKET.range(deps.storage, None, None, Order::Ascending) {
+        let (denom, weight) = item?;
+        total += weighted(BASKET_BALANCES.load(deps.storage, &denom)?, weight);
+    }
+    Ok(TotalValueResponse { total })
+}
+
+/// Claims of every basket denom, weighted like `StakedValue`. The claimed
+/// denoms themselves are answered by `BasketClaims`.
+pub fn query_claims(deps: Deps, address: String) -> StdResult<ClaimsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let claims = BASKET_CLAIMS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|claim| {
+            let weight = BASKET.load(deps.storage, &claim.denom)?;
+            Ok(Claim {
+                amount: weighted(claim.amount, weight),
+                release_at: claim.release_at,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ClaimsResponse { claims })
+}
+
+pub fn query_basket_claims(deps: Deps, address: String) -> StdResult<BasketClaimsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let claims = BASKET_CLAIMS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(BasketClaimsResponse { claims })
+}
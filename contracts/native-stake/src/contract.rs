@@ -2,19 +2,13 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    coins, to_binary, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Response,
-    StdError, StdResult, Uint128,
+    to_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Reply, Response, StdError,
+    StdResult, Uint128,
 };
 
-use crate::hooks::{stake_hook_msgs, unstake_hook_msgs};
-use crate::msg::{
-    ExecuteMsg, GetHooksResponse, InstantiateMsg, ListStakersResponse, QueryMsg,
-    StakedBalanceAtHeightResponse, StakedValueResponse, StakerBalanceResponse,
-    TotalStakedAtHeightResponse, TotalValueResponse,
-};
-use crate::state::{
-    Config, BALANCE, CLAIMS, CONFIG, HOOKS, MAX_CLAIMS, STAKED_BALANCES, STAKED_TOTAL,
-};
+use crate::basket;
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::state::{Config, CONFIG, DAO, MAX_CLAIMS};
 use crate::ContractError;
 use cw2::set_contract_version;
 pub use cw20_base::allowances::{
@@ -27,35 +21,18 @@ pub use cw20_base::contract::{
     query_token_info,
 };
 pub use cw20_base::enumerable::{query_all_accounts, query_all_allowances};
-use cw_controllers::ClaimsResponse;
-use cw_utils::{must_pay, Duration};
+use cw_utils::Duration;
+use staking_core::hooks::HOOK_REPLY_ID_OFFSET;
+use staking_core::{delegation, staking, NativeAsset, StakingAsset};
 
 pub(crate) const CONTRACT_NAME: &str = "crates.io:native-stake";
 pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-fn validate_duration(duration: Option<Duration>) -> Result<(), ContractError> {
-    if let Some(unstaking_duration) = duration {
-        match unstaking_duration {
-            Duration::Height(height) => {
-                if height == 0 {
-                    return Err(ContractError::InvalidUnstakingDuration {});
-                }
-            }
-            Duration::Time(time) => {
-                if time == 0 {
-                    return Err(ContractError::InvalidUnstakingDuration {});
-                }
-            }
-        }
-    }
-    Ok(())
-}
-
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response<Empty>, ContractError> {
     let owner = match msg.owner {
@@ -68,21 +45,20 @@ pub fn instantiate(
         None => None,
     };
 
-    validate_duration(msg.unstaking_duration)?;
+    staking::validate_duration(msg.unstaking_duration)?;
     let config = Config {
         owner,
         manager,
         denom: msg.denom,
         unstaking_duration: msg.unstaking_duration,
+        max_claims: None,
     };
+    if let Some(basket_denoms) = msg.basket {
+        basket::instantiate(deps.storage, &config.denom, basket_denoms)?;
+    }
     CONFIG.save(deps.storage, &config)?;
-
-    // Initialize state to zero. We do this instead of using
-    // `unwrap_or_default` where this is used as it protects us
-    // against a scenerio where state is cleared by a bad actor and
-    // `unwrap_or_default` carries on.
-    STAKED_TOTAL.save(deps.storage, &Uint128::zero(), env.block.height)?;
-    BALANCE.save(deps.storage, &Uint128::zero())?;
+    DAO.save(deps.storage, &info.sender)?;
+    staking::instantiate(deps.storage, &env)?;
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
@@ -98,16 +74,39 @@ pub fn execute(
 ) -> Result<Response<Empty>, ContractError> {
     match msg {
         ExecuteMsg::Fund {} => execute_fund(deps, env, info),
-        ExecuteMsg::Stake {} => execute_stake(deps, env, info),
-        ExecuteMsg::Unstake { amount } => execute_unstake(deps, env, info, amount),
-        ExecuteMsg::Claim {} => execute_claim(deps, env, info),
+        ExecuteMsg::Stake { recipient } => execute_stake(deps, env, info, recipient),
+        ExecuteMsg::Unstake { amount, denom } => execute_unstake(deps, env, info, amount, denom),
+        ExecuteMsg::Claim { limit } => execute_claim(deps, env, info, limit),
+        ExecuteMsg::CancelUnbonding { amount, denom } => {
+            execute_cancel_unbonding(deps, env, info, amount, denom)
+        }
         ExecuteMsg::UpdateConfig {
             owner,
             manager,
             duration,
-        } => execute_update_config(info, deps, owner, manager, duration),
+            max_claims,
+        } => execute_update_config(info, deps, owner, manager, duration, max_claims),
         ExecuteMsg::AddHook { addr } => execute_add_hook(deps, env, info, addr),
         ExecuteMsg::RemoveHook { addr } => execute_remove_hook(deps, env, info, addr),
+        ExecuteMsg::UpdateHookGasLimit { gas_limit } => {
+            execute_update_hook_gas_limit(deps, info, gas_limit)
+        }
+        ExecuteMsg::ReplayFailedHook { id } => execute_replay_failed_hook(deps, info, id),
+        ExecuteMsg::ClearFailedHook { id } => execute_clear_failed_hook(deps, info, id),
+        ExecuteMsg::Delegate { to } => execute_delegate(deps, env, info, to),
+        ExecuteMsg::Undelegate {} => execute_undelegate(deps, env, info),
+        ExecuteMsg::SetAcceptStakes { enabled } => execute_set_accept_stakes(deps, info, enabled),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        id if id >= HOOK_REPLY_ID_OFFSET => Ok(staking::reply_hook(deps, &env, msg)?),
+        id => Err(ContractError::Std(StdError::generic_err(format!(
+            "unknown reply id: {}",
+            id
+        )))),
     }
 }
 
@@ -117,6 +116,7 @@ pub fn execute_update_config(
     new_owner: Option<String>,
     new_manager: Option<String>,
     duration: Option<Duration>,
+    max_claims: Option<u64>,
 ) -> Result<Response, ContractError> {
     let new_owner = new_owner
         .map(|new_owner| deps.api.addr_validate(&new_owner))
@@ -132,12 +132,14 @@ pub fn execute_update_config(
         return Err(ContractError::OnlyOwnerCanChangeOwner {});
     };
 
-    validate_duration(duration)?;
+    staking::validate_duration(duration)?;
+    staking::validate_max_claims(max_claims)?;
 
     config.owner = new_owner;
     config.manager = new_manager;
 
     config.unstaking_duration = duration;
+    config.max_claims = max_claims;
 
     CONFIG.save(deps.storage, &config)?;
     Ok(Response::new()
@@ -162,45 +164,24 @@ pub fn execute_stake(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    recipient: Option<String>,
 ) -> Result<Response, ContractError> {
+    let recipient = recipient
+        .map(|recipient| deps.api.addr_validate(&recipient))
+        .transpose()?;
+    if basket::is_basket(deps.storage) {
+        let recipient = recipient.unwrap_or_else(|| info.sender.clone());
+        return basket::execute_stake(deps, &env, info, recipient);
+    }
     let config = CONFIG.load(deps.storage)?;
-    let amount = must_pay(&info, &config.denom)?;
-    let balance = BALANCE.load(deps.storage)?;
-    let staked_total = STAKED_TOTAL.load(deps.storage)?;
-    let amount_to_stake = if staked_total == Uint128::zero() || balance == Uint128::zero() {
-        amount
-    } else {
-        staked_total
-            .checked_mul(amount)
-            .map_err(StdError::overflow)?
-            .checked_div(balance)
-            .map_err(StdError::divide_by_zero)?
-    };
-    STAKED_BALANCES.update(
-        deps.storage,
-        &info.sender,
-        env.block.height,
-        |balance| -> StdResult<Uint128> {
-            Ok(balance.unwrap_or_default().checked_add(amount_to_stake)?)
-        },
-    )?;
-    STAKED_TOTAL.update(
-        deps.storage,
-        env.block.height,
-        |total| -> StdResult<Uint128> {
-            Ok(total.unwrap_or_default().checked_add(amount_to_stake)?)
-        },
-    )?;
-    BALANCE.save(
-        deps.storage,
-        &balance.checked_add(amount).map_err(StdError::overflow)?,
-    )?;
-    let hook_msgs = stake_hook_msgs(deps.storage, info.sender.clone(), amount_to_stake)?;
-    Ok(Response::new()
-        .add_submessages(hook_msgs)
-        .add_attribute("action", "stake")
-        .add_attribute("from", info.sender)
-        .add_attribute("amount", amount))
+    let amount = NativeAsset(config.denom).amount_in(&info, None)?;
+    Ok(staking::execute_stake(
+        deps,
+        &env,
+        info.sender,
+        recipient,
+        amount,
+    )?)
 }
 
 pub fn execute_unstake(
@@ -208,98 +189,75 @@ pub fn execute_unstake(
     env: Env,
     info: MessageInfo,
     amount: Uint128,
+    denom: Option<String>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let balance = BALANCE.load(deps.storage)?;
-    let staked_total = STAKED_TOTAL.load(deps.storage)?;
-    let amount_to_claim = amount
-        .checked_mul(balance)
-        .map_err(StdError::overflow)?
-        .checked_div(staked_total)
-        .map_err(|_e| ContractError::InvalidUnstakeAmount {})?;
-    STAKED_BALANCES.update(
-        deps.storage,
-        &info.sender,
-        env.block.height,
-        |balance| -> Result<Uint128, ContractError> {
-            balance
-                .unwrap_or_default()
-                .checked_sub(amount)
-                .map_err(|_e| ContractError::InvalidUnstakeAmount {})
-        },
-    )?;
-    STAKED_TOTAL.update(
-        deps.storage,
-        env.block.height,
-        |total| -> Result<Uint128, ContractError> {
-            total
-                .unwrap_or_default()
-                .checked_sub(amount)
-                .map_err(|_e| ContractError::InvalidUnstakeAmount {})
-        },
-    )?;
-    BALANCE.update(deps.storage, |bal| -> Result<Uint128, ContractError> {
-        bal.checked_sub(amount_to_claim)
-            .map_err(|_e| ContractError::InvalidUnstakeAmount {})
-    })?;
-    let hook_msgs = unstake_hook_msgs(deps.storage, info.sender.clone(), amount)?;
-    match config.unstaking_duration {
-        None => {
-            let msg = CosmosMsg::Bank(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: coins(amount_to_claim.u128(), config.denom),
-            });
-            Ok(Response::new()
-                .add_message(msg)
-                .add_submessages(hook_msgs)
-                .add_attribute("action", "unstake")
-                .add_attribute("from", info.sender)
-                .add_attribute("amount", amount)
-                .add_attribute("claim_duration", "None"))
-        }
-        Some(duration) => {
-            let outstanding_claims = CLAIMS.query_claims(deps.as_ref(), &info.sender)?.claims;
-            if outstanding_claims.len() >= MAX_CLAIMS as usize {
-                return Err(ContractError::TooManyClaims {});
-            }
-
-            CLAIMS.create_claim(
-                deps.storage,
-                &info.sender,
-                amount_to_claim,
-                duration.after(&env.block),
-            )?;
-            Ok(Response::new()
-                .add_attribute("action", "unstake")
-                .add_submessages(hook_msgs)
-                .add_attribute("from", info.sender)
-                .add_attribute("amount", amount)
-                .add_attribute("claim_duration", format!("{}", duration)))
-        }
+    let denom = denom.unwrap_or_else(|| config.denom.clone());
+    if basket::is_basket(deps.storage) {
+        return basket::execute_unstake(
+            deps,
+            &env,
+            info.sender,
+            denom,
+            amount,
+            config.unstaking_duration,
+            config.max_claims.unwrap_or(MAX_CLAIMS),
+        );
+    }
+    if denom != config.denom {
+        return Err(ContractError::InvalidStakeDenom { denom });
     }
+    Ok(staking::execute_unstake(
+        deps,
+        &env,
+        &NativeAsset(config.denom),
+        info.sender,
+        amount,
+        config.unstaking_duration,
+        config.max_claims.unwrap_or(MAX_CLAIMS),
+    )?)
 }
 
 pub fn execute_claim(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    limit: Option<u32>,
 ) -> Result<Response, ContractError> {
-    let release = CLAIMS.claim_tokens(deps.storage, &info.sender, &env.block, None)?;
-    if release.is_zero() {
-        return Err(ContractError::NothingToClaim {});
+    if basket::is_basket(deps.storage) {
+        return basket::execute_claim(deps, &env, info.sender, limit);
     }
-
     let config = CONFIG.load(deps.storage)?;
-    let msg = CosmosMsg::Bank(BankMsg::Send {
-        to_address: info.sender.to_string(),
-        amount: coins(release.u128(), config.denom),
-    });
+    Ok(staking::execute_claim(
+        deps,
+        &env,
+        &NativeAsset(config.denom),
+        info.sender,
+        limit,
+    )?)
+}
 
-    Ok(Response::new()
-        .add_message(msg)
-        .add_attribute("action", "claim")
-        .add_attribute("from", info.sender)
-        .add_attribute("amount", release))
+pub fn execute_cancel_unbonding(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let denom = denom.unwrap_or_else(|| config.denom.clone());
+    if basket::is_basket(deps.storage) {
+        return basket::execute_cancel_unbonding(deps, &env, info.sender, denom, amount);
+    }
+    if denom != config.denom {
+        return Err(ContractError::InvalidStakeDenom { denom });
+    }
+    Ok(staking::execute_cancel_unbonding(
+        deps,
+        &env,
+        info.sender,
+        amount,
+    )?)
 }
 
 pub fn execute_fund(
@@ -307,15 +265,12 @@ pub fn execute_fund(
     _env: Env,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
+    if basket::is_basket(deps.storage) {
+        return basket::execute_fund(deps.storage, info);
+    }
     let config = CONFIG.load(deps.storage)?;
-    let amount = must_pay(&info, &config.denom)?;
-    BALANCE.update(deps.storage, |balance| -> StdResult<_> {
-        balance.checked_add(amount).map_err(StdError::overflow)
-    })?;
-    Ok(Response::new()
-        .add_attribute("action", "fund")
-        .add_attribute("from", info.sender)
-        .add_attribute("amount", amount))
+    let amount = NativeAsset(config.denom).amount_in(&info, None)?;
+    Ok(staking::execute_fund(deps.storage, info.sender, amount)?)
 }
 
 pub fn execute_add_hook(
@@ -329,10 +284,7 @@ pub fn execute_add_hook(
     if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
         return Err(ContractError::Unauthorized {});
     };
-    HOOKS.add_hook(deps.storage, addr.clone())?;
-    Ok(Response::new()
-        .add_attribute("action", "add_hook")
-        .add_attribute("hook", addr))
+    Ok(staking::execute_add_hook(deps.storage, addr)?)
 }
 
 pub fn execute_remove_hook(
@@ -346,127 +298,139 @@ pub fn execute_remove_hook(
     if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
         return Err(ContractError::Unauthorized {});
     };
-    HOOKS.remove_hook(deps.storage, addr.clone())?;
-    Ok(Response::new()
-        .add_attribute("action", "remove_hook")
-        .add_attribute("hook", addr))
+    Ok(staking::execute_remove_hook(deps.storage, addr)?)
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
-        QueryMsg::StakedBalanceAtHeight { address, height } => {
-            to_binary(&query_staked_balance_at_height(deps, env, address, height)?)
-        }
-        QueryMsg::TotalStakedAtHeight { height } => {
-            to_binary(&query_total_staked_at_height(deps, env, height)?)
-        }
-        QueryMsg::StakedValue { address } => to_binary(&query_staked_value(deps, env, address)?),
-        QueryMsg::TotalValue {} => to_binary(&query_total_value(deps, env)?),
-        QueryMsg::Claims { address } => to_binary(&query_claims(deps, address)?),
-        QueryMsg::GetHooks {} => to_binary(&query_hooks(deps)?),
-        QueryMsg::ListStakers { start_after, limit } => {
-            query_list_stakers(deps, start_after, limit)
-        }
-    }
-}
-
-pub fn query_staked_balance_at_height(
-    deps: Deps,
-    _env: Env,
-    address: String,
-    height: Option<u64>,
-) -> StdResult<StakedBalanceAtHeightResponse> {
-    let address = deps.api.addr_validate(&address)?;
-    let height = height.unwrap_or(_env.block.height);
-    let balance = STAKED_BALANCES
-        .may_load_at_height(deps.storage, &address, height)?
-        .unwrap_or_default();
-    Ok(StakedBalanceAtHeightResponse { balance, height })
+pub fn execute_update_hook_gas_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    gas_limit: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    };
+    Ok(staking::execute_update_hook_gas_limit(
+        deps.storage,
+        gas_limit,
+    )?)
 }
 
-pub fn query_total_staked_at_height(
-    deps: Deps,
-    _env: Env,
-    height: Option<u64>,
-) -> StdResult<TotalStakedAtHeightResponse> {
-    let height = height.unwrap_or(_env.block.height);
-    let total = STAKED_TOTAL
-        .may_load_at_height(deps.storage, height)?
-        .unwrap_or_default();
-    Ok(TotalStakedAtHeightResponse { total, height })
+pub fn execute_replay_failed_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    };
+    Ok(staking::execute_replay_failed_hook(deps.storage, id)?)
 }
 
-pub fn query_staked_value(
-    deps: Deps,
-    _env: Env,
-    address: String,
-) -> StdResult<StakedValueResponse> {
-    let address = deps.api.addr_validate(&address)?;
-    let balance = BALANCE.load(deps.storage).unwrap_or_default();
-    let staked = STAKED_BALANCES
-        .load(deps.storage, &address)
-        .unwrap_or_default();
-    let total = STAKED_TOTAL.load(deps.storage)?;
-    if balance == Uint128::zero() || staked == Uint128::zero() || total == Uint128::zero() {
-        Ok(StakedValueResponse {
-            value: Uint128::zero(),
-        })
-    } else {
-        let value = staked
-            .checked_mul(balance)
-            .map_err(StdError::overflow)?
-            .checked_div(total)
-            .map_err(StdError::divide_by_zero)?;
-        Ok(StakedValueResponse { value })
-    }
+pub fn execute_clear_failed_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    };
+    Ok(staking::execute_clear_failed_hook(deps.storage, id)?)
 }
 
-pub fn query_total_value(deps: Deps, _env: Env) -> StdResult<TotalValueResponse> {
-    let balance = BALANCE.load(deps.storage)?;
-    Ok(TotalValueResponse { total: balance })
+pub fn execute_delegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: String,
+) -> Result<Response, ContractError> {
+    let to = deps.api.addr_validate(&to)?;
+    Ok(delegation::execute_delegate(deps, &env, info.sender, to)?)
 }
 
-pub fn query_config(deps: Deps) -> StdResult<Config> {
-    let config = CONFIG.load(deps.storage)?;
-    Ok(config)
+pub fn execute_undelegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    Ok(delegation::execute_undelegate(deps, &env, info.sender)?)
 }
 
-pub fn query_claims(deps: Deps, address: String) -> StdResult<ClaimsResponse> {
-    CLAIMS.query_claims(deps, &deps.api.addr_validate(&address)?)
+pub fn execute_set_accept_stakes(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    Ok(staking::execute_set_accept_stakes(
+        deps.storage,
+        info.sender,
+        enabled,
+    )?)
 }
 
-pub fn query_hooks(deps: Deps) -> StdResult<GetHooksResponse> {
-    Ok(GetHooksResponse {
-        hooks: HOOKS.query_hooks(deps)?.hooks,
-    })
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
+        QueryMsg::StakedBalanceAtHeight { address, height } => to_binary(
+            &staking::query_staked_balance_at_height(deps, &env, address, height)?,
+        ),
+        QueryMsg::TotalStakedAtHeight { height } => {
+            to_binary(&staking::query_total_staked_at_height(deps, &env, height)?)
+        }
+        QueryMsg::StakedValue { address } => {
+            if basket::is_basket(deps.storage) {
+                to_binary(&basket::query_staked_value(deps, address)?)
+            } else {
+                to_binary(&staking::query_staked_value(deps, address)?)
+            }
+        }
+        QueryMsg::TotalValue {} => {
+            if basket::is_basket(deps.storage) {
+                to_binary(&basket::query_total_value(deps)?)
+            } else {
+                to_binary(&staking::query_total_value(deps)?)
+            }
+        }
+        QueryMsg::Claims { address } => {
+            if basket::is_basket(deps.storage) {
+                to_binary(&basket::query_claims(deps, address)?)
+            } else {
+                to_binary(&staking::query_claims(deps, address)?)
+            }
+        }
+        QueryMsg::GetHooks {} => to_binary(&staking::query_hooks(deps)?),
+        QueryMsg::FailedHooks { start_after, limit } => {
+            to_binary(&staking::query_failed_hooks(deps, start_after, limit)?)
+        }
+        QueryMsg::ListStakers { start_after, limit } => {
+            to_binary(&staking::query_list_stakers(deps, start_after, limit)?)
+        }
+        QueryMsg::VotingPowerAtHeight { address, height } => to_binary(
+            &staking::query_voting_power_at_height(deps, &env, address, height)?,
+        ),
+        QueryMsg::TotalPowerAtHeight { height } => {
+            to_binary(&staking::query_total_power_at_height(deps, &env, height)?)
+        }
+        QueryMsg::Dao {} => to_binary(&staking::query_dao(deps)?),
+        QueryMsg::Info {} => to_binary(&staking::query_info(deps)?),
+        QueryMsg::Delegation { address } => {
+            to_binary(&delegation::query_delegation(deps, address)?)
+        }
+        QueryMsg::AcceptsStakes { address } => {
+            to_binary(&staking::query_accepts_stakes(deps, address)?)
+        }
+        QueryMsg::Basket {} => to_binary(&basket::query_basket(deps)?),
+        QueryMsg::BasketStake { address } => to_binary(&basket::query_basket_stake(deps, address)?),
+        QueryMsg::BasketClaims { address } => {
+            to_binary(&basket::query_basket_claims(deps, address)?)
+        }
+    }
 }
 
-pub fn query_list_stakers(
-    deps: Deps,
-    start_after: Option<String>,
-    limit: Option<u32>,
-) -> StdResult<Binary> {
-    let start_at = start_after
-        .map(|addr| deps.api.addr_validate(&addr))
-        .transpose()?;
-
-    let stakers = cw_paginate::paginate_snapshot_map(
-        deps,
-        &STAKED_BALANCES,
-        start_at.as_ref(),
-        limit,
-        cosmwasm_std::Order::Ascending,
-    )?;
-
-    let stakers = stakers
-        .into_iter()
-        .map(|(address, balance)| StakerBalanceResponse {
-            address: address.into_string(),
-            balance,
-        })
-        .collect();
-
-    to_binary(&ListStakersResponse { stakers })
+pub fn query_config(deps: Deps) -> StdResult<Config> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(config)
 }
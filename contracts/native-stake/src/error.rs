@@ -1,5 +1,6 @@
-use cosmwasm_std::{Addr, StdError};
+use cosmwasm_std::StdError;
 use cw_utils::PaymentError;
+use staking_core::StakingError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -8,22 +9,18 @@ pub enum ContractError {
     Std(#[from] StdError),
     #[error("{0}")]
     PaymentError(#[from] PaymentError),
-    #[error("Nothing to claim")]
-    NothingToClaim {},
-    #[error("Invalid token")]
-    InvalidToken { received: Addr, expected: Addr },
     #[error("Unauthorized")]
     Unauthorized {},
-    #[error("Too many outstanding claims. Claim some tokens before unstaking more.")]
-    TooManyClaims {},
     #[error("No admin configured")]
     NoAdminConfigured {},
     #[error("{0}")]
     HookError(#[from] cw_controllers::HookError),
+    #[error("{0}")]
+    Staking(#[from] StakingError),
     #[error("Only owner can change owner")]
     OnlyOwnerCanChangeOwner {},
-    #[error("Invalid unstaking duration, unstaking duration cannot be 0")]
-    InvalidUnstakingDuration {},
-    #[error("Can only unstake less than or equal to the amount you have staked")]
-    InvalidUnstakeAmount {},
+    #[error("Basket denoms must be unique, have non-zero weights and include the staked denom")]
+    InvalidBasket {},
+    #[error("Denom {denom} can not be staked")]
+    InvalidStakeDenom { denom: String },
 }
@@ -1,6 +1,6 @@
+pub mod basket;
 pub mod contract;
 mod error;
-pub mod hooks;
 pub mod msg;
 pub mod state;
 
@@ -8,3 +8,4 @@ pub mod state;
 mod tests;
 
 pub use crate::error::ContractError;
+pub use staking_core::hooks;
@@ -1,10 +1,18 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cw_utils::Duration;
 
-pub use cw_controllers::ClaimsResponse;
+use crate::state::BasketClaim;
+
+pub use staking_core::msg::{
+    AcceptsStakesResponse, ClaimsResponse, DelegationResponse, FailedHookResponse,
+    FailedHooksResponse, GetHooksResponse, InfoResponse, ListStakersResponse,
+    StakedBalanceAtHeightResponse, StakedValueResponse, StakerBalanceResponse,
+    TotalPowerAtHeightResponse, TotalStakedAtHeightResponse, TotalValueResponse,
+    VotingPowerAtHeightResponse,
+};
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct InstantiateMsg {
@@ -14,21 +22,44 @@ pub struct InstantiateMsg {
     pub manager: Option<String>,
     pub denom: String,
     pub unstaking_duration: Option<Duration>,
+    // Stakes each of these denoms, weighted in voting power, instead of only denom. Must include denom.
+    pub basket: Option<Vec<BasketDenom>>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct BasketDenom {
+    pub denom: String,
+    pub weight: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Fund {},
-    Stake {},
+    // Stakes for `recipient` if given, otherwise for the sender.
+    Stake {
+        recipient: Option<String>,
+    },
     Unstake {
         amount: Uint128,
+        // The basket denom to unstake, defaults to the configured denom.
+        denom: Option<String>,
+    },
+    // Claims at most `limit` matured claims, or all of them.
+    Claim {
+        limit: Option<u32>,
+    },
+    // Restakes unbonding claims which have not matured yet.
+    CancelUnbonding {
+        amount: Uint128,
+        // The basket denom to restake, defaults to the configured denom.
+        denom: Option<String>,
     },
-    Claim {},
     UpdateConfig {
         owner: Option<String>,
         manager: Option<String>,
         duration: Option<Duration>,
+        max_claims: Option<u64>,
     },
     AddHook {
         addr: String,
@@ -36,6 +67,25 @@ pub enum ExecuteMsg {
     RemoveHook {
         addr: String,
     },
+    UpdateHookGasLimit {
+        gas_limit: Option<u64>,
+    },
+    // Resends the message of a failed hook, removing it from the failure log if it succeeds.
+    ReplayFailedHook {
+        id: u64,
+    },
+    ClearFailedHook {
+        id: u64,
+    },
+    // Delegates the sender's voting power, replacing any earlier delegation.
+    Delegate {
+        to: String,
+    },
+    Undelegate {},
+    // Opts in or out of being staked for by other addresses.
+    SetAcceptStakes {
+        enabled: bool,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -55,6 +105,7 @@ pub enum QueryMsg {
     TotalStakedAtHeight {
         height: Option<u64>,
     },
+    // In basket mode the value and claims of each denom are weighted like voting power.
     StakedValue {
         address: String,
     },
@@ -64,10 +115,38 @@ pub enum QueryMsg {
         address: String,
     },
     GetHooks {},
+    FailedHooks {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     ListStakers {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    // DAO DAO voting module queries, answered from the staked balances.
+    VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    TotalPowerAtHeight {
+        height: Option<u64>,
+    },
+    Dao {},
+    Info {},
+    Delegation {
+        address: String,
+    },
+    AcceptsStakes {
+        address: String,
+    },
+    Basket {},
+    // Unweighted staked balance and value of an address in each basket denom.
+    BasketStake {
+        address: String,
+    },
+    BasketClaims {
+        address: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -79,45 +158,35 @@ pub enum MigrateMsg {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct StakedBalanceAtHeightResponse {
-    pub balance: Uint128,
-    pub height: u64,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct TotalStakedAtHeightResponse {
-    pub total: Uint128,
-    pub height: u64,
+pub struct BasketResponse {
+    pub denoms: Vec<BasketDenomResponse>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct StakedValueResponse {
-    pub value: Uint128,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct TotalValueResponse {
-    pub total: Uint128,
+pub struct BasketDenomResponse {
+    pub denom: String,
+    pub weight: Decimal,
+    pub balance: Uint128,
+    pub staked_total: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct GetHooksResponse {
-    pub hooks: Vec<String>,
+pub struct BasketStakeResponse {
+    pub stakes: Vec<DenomStakeResponse>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct ListStakersResponse {
-    pub stakers: Vec<StakerBalanceResponse>,
+pub struct DenomStakeResponse {
+    pub denom: String,
+    pub staked: Uint128,
+    pub value: Uint128,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct StakerBalanceResponse {
-    pub address: String,
-    pub balance: Uint128,
+pub struct BasketClaimsResponse {
+    pub claims: Vec<BasketClaim>,
 }
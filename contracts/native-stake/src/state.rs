@@ -1,11 +1,14 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
-use cw_controllers::Claims;
-use cw_controllers::Hooks;
-use cw_storage_plus::{Item, SnapshotItem, SnapshotMap, Strategy};
-use cw_utils::Duration;
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_storage_plus::{Item, Map};
+use cw_utils::{Duration, Expiration};
+
+pub use staking_core::state::{
+    FailedHook, BALANCE, CLAIMS, DAO, FAILED_HOOKS, FAILED_HOOK_COUNT, HOOKS, HOOK_GAS_LIMIT,
+    MAX_CLAIMS, STAKED_BALANCES, STAKED_TOTAL,
+};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Config {
@@ -13,30 +16,31 @@ pub struct Config {
     pub manager: Option<Addr>,
     pub denom: String,
     pub unstaking_duration: Option<Duration>,
+    // Outstanding claims allowed per staker, `MAX_CLAIMS` if unset.
+    pub max_claims: Option<u64>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
-pub const STAKED_BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
-    "staked_balances",
-    "staked_balance__checkpoints",
-    "staked_balance__changelog",
-    Strategy::EveryBlock,
-);
+/// Voting power weight of each denom which can be staked in basket mode.
+/// Empty unless the contract was instantiated with a basket.
+pub const BASKET: Map<&str, Decimal> = Map::new("basket");
 
-pub const STAKED_TOTAL: SnapshotItem<Uint128> = SnapshotItem::new(
-    "total_staked",
-    "total_staked__checkpoints",
-    "total_staked__changelog",
-    Strategy::EveryBlock,
-);
+/// Amount of each basket denom backing its staked balances.
+pub const BASKET_BALANCES: Map<&str, Uint128> = Map::new("basket_balances");
 
-/// The maximum number of claims that may be outstanding.
-pub const MAX_CLAIMS: u64 = 100;
+/// Total staked balance of each basket denom, before weighting.
+pub const BASKET_STAKED_TOTALS: Map<&str, Uint128> = Map::new("basket_staked_totals");
 
-pub const CLAIMS: Claims = Claims::new("claims");
+/// Staked balance of each staker in each basket denom, before weighting.
+/// `STAKED_BALANCES` holds the weighted sum used as voting power.
+pub const BASKET_STAKED_BALANCES: Map<(&Addr, &str), Uint128> = Map::new("basket_staked_balances");
 
-pub const BALANCE: Item<Uint128> = Item::new("balance");
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BasketClaim {
+    pub denom: String,
+    pub amount: Uint128,
+    pub release_at: Expiration,
+}
 
-// Hooks to contracts that will receive staking and unstaking messages
-pub const HOOKS: Hooks = Hooks::new("hooks");
+pub const BASKET_CLAIMS: Map<&Addr, Vec<BasketClaim>> = Map::new("basket_claims");
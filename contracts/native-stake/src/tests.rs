@@ -1,23 +1,34 @@
 use std::borrow::BorrowMut;
+use std::str::FromStr;
 
 use crate::msg::{
-    ExecuteMsg, InstantiateMsg, ListStakersResponse, QueryMsg, StakedBalanceAtHeightResponse,
-    StakedValueResponse, StakerBalanceResponse, TotalStakedAtHeightResponse, TotalValueResponse,
+    AcceptsStakesResponse, BasketClaimsResponse, BasketDenom, BasketResponse, BasketStakeResponse,
+    DelegationResponse, DenomStakeResponse, ExecuteMsg, FailedHooksResponse, GetHooksResponse,
+    InfoResponse, InstantiateMsg, ListStakersResponse, QueryMsg, StakedBalanceAtHeightResponse,
+    StakedValueResponse, StakerBalanceResponse, TotalPowerAtHeightResponse,
+    TotalStakedAtHeightResponse, TotalValueResponse, VotingPowerAtHeightResponse,
 };
 use crate::state::Config;
+use crate::ContractError;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{coins, Addr, Coin, Empty, Uint128};
+use cosmwasm_std::{
+    coins, to_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128,
+};
 use cw_controllers::ClaimsResponse;
 use cw_multi_test::{
     custom_app, next_block, App, AppResponse, Contract, ContractWrapper, Executor,
 };
 use cw_utils::Duration;
+use staking_core::state::{SCORES, TOTAL_SCORE};
+use staking_core::StakingError;
 
 const DAO_ADDR: &str = "dao";
 const ADDR1: &str = "addr1";
 const ADDR2: &str = "addr2";
 const DENOM: &str = "ujuno";
 const INVALID_DENOM: &str = "uinvalid";
+const LP_DENOM: &str = "gamm/pool/1";
 
 fn query_staked_balance<T: Into<String>, U: Into<String>>(
     app: &App,
@@ -63,10 +74,24 @@ fn staking_contract() -> Box<dyn Contract<Empty>> {
         crate::contract::execute,
         crate::contract::instantiate,
         crate::contract::query,
-    );
+    )
+    .with_reply(crate::contract::reply);
     Box::new(contract)
 }
 
+fn failing_hook_contract() -> Box<dyn Contract<Empty>> {
+    fn execute(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+        Err(StdError::generic_err("hook failed"))
+    }
+    fn instantiate(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+    fn query(_: Deps, _: Env, _: Empty) -> StdResult<Binary> {
+        to_binary(&Empty {})
+    }
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
 fn mock_app() -> App {
     custom_app(|r, _a, s| {
         r.bank
@@ -142,7 +167,7 @@ fn stake_tokens(
     app.execute_contract(
         Addr::unchecked(sender),
         staking_addr.clone(),
-        &ExecuteMsg::Stake {},
+        &ExecuteMsg::Stake { recipient: None },
         &coins(amount, denom),
     )
 }
@@ -158,6 +183,7 @@ fn unstake_tokens(
         staking_addr.clone(),
         &ExecuteMsg::Unstake {
             amount: Uint128::new(amount),
+            denom: None,
         },
         &[],
     )
@@ -167,7 +193,7 @@ fn claim(app: &mut App, staking_addr: Addr, sender: &str) -> anyhow::Result<AppR
     app.execute_contract(
         Addr::unchecked(sender),
         staking_addr,
-        &ExecuteMsg::Claim {},
+        &ExecuteMsg::Claim { limit: None },
         &[],
     )
 }
@@ -187,6 +213,7 @@ fn update_config(
             owner,
             manager,
             duration,
+            max_claims: None,
         },
         &[],
     )
@@ -221,6 +248,7 @@ fn test_instantiate() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -233,6 +261,7 @@ fn test_instantiate() {
             manager: None,
             denom: DENOM.to_string(),
             unstaking_duration: None,
+            basket: None,
         },
     );
 }
@@ -250,6 +279,7 @@ fn test_instantiate_dao_owner() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -272,6 +302,7 @@ fn test_instantiate_invalid_unstaking_duration() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(0)),
+            basket: None,
         },
     );
 
@@ -284,6 +315,7 @@ fn test_instantiate_invalid_unstaking_duration() {
             manager: None,
             denom: DENOM.to_string(),
             unstaking_duration: None,
+            basket: None,
         },
     );
 }
@@ -301,6 +333,7 @@ fn test_stake_invalid_denom() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -320,6 +353,7 @@ fn test_stake_valid_denom() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -341,6 +375,7 @@ fn test_unstake_none_staked() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -360,6 +395,7 @@ fn test_unstake_invalid_balance() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -383,6 +419,7 @@ fn test_unstake() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -418,6 +455,7 @@ fn test_unstake_no_unstaking_duration() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: None,
+            basket: None,
         },
     );
 
@@ -455,6 +493,7 @@ fn test_claim_no_claims() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -474,6 +513,7 @@ fn test_claim_claim_not_reached() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -501,6 +541,7 @@ fn test_claim() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -552,6 +593,7 @@ fn test_update_config_invalid_sender() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -580,6 +622,7 @@ fn test_update_config_non_owner_changes_owner() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -599,6 +642,7 @@ fn test_update_config_as_owner() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -619,6 +663,7 @@ fn test_update_config_as_owner() {
             owner: Some(Addr::unchecked(ADDR1)),
             manager: Some(Addr::unchecked(DAO_ADDR)),
             unstaking_duration: Some(Duration::Height(10)),
+            max_claims: None,
             denom: DENOM.to_string(),
         },
         config
@@ -637,6 +682,7 @@ fn test_update_config_as_manager() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -657,6 +703,7 @@ fn test_update_config_as_manager() {
             owner: Some(Addr::unchecked(DAO_ADDR)),
             manager: Some(Addr::unchecked(ADDR2)),
             unstaking_duration: Some(Duration::Height(10)),
+            max_claims: None,
             denom: DENOM.to_string(),
         },
         config
@@ -676,6 +723,7 @@ fn test_update_config_invalid_duration() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -703,6 +751,7 @@ fn test_query_claims() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -739,6 +788,7 @@ fn test_query_get_config() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -749,6 +799,7 @@ fn test_query_get_config() {
             owner: Some(Addr::unchecked(DAO_ADDR)),
             manager: Some(Addr::unchecked(ADDR1)),
             unstaking_duration: Some(Duration::Height(5)),
+            max_claims: None,
             denom: DENOM.to_string(),
         }
     )
@@ -766,6 +817,7 @@ fn test_query_list_stakers() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
+            basket: None,
         },
     );
 
@@ -907,6 +959,7 @@ fn test_auto_compounding_staking() {
             manager: Some(ADDR1.to_string()),
             denom: DENOM.to_string(),
             unstaking_duration: None,
+            basket: None,
         },
     );
     app.update_block(next_block);
@@ -1103,3 +1156,734 @@ fn test_auto_compounding_staking() {
         "Balance should be 65 after unstaking"
     );
 }
+
+#[test]
+fn test_failed_hooks() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let hook_id = app.store_code(failing_hook_contract());
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            basket: None,
+        },
+    );
+    let hook_addr = app
+        .instantiate_contract(
+            hook_id,
+            Addr::unchecked(DAO_ADDR),
+            &Empty {},
+            &[],
+            "hook",
+            None,
+        )
+        .unwrap();
+    app.execute_contract(
+        Addr::unchecked(DAO_ADDR),
+        staking_addr.clone(),
+        &ExecuteMsg::AddHook {
+            addr: hook_addr.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(DAO_ADDR),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateHookGasLimit {
+            gas_limit: Some(500_000),
+        },
+        &[],
+    )
+    .unwrap();
+    let hooks: GetHooksResponse = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::GetHooks {})
+        .unwrap();
+    assert_eq!(hooks.gas_limit, Some(500_000));
+
+    // Staking and unstaking go through despite the failing hook
+    stake_tokens(&mut app, &staking_addr, ADDR1, 100, DENOM).unwrap();
+    app.update_block(next_block);
+    unstake_tokens(&mut app, &staking_addr, ADDR1, 40).unwrap();
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR1),
+        Uint128::new(60)
+    );
+
+    let failed: FailedHooksResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::FailedHooks {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(failed.failed_hooks.len(), 2);
+    assert_eq!(failed.failed_hooks[0].id, 0);
+    assert_eq!(failed.failed_hooks[0].hook, hook_addr.to_string());
+    assert!(failed.failed_hooks[0].error.contains("hook failed"));
+    assert_eq!(failed.failed_hooks[1].id, 1);
+    assert_ne!(failed.failed_hooks[0].msg, failed.failed_hooks[1].msg);
+
+    let failed: FailedHooksResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::FailedHooks {
+                start_after: Some(0),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(failed.failed_hooks.len(), 1);
+    assert_eq!(failed.failed_hooks[0].id, 1);
+
+    // Only the owner or manager can replay or clear failed hooks
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADDR1),
+            staking_addr.clone(),
+            &ExecuteMsg::ClearFailedHook { id: 0 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // A replay which fails again reverts and keeps the failure logged
+    app.execute_contract(
+        Addr::unchecked(DAO_ADDR),
+        staking_addr.clone(),
+        &ExecuteMsg::ReplayFailedHook { id: 0 },
+        &[],
+    )
+    .unwrap_err();
+
+    app.execute_contract(
+        Addr::unchecked(DAO_ADDR),
+        staking_addr.clone(),
+        &ExecuteMsg::ClearFailedHook { id: 0 },
+        &[],
+    )
+    .unwrap();
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(DAO_ADDR),
+            staking_addr.clone(),
+            &ExecuteMsg::ReplayFailedHook { id: 0 },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Staking(StakingError::FailedHookNotFound { id: 0 })
+    );
+
+    let failed: FailedHooksResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::FailedHooks {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(failed.failed_hooks.len(), 1);
+    assert_eq!(failed.failed_hooks[0].id, 1);
+}
+
+fn mock_basket_app() -> App {
+    custom_app(|r, _a, s| {
+        for addr in [DAO_ADDR, ADDR1, ADDR2] {
+            r.bank
+                .init_balance(
+                    s,
+                    &Addr::unchecked(addr),
+                    vec![
+                        Coin {
+                            denom: LP_DENOM.to_string(),
+                            amount: Uint128::new(10000),
+                        },
+                        Coin {
+                            denom: INVALID_DENOM.to_string(),
+                            amount: Uint128::new(10000),
+                        },
+                        Coin {
+                            denom: DENOM.to_string(),
+                            amount: Uint128::new(10000),
+                        },
+                    ],
+                )
+                .unwrap();
+        }
+    })
+}
+
+fn basket_instantiate_msg(basket: Vec<(&str, &str)>) -> InstantiateMsg {
+    InstantiateMsg {
+        owner: Some(DAO_ADDR.to_string()),
+        manager: None,
+        denom: DENOM.to_string(),
+        unstaking_duration: Some(Duration::Height(5)),
+        basket: Some(
+            basket
+                .into_iter()
+                .map(|(denom, weight)| BasketDenom {
+                    denom: denom.to_string(),
+                    weight: Decimal::from_str(weight).unwrap(),
+                })
+                .collect(),
+        ),
+    }
+}
+
+#[test]
+fn test_basket_staking() {
+    let mut app = mock_basket_app();
+    let staking_id = app.store_code(staking_contract());
+
+    for basket in [
+        vec![(DENOM, "1"), (LP_DENOM, "0")],
+        vec![(DENOM, "1"), (DENOM, "2")],
+        vec![(LP_DENOM, "1")],
+    ] {
+        let err: ContractError = app
+            .instantiate_contract(
+                staking_id,
+                Addr::unchecked(DAO_ADDR),
+                &basket_instantiate_msg(basket),
+                &[],
+                "Staking",
+                None,
+            )
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(err, ContractError::InvalidBasket {});
+    }
+
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        basket_instantiate_msg(vec![(DENOM, "1"), (LP_DENOM, "2.5")]),
+    );
+
+    // Both denoms can be staked at once, each weighted in voting power
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::Stake { recipient: None },
+        &[Coin::new(40, LP_DENOM), Coin::new(100, DENOM)],
+    )
+    .unwrap();
+    stake_tokens(&mut app, &staking_addr, ADDR2, 10, LP_DENOM).unwrap();
+    let err: ContractError = stake_tokens(&mut app, &staking_addr, ADDR2, 10, INVALID_DENOM)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InvalidStakeDenom {
+            denom: INVALID_DENOM.to_string()
+        }
+    );
+    app.update_block(next_block);
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR1),
+        Uint128::new(200)
+    );
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR2),
+        Uint128::new(25)
+    );
+    assert_eq!(query_total_staked(&app, &staking_addr), Uint128::new(225));
+
+    // Funding raises the value of one denom without changing voting power
+    app.execute_contract(
+        Addr::unchecked(DAO_ADDR),
+        staking_addr.clone(),
+        &ExecuteMsg::Fund {},
+        &coins(20, LP_DENOM),
+    )
+    .unwrap();
+    let basket: BasketResponse = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::Basket {})
+        .unwrap();
+    assert_eq!(basket.denoms.len(), 2);
+    assert_eq!(basket.denoms[0].denom, LP_DENOM);
+    assert_eq!(basket.denoms[0].weight, Decimal::from_str("2.5").unwrap());
+    assert_eq!(basket.denoms[0].balance, Uint128::new(70));
+    assert_eq!(basket.denoms[0].staked_total, Uint128::new(50));
+    let stake: BasketStakeResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::BasketStake {
+                address: ADDR2.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        stake.stakes,
+        vec![DenomStakeResponse {
+            denom: LP_DENOM.to_string(),
+            staked: Uint128::new(10),
+            value: Uint128::new(14),
+        }]
+    );
+    // Summed values weigh each denom like its voting power
+    assert_eq!(
+        query_staked_value(&app, &staking_addr, ADDR2),
+        Uint128::new(35)
+    );
+    assert_eq!(
+        query_staked_value(&app, &staking_addr, ADDR1),
+        Uint128::new(240)
+    );
+    assert_eq!(query_total_value(&app, &staking_addr), Uint128::new(275));
+
+    // Unstaking one denom removes its weighted power and claims its value
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::Unstake {
+            amount: Uint128::new(20),
+            denom: Some(LP_DENOM.to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+    unstake_tokens(&mut app, &staking_addr, ADDR1, 50).unwrap();
+    app.update_block(next_block);
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR1),
+        Uint128::new(100)
+    );
+    assert_eq!(query_total_staked(&app, &staking_addr), Uint128::new(125));
+    // Scores accrued on the weighted power held over the first block
+    let score = SCORES
+        .query(&app.wrap(), staking_addr.clone(), &Addr::unchecked(ADDR1))
+        .unwrap();
+    assert_eq!(score.score, Uint128::new(1000));
+    let total_score = TOTAL_SCORE
+        .query(&app.wrap(), staking_addr.clone())
+        .unwrap();
+    assert_eq!(total_score.score, Uint128::new(1125));
+    let claims: BasketClaimsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::BasketClaims {
+                address: ADDR1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(claims.claims.len(), 2);
+    assert_eq!(claims.claims[0].amount, Uint128::new(28));
+    assert_eq!(claims.claims[1].amount, Uint128::new(50));
+    let weighted_claims = get_claims(&mut app, staking_addr.clone(), ADDR1.to_string());
+    assert_eq!(
+        weighted_claims
+            .claims
+            .iter()
+            .map(|claim| claim.amount)
+            .collect::<Vec<_>>(),
+        vec![Uint128::new(70), Uint128::new(50)]
+    );
+
+    let err: ContractError = claim(&mut app, staking_addr.clone(), ADDR1)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Staking(StakingError::NothingToClaim {}));
+    app.update_block(|b| b.height += 5);
+    claim(&mut app, staking_addr.clone(), ADDR1).unwrap();
+    assert_eq!(get_balance(&app, ADDR1, LP_DENOM), Uint128::new(9988));
+    assert_eq!(get_balance(&app, ADDR1, DENOM), Uint128::new(9950));
+}
+
+#[test]
+fn test_voting_module_queries() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: None,
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            basket: None,
+        },
+    );
+
+    stake_tokens(&mut app, &staking_addr, ADDR1, 100, DENOM).unwrap();
+    let staked_height = app.block_info().height;
+    app.update_block(next_block);
+    stake_tokens(&mut app, &staking_addr, ADDR2, 50, DENOM).unwrap();
+    app.update_block(next_block);
+
+    // Power is snapshotted like staked balances, so a stake counts from the
+    // block after it
+    let power: VotingPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::VotingPowerAtHeight {
+                address: ADDR1.to_string(),
+                height: Some(staked_height),
+            },
+        )
+        .unwrap();
+    assert_eq!(power.power, Uint128::zero());
+    let power: VotingPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::VotingPowerAtHeight {
+                address: ADDR1.to_string(),
+                height: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(power.power, Uint128::new(100));
+    assert_eq!(power.height, app.block_info().height);
+
+    let total: TotalPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::TotalPowerAtHeight {
+                height: Some(staked_height + 1),
+            },
+        )
+        .unwrap();
+    assert_eq!(total.power, Uint128::new(100));
+    let total: TotalPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::TotalPowerAtHeight { height: None },
+        )
+        .unwrap();
+    assert_eq!(total.power, Uint128::new(150));
+
+    let dao: Addr = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::Dao {})
+        .unwrap();
+    assert_eq!(dao, Addr::unchecked(DAO_ADDR));
+    let info: InfoResponse = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::Info {})
+        .unwrap();
+    assert_eq!(info.info.contract, crate::contract::CONTRACT_NAME);
+    assert_eq!(info.info.version, crate::contract::CONTRACT_VERSION);
+}
+
+fn query_voting_power(app: &App, staking_addr: &Addr, address: &str, height: Option<u64>) -> u128 {
+    let power: VotingPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            staking_addr,
+            &QueryMsg::VotingPowerAtHeight {
+                address: address.to_string(),
+                height,
+            },
+        )
+        .unwrap();
+    power.power.u128()
+}
+
+#[test]
+fn test_delegation() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: None,
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            basket: None,
+        },
+    );
+
+    stake_tokens(&mut app, &staking_addr, ADDR1, 100, DENOM).unwrap();
+    stake_tokens(&mut app, &staking_addr, ADDR2, 50, DENOM).unwrap();
+    app.update_block(next_block);
+    let before_delegation = app.block_info().height;
+
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADDR1),
+            staking_addr.clone(),
+            &ExecuteMsg::Delegate {
+                to: ADDR1.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Staking(StakingError::SelfDelegation {}));
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADDR1),
+            staking_addr.clone(),
+            &ExecuteMsg::Undelegate {},
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Staking(StakingError::NotDelegating {}));
+
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::Delegate {
+            to: ADDR2.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(next_block);
+
+    assert_eq!(query_voting_power(&app, &staking_addr, ADDR1, None), 0);
+    assert_eq!(query_voting_power(&app, &staking_addr, ADDR2, None), 150);
+    assert_eq!(
+        query_voting_power(&app, &staking_addr, ADDR1, Some(before_delegation)),
+        100
+    );
+    assert_eq!(
+        query_voting_power(&app, &staking_addr, ADDR2, Some(before_delegation)),
+        50
+    );
+    // Delegation moves voting power, not stake
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR1),
+        Uint128::new(100)
+    );
+    let total: TotalPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::TotalPowerAtHeight { height: None },
+        )
+        .unwrap();
+    assert_eq!(total.power, Uint128::new(150));
+
+    let delegation: DelegationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::Delegation {
+                address: ADDR1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        delegation,
+        DelegationResponse {
+            delegate: Some(ADDR2.to_string()),
+            delegated_power: Uint128::zero(),
+        }
+    );
+    let delegation: DelegationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::Delegation {
+                address: ADDR2.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        delegation,
+        DelegationResponse {
+            delegate: None,
+            delegated_power: Uint128::new(100),
+        }
+    );
+
+    // Stake added or removed while delegating follows the delegation
+    stake_tokens(&mut app, &staking_addr, ADDR1, 20, DENOM).unwrap();
+    unstake_tokens(&mut app, &staking_addr, ADDR1, 10).unwrap();
+    app.update_block(next_block);
+    assert_eq!(query_voting_power(&app, &staking_addr, ADDR1, None), 0);
+    assert_eq!(query_voting_power(&app, &staking_addr, ADDR2, None), 160);
+
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::Undelegate {},
+        &[],
+    )
+    .unwrap();
+    app.update_block(next_block);
+    assert_eq!(query_voting_power(&app, &staking_addr, ADDR1, None), 110);
+    assert_eq!(query_voting_power(&app, &staking_addr, ADDR2, None), 50);
+}
+
+#[test]
+fn test_cancel_unbonding() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: None,
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: Some(Duration::Height(10)),
+            basket: None,
+        },
+    );
+
+    stake_tokens(&mut app, &staking_addr, ADDR1, 100, DENOM).unwrap();
+    unstake_tokens(&mut app, &staking_addr, ADDR1, 30).unwrap();
+    app.update_block(next_block);
+    unstake_tokens(&mut app, &staking_addr, ADDR1, 20).unwrap();
+
+    let cancel = |app: &mut App, amount: u128| {
+        app.execute_contract(
+            Addr::unchecked(ADDR1),
+            staking_addr.clone(),
+            &ExecuteMsg::CancelUnbonding {
+                amount: Uint128::new(amount),
+                denom: None,
+            },
+            &[],
+        )
+    };
+    let err: ContractError = cancel(&mut app, 51).unwrap_err().downcast().unwrap();
+    assert_eq!(
+        err,
+        ContractError::Staking(StakingError::InvalidCancelAmount {})
+    );
+
+    // The newest claims are restaked first
+    cancel(&mut app, 25).unwrap();
+    app.update_block(next_block);
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR1),
+        Uint128::new(75)
+    );
+    assert_eq!(query_total_staked(&app, &staking_addr), Uint128::new(75));
+    let claims = get_claims(&mut app, staking_addr.clone(), ADDR1.to_string());
+    assert_eq!(claims.claims.len(), 1);
+    assert_eq!(claims.claims[0].amount, Uint128::new(25));
+
+    // Matured claims can only be claimed
+    app.update_block(|b| b.height += 10);
+    let err: ContractError = cancel(&mut app, 1).unwrap_err().downcast().unwrap();
+    assert_eq!(
+        err,
+        ContractError::Staking(StakingError::InvalidCancelAmount {})
+    );
+    claim(&mut app, staking_addr.clone(), ADDR1).unwrap();
+    assert_eq!(get_balance(&app, ADDR1, DENOM), Uint128::new(9925));
+}
+
+#[test]
+fn test_stake_for_recipient() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: None,
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: Some(Duration::Height(10)),
+            basket: None,
+        },
+    );
+
+    let stake_for = |app: &mut App, sender: &str, recipient: &str, amount: u128| {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            staking_addr.clone(),
+            &ExecuteMsg::Stake {
+                recipient: Some(recipient.to_string()),
+            },
+            &coins(amount, DENOM),
+        )
+    };
+    let set_accept_stakes = |app: &mut App, sender: &str, enabled: bool| {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            staking_addr.clone(),
+            &ExecuteMsg::SetAcceptStakes { enabled },
+            &[],
+        )
+    };
+    let accepts_stakes = |app: &App, address: &str| {
+        let resp: AcceptsStakesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                staking_addr.clone(),
+                &QueryMsg::AcceptsStakes {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        resp.enabled
+    };
+
+    // Stakes are accepted by default and credited to the recipient
+    assert!(accepts_stakes(&app, ADDR2));
+    stake_for(&mut app, ADDR1, ADDR2, 100).unwrap();
+    app.update_block(next_block);
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR2),
+        Uint128::new(100)
+    );
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR1),
+        Uint128::zero()
+    );
+    assert_eq!(get_balance(&app, ADDR1, DENOM), Uint128::new(9900));
+
+    // Once opted out, only the recipient itself can stake for it
+    set_accept_stakes(&mut app, ADDR2, false).unwrap();
+    assert!(!accepts_stakes(&app, ADDR2));
+    let err: ContractError = stake_for(&mut app, ADDR1, ADDR2, 1)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::Staking(StakingError::RecipientOptedOut {})
+    );
+    stake_for(&mut app, ADDR2, ADDR2, 50).unwrap();
+    stake_tokens(&mut app, &staking_addr, ADDR2, 50, DENOM).unwrap();
+    app.update_block(next_block);
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR2),
+        Uint128::new(200)
+    );
+
+    set_accept_stakes(&mut app, ADDR2, true).unwrap();
+    assert!(accepts_stakes(&app, ADDR2));
+    stake_for(&mut app, ADDR1, ADDR2, 1).unwrap();
+}
@@ -8,11 +8,15 @@ use cw20::{
     TokenInfoResponse,
 };
 use osmo_lp_lockdrop::msg::{
-    ClaimsResponse, ExecuteMsg, GetHooksResponse, InstantiateMsg, ListStakersResponse, QueryMsg,
-    StakedBalanceAtHeightResponse, StakedValueResponse, TotalStakedAtHeightResponse,
-    TotalValueResponse,
+    AcceptsStakesResponse, AirdropClaimedResponse, AirdropResponse, AllowlistedResponse,
+    ClaimsResponse, CompoundingResponse, DelegationResponse, DepositResponse, ExecuteMsg,
+    FailedHooksResponse, GetHooksResponse, InfoResponse, InstantiateMsg, ListStakersResponse,
+    NftInfoResponse, OwnerOfResponse, PendingRewardsResponse, PositionInfo, QueryMsg,
+    ScoreAllocationResponse, ScoreResponse, SeedTargetResponse, SeededPositionsResponse,
+    StakedBalanceAtHeightResponse, StakedValueResponse, TokensResponse, TotalPowerAtHeightResponse,
+    TotalStakedAtHeightResponse, TotalValueResponse, VotingPowerAtHeightResponse,
 };
-use osmo_lp_lockdrop::state::Config;
+use osmo_lp_lockdrop::state::{Config, DepositLimits};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -28,6 +32,7 @@ fn main() {
     export_schema(&schema_for!(StakedValueResponse), &out_dir);
     export_schema(&schema_for!(TotalValueResponse), &out_dir);
     export_schema(&schema_for!(GetHooksResponse), &out_dir);
+    export_schema(&schema_for!(FailedHooksResponse), &out_dir);
     export_schema(&schema_for!(ClaimsResponse), &out_dir);
     export_schema(&schema_for!(AllowanceResponse), &out_dir);
     export_schema(&schema_for!(BalanceResponse), &out_dir);
@@ -35,6 +40,31 @@ fn main() {
     export_schema(&schema_for!(AllAllowancesResponse), &out_dir);
     export_schema(&schema_for!(AllAccountsResponse), &out_dir);
     export_schema(&schema_for!(ListStakersResponse), &out_dir);
+    export_schema(&schema_for!(AcceptsStakesResponse), &out_dir);
+    export_schema(&schema_for!(VotingPowerAtHeightResponse), &out_dir);
+    export_schema(&schema_for!(DelegationResponse), &out_dir);
+    export_schema(&schema_for!(TotalPowerAtHeightResponse), &out_dir);
+    export_schema(&schema_for!(InfoResponse), &out_dir);
+    export_schema(&schema_for!(PendingRewardsResponse), &out_dir);
+    export_schema(&schema_for!(CompoundingResponse), &out_dir);
+    export_schema(&schema_for!(OwnerOfResponse), &out_dir);
+    export_schema(&schema_for!(TokensResponse), &out_dir);
+    export_schema_with_title(
+        &schema_for!(NftInfoResponse<PositionInfo>),
+        &out_dir,
+        "NftInfoResponse",
+    );
+
+    export_schema(&schema_for!(DepositLimits), &out_dir);
+    export_schema(&schema_for!(AllowlistedResponse), &out_dir);
+    export_schema(&schema_for!(DepositResponse), &out_dir);
+    export_schema(&schema_for!(AirdropResponse), &out_dir);
+    export_schema(&schema_for!(AirdropClaimedResponse), &out_dir);
+    export_schema(&schema_for!(ScoreResponse), &out_dir);
+    export_schema(&schema_for!(ScoreAllocationResponse), &out_dir);
+    export_schema(&schema_for!(SeedTargetResponse), &out_dir);
+    export_schema(&schema_for!(SeededPositionsResponse), &out_dir);
+    export_schema(&schema_for!(SeedLocksResponse), &out_dir);
 
     // Need to rename so it matches the TS pattern
     export_schema_with_title(&schema_for!(Config), &out_dir, "GetConfigResponse");
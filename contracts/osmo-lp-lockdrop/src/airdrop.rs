@@ -0,0 +1,166 @@
+use cosmwasm_std::{
+    coins, BankMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage,
+    Uint128,
+};
+use cw_utils::{must_pay, Expiration};
+
+use crate::contract::ensure_owner_or_manager;
+use crate::merkle;
+use crate::msg::{AirdropClaimedResponse, AirdropResponse};
+use crate::state::{Airdrop, AIRDROP, AIRDROP_CLAIMED, AIRDROP_COUNT, CONFIG};
+use crate::ContractError;
+
+fn is_claimed(storage: &dyn Storage, id: u64, index: u64) -> StdResult<bool> {
+    let word = AIRDROP_CLAIMED
+        .may_load(storage, (id, index / 64))?
+        .unwrap_or_default();
+    Ok(word & (1 << (index % 64)) != 0)
+}
+
+fn set_claimed(storage: &mut dyn Storage, id: u64, index: u64) -> StdResult<()> {
+    AIRDROP_CLAIMED.update(storage, (id, index / 64), |word| -> StdResult<_> {
+        Ok(word.unwrap_or_default() | (1 << (index % 64)))
+    })?;
+    Ok(())
+}
+
+/// Tokens of `denom` held back for the open airdrop's unclaimed leaves.
+pub fn reserved(storage: &dyn Storage, denom: &str) -> StdResult<Uint128> {
+    match AIRDROP.may_load(storage)? {
+        Some(airdrop) if airdrop.denom == denom => Ok(airdrop.total - airdrop.claimed),
+        _ => Ok(Uint128::zero()),
+    }
+}
+
+/// Opens an airdrop of the seed tokens sent along, claimable by the leaves of
+/// the tree with `merkle_root` until `expiry`.
+pub fn execute_register_airdrop(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    merkle_root: String,
+    denom: String,
+    expiry: Expiration,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    if AIRDROP.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::AirdropOpen {});
+    }
+    if expiry.is_expired(&env.block) {
+        return Err(ContractError::AirdropExpired {});
+    }
+    merkle::validate_root(&merkle_root)?;
+    let total = must_pay(&info, &denom)?;
+
+    let id = AIRDROP_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    AIRDROP_COUNT.save(deps.storage, &(id + 1))?;
+    AIRDROP.save(
+        deps.storage,
+        &Airdrop {
+            id,
+            merkle_root,
+            denom,
+            expiry,
+            total,
+            claimed: Uint128::zero(),
+        },
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "register_airdrop")
+        .add_attribute("id", id.to_string())
+        .add_attribute("total", total))
+}
+
+/// Pays out the sender's `amount` of the airdrop with a proof that
+/// `index:sender:amount` is a leaf of its tree.
+pub fn execute_claim_airdrop(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    index: u64,
+    amount: Uint128,
+    proof: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut airdrop = AIRDROP
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoAirdrop {})?;
+    if airdrop.expiry.is_expired(&env.block) {
+        return Err(ContractError::AirdropExpired {});
+    }
+    if is_claimed(deps.storage, airdrop.id, index)? {
+        return Err(ContractError::AirdropClaimed {});
+    }
+    let leaf = format!("{}:{}:{}", index, info.sender, amount);
+    merkle::verify_proof(&airdrop.merkle_root, leaf.as_bytes(), &proof)?;
+
+    set_claimed(deps.storage, airdrop.id, index)?;
+    airdrop.claimed = airdrop
+        .claimed
+        .checked_add(amount)
+        .map_err(StdError::overflow)?;
+    if airdrop.claimed > airdrop.total {
+        return Err(ContractError::Std(StdError::generic_err(
+            "airdrop claims exceed its total",
+        )));
+    }
+    AIRDROP.save(deps.storage, &airdrop)?;
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(amount.u128(), airdrop.denom),
+        })
+        .add_attribute("action", "claim_airdrop")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", amount))
+}
+
+/// Closes the expired airdrop, returning its unclaimed tokens to the owner,
+/// or to the manager if there is no owner.
+pub fn execute_reclaim_airdrop(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+    let to_address = config
+        .owner
+        .or(config.manager)
+        .ok_or(ContractError::Unauthorized {})?;
+    let airdrop = AIRDROP
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoAirdrop {})?;
+    if !airdrop.expiry.is_expired(&env.block) {
+        return Err(ContractError::AirdropNotExpired {});
+    }
+    AIRDROP.remove(deps.storage);
+    let unclaimed = airdrop
+        .total
+        .checked_sub(airdrop.claimed)
+        .map_err(StdError::overflow)?;
+    let mut res = Response::new()
+        .add_attribute("action", "reclaim_airdrop")
+        .add_attribute("id", airdrop.id.to_string())
+        .add_attribute("amount", unclaimed);
+    if !unclaimed.is_zero() {
+        res = res.add_message(BankMsg::Send {
+            to_address: to_address.to_string(),
+            amount: coins(unclaimed.u128(), airdrop.denom),
+        });
+    }
+    Ok(res)
+}
+
+pub fn query_airdrop(deps: Deps) -> StdResult<AirdropResponse> {
+    Ok(AirdropResponse {
+        airdrop: AIRDROP.may_load(deps.storage)?,
+    })
+}
+
+pub fn query_airdrop_claimed(deps: Deps, index: u64) -> StdResult<AirdropClaimedResponse> {
+    let claimed = match AIRDROP.may_load(deps.storage)? {
+        Some(airdrop) => is_claimed(deps.storage, airdrop.id, index)?,
+        None => false,
+    };
+    Ok(AirdropClaimedResponse { claimed })
+}
@@ -0,0 +1,122 @@
+use cosmwasm_std::{
+    coins, Addr, BankMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage,
+    Uint128,
+};
+use staking_core::score::{score_of, total_score};
+use staking_core::StakingError;
+
+use crate::airdrop;
+use crate::msg::{ScoreAllocationResponse, ScoreResponse};
+use crate::state::{SCORE_ALLOCATIONS, SCORE_ALLOCATION_CLAIMS, SCORE_END};
+use crate::ContractError;
+
+fn allocated(
+    storage: &dyn Storage,
+    env: &Env,
+    staker: &Addr,
+    total: Uint128,
+) -> StdResult<Uint128> {
+    let total_score = total_score(storage, env)?;
+    if total_score.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    Ok(total.multiply_ratio(score_of(storage, env, staker)?, total_score))
+}
+
+/// Allocates the contract's balance of `denom` not allocated yet to stakers
+/// in proportion to their score. Scores are final once liquidity is seeded,
+/// so allocating before then is an error.
+pub fn execute_allocate(
+    deps: DepsMut,
+    env: &Env,
+    denom: String,
+) -> Result<Response, ContractError> {
+    if SCORE_END.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::ScoringOpen {});
+    }
+    let mut allocation = SCORE_ALLOCATIONS
+        .may_load(deps.storage, &denom)?
+        .unwrap_or_default();
+    let mut reserved = allocation
+        .total
+        .checked_sub(allocation.claimed)
+        .map_err(StdError::overflow)?;
+    // tokens of the open airdrop are not for allocation
+    reserved += airdrop::reserved(deps.storage, &denom)?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    let amount = balance.saturating_sub(reserved);
+    allocation.total = allocation
+        .total
+        .checked_add(amount)
+        .map_err(StdError::overflow)?;
+    SCORE_ALLOCATIONS.save(deps.storage, &denom, &allocation)?;
+    Ok(Response::new()
+        .add_attribute("action", "allocate_by_score")
+        .add_attribute("denom", denom)
+        .add_attribute("amount", amount))
+}
+
+/// Pays out the sender's share of the tokens allocated by score in `denom`
+/// which it has not claimed yet.
+pub fn execute_claim_allocation(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    let mut allocation = SCORE_ALLOCATIONS
+        .may_load(deps.storage, &denom)?
+        .ok_or(ContractError::InvalidDenom {})?;
+    let claimed = SCORE_ALLOCATION_CLAIMS
+        .may_load(deps.storage, (&info.sender, &denom))?
+        .unwrap_or_default();
+    let amount = allocated(deps.storage, env, &info.sender, allocation.total)?
+        .checked_sub(claimed)
+        .map_err(StdError::overflow)?;
+    if amount.is_zero() {
+        return Err(ContractError::Staking(StakingError::NothingToClaim {}));
+    }
+    SCORE_ALLOCATION_CLAIMS.save(deps.storage, (&info.sender, &denom), &(claimed + amount))?;
+    allocation.claimed += amount;
+    SCORE_ALLOCATIONS.save(deps.storage, &denom, &allocation)?;
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(amount.u128(), &denom),
+        })
+        .add_attribute("action", "claim_allocation")
+        .add_attribute("from", info.sender)
+        .add_attribute("denom", denom)
+        .add_attribute("amount", amount))
+}
+
+pub fn query_score(deps: Deps, env: &Env, address: String) -> StdResult<ScoreResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(ScoreResponse {
+        score: score_of(deps.storage, env, &address)?,
+        total_score: total_score(deps.storage, env)?,
+        end: SCORE_END.may_load(deps.storage)?,
+    })
+}
+
+pub fn query_score_allocation(
+    deps: Deps,
+    env: &Env,
+    address: String,
+    denom: String,
+) -> StdResult<ScoreAllocationResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let total = SCORE_ALLOCATIONS
+        .may_load(deps.storage, &denom)?
+        .unwrap_or_default()
+        .total;
+    Ok(ScoreAllocationResponse {
+        allocated: allocated(deps.storage, env, &address, total)?,
+        claimed: SCORE_ALLOCATION_CLAIMS
+            .may_load(deps.storage, (&address, &denom))?
+            .unwrap_or_default(),
+    })
+}
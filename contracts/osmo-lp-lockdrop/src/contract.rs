@@ -1,3 +1,4 @@
+use std::convert::TryInto;
 use std::ops::Mul;
 use std::str::FromStr;
 
@@ -6,19 +7,24 @@ use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
     coins, to_binary, Addr, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo,
-    Order, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    Order, Reply, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
-use cw20::Denom;
+use cw20::{Denom, TokenInfoResponse};
 
-use crate::hooks::{stake_hook_msgs, unstake_hook_msgs};
+use crate::airdrop;
+use crate::allocation;
+use crate::deposit;
+use crate::lock;
 use crate::msg::{
-    AllRewardContractsResponse, ExecuteMsg, GetHooksResponse, InstantiateMsg, ListStakersResponse,
-    QueryMsg, StakedBalanceAtHeightResponse, StakedValueResponse, StakerBalanceResponse,
-    TotalStakedAtHeightResponse, TotalValueResponse,
+    AllRewardContractsResponse, CompoundRouteResponse, CompoundingResponse, DenomPendingRewards,
+    ExecuteMsg, InstantiateMsg, PendingRewardsResponse, QueryMsg,
 };
+use crate::position;
+use crate::seed;
+use crate::state::DEPOSIT_LIMITS;
 use crate::state::{
-    Config, BALANCE, CLAIMS, CONFIG, HOOKS, MAX_CLAIMS, REWARD_CONTRACTS_BY_DENOM, STAKED_BALANCES,
-    STAKED_TOTAL,
+    Config, PendingCompound, SeedTarget, SwapHop, COMPOUNDING, COMPOUND_ROUTES, CONFIG, DAO,
+    MAX_CLAIMS, PENDING_COMPOUND, REWARD_CONTRACTS_BY_DENOM, STAKED_TOTAL,
 };
 use crate::ContractError;
 use cw2::set_contract_version;
@@ -27,48 +33,68 @@ pub use cw20_base::allowances::{
     execute_transfer_from, query_allowance,
 };
 pub use cw20_base::contract::{
-    execute_burn, execute_mint, execute_send, execute_transfer, execute_update_marketing,
-    execute_upload_logo, query_balance, query_download_logo, query_marketing_info, query_minter,
-    query_token_info,
+    execute_burn, execute_mint, execute_update_marketing, execute_upload_logo, query_download_logo,
+    query_marketing_info, query_minter,
 };
 pub use cw20_base::enumerable::{query_all_accounts, query_all_allowances};
-use cw_controllers::ClaimsResponse;
+use cw_storage_plus::Bound;
 use cw_utils::{must_pay, parse_reply_instantiate_data, Duration};
 use osmosis_std::types::{
     cosmos::base::v1beta1::Coin,
     osmosis::gamm::{
         poolmodels::balancer::v1beta1::{MsgCreateBalancerPool, MsgCreateBalancerPoolResponse},
         v1beta1::{
-            GammQuerier, MsgExitPool, MsgExitPoolResponse, Pool, PoolAsset, PoolParams,
-            QueryPoolResponse, QueryTotalPoolLiquidityResponse,
+            GammQuerier, MsgExitPool, MsgExitPoolResponse, MsgJoinSwapExternAmountIn,
+            MsgJoinSwapExternAmountInResponse, Pool, PoolAsset, PoolParams, QueryPoolResponse,
+            QueryTotalPoolLiquidityResponse,
         },
     },
+    osmosis::poolmanager::v1beta1::{
+        MsgSwapExactAmountIn, MsgSwapExactAmountInResponse, SwapAmountInRoute,
+    },
 };
+use staking_core::hooks::HOOK_REPLY_ID_OFFSET;
+use staking_core::staking::{self, DEFAULT_LIMIT, MAX_LIMIT};
+use staking_core::{delegation, score, NativeAsset, StakingAsset, StakingError};
 
 pub(crate) const CONTRACT_NAME: &str = "crates.io:native-stake";
 pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-fn validate_duration(duration: Option<Duration>) -> Result<(), ContractError> {
-    if let Some(unstaking_duration) = duration {
-        match unstaking_duration {
-            Duration::Height(height) => {
-                if height == 0 {
-                    return Err(ContractError::InvalidUnstakingDuration {});
-                }
-            }
-            Duration::Time(time) => {
-                if time == 0 {
-                    return Err(ContractError::InvalidUnstakingDuration {});
-                }
-            }
-        }
-    }
-    Ok(())
-}
+pub(crate) const RECEIPT_SYMBOL: &str = "LOCKED";
+const RECEIPT_DECIMALS: u8 = 18;
+
+const INSTANTIATE_REWARDS_REPLY_ID: u64 = 1;
+const COMPOUND_REPLY_ID: u64 = 2;
+const COMPOUND_SWAP_REPLY_ID: u64 = 8;
 
 // handle reply
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_REWARDS_REPLY_ID => reply_instantiate_rewards(deps, env, msg),
+        COMPOUND_REPLY_ID => reply_compound(deps, env, msg),
+        COMPOUND_SWAP_REPLY_ID => reply_compound_swap(deps, env, msg),
+        seed::CREATE_CONCENTRATED_POOL_REPLY_ID => {
+            seed::reply_create_concentrated_pool(deps, env, msg)
+        }
+        seed::CREATE_POSITION_REPLY_ID => seed::reply_create_position(deps, msg),
+        lock::CREATE_BALANCER_POOL_REPLY_ID | lock::CREATE_STABLESWAP_POOL_REPLY_ID => {
+            lock::reply_create_pool(deps, env, msg)
+        }
+        lock::LOCK_TOKENS_REPLY_ID => lock::reply_lock_tokens(deps, msg),
+        id if id >= HOOK_REPLY_ID_OFFSET => Ok(staking::reply_hook(deps, &env, msg)?),
+        id => Err(ContractError::Std(StdError::generic_err(format!(
+            "unknown reply id: {}",
+            id
+        )))),
+    }
+}
+
+fn reply_instantiate_rewards(
+    deps: DepsMut,
+    env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
     let res = parse_reply_instantiate_data(msg)
         .map_err(|e| ContractError::Std(StdError::generic_err("failed to instantiate")))?;
     let contract_addr = deps.api.addr_validate(&res.contract_address)?;
@@ -93,6 +119,64 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
     Ok(Response::new().add_message(fund_rewards_contract_msg))
 }
 
+/// Sums the shares out of each compound join and, once the last one replies,
+/// checks them against the minimum and restakes them.
+fn reply_compound(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let res: MsgJoinSwapExternAmountInResponse = msg.result.try_into()?;
+    let mut pending = PENDING_COMPOUND.load(deps.storage)?;
+    pending.shares_out += Uint128::from_str(&res.share_out_amount)?;
+    pending.joins_left -= 1;
+    if pending.joins_left > 0 {
+        PENDING_COMPOUND.save(deps.storage, &pending)?;
+        return Ok(Response::new());
+    }
+
+    PENDING_COMPOUND.remove(deps.storage);
+    if pending.shares_out < pending.min_shares_out {
+        return Err(ContractError::MinSharesOut {
+            min: pending.min_shares_out,
+        });
+    }
+    // restaked rewards are not deposits, so the deposit limits don't apply
+    let hook_msgs = staking::stake(deps.storage, &env, &pending.staker, pending.shares_out)?;
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_attribute("action", "compound")
+        .add_attribute("from", pending.staker)
+        .add_attribute("amount", pending.shares_out))
+}
+
+/// Joins the output of a reward swap into the staked pool.
+fn reply_compound_swap(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let res: MsgSwapExactAmountInResponse = msg.result.try_into()?;
+    let mut pending = PENDING_COMPOUND.load(deps.storage)?;
+    if pending.swap_denoms.is_empty() {
+        return Err(ContractError::Std(StdError::not_found("compound swap")));
+    }
+    let denom = pending.swap_denoms.remove(0);
+    PENDING_COMPOUND.save(deps.storage, &pending)?;
+    let token_in = Coin {
+        denom,
+        amount: res.token_out_amount,
+    };
+    Ok(Response::new()
+        .add_submessage(compound_join_msg(&env, &pending, token_in))
+        .add_attribute("action", "compound_swap")
+        .add_attribute("from", pending.staker))
+}
+
+fn compound_join_msg(env: &Env, pending: &PendingCompound, token_in: Coin) -> SubMsg {
+    // the shares out are checked against `min_shares_out` once all joins reply
+    let join_msg: CosmosMsg = MsgJoinSwapExternAmountIn {
+        sender: env.contract.address.to_string(),
+        pool_id: pending.pool_id,
+        token_in: Some(token_in),
+        share_out_min_amount: "1".to_string(),
+    }
+    .into();
+    SubMsg::reply_on_success(join_msg, COMPOUND_REPLY_ID)
+}
+
 pub fn execute_fund_rewards_contract(
     deps: DepsMut,
     env: Env,
@@ -108,10 +192,18 @@ pub fn execute_fund_rewards_contract(
         Denom::Native(denom) => denom,
         Denom::Cw20(_) => return Err(ContractError::InvalidDenom {}),
     };
-    let balance = deps.querier.query_balance(&env.contract.address, denom)?;
+    // tokens of the open airdrop stay in escrow for its claims
+    let amount = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount
+        .saturating_sub(airdrop::reserved(deps.storage, &denom)?);
+    if amount.is_zero() {
+        return Ok(Response::new());
+    }
     let fund_lockdrop_rewards_msg: CosmosMsg<Empty> = CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: reward_contract_addr.to_string(),
-        funds: vec![balance],
+        funds: coins(amount.u128(), denom),
         msg: to_binary(&lockdrop_rewards::msg::ExecuteMsg::Fund {})?,
     });
 
@@ -122,7 +214,7 @@ pub fn execute_fund_rewards_contract(
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response<Empty>, ContractError> {
     let owner = match msg.owner {
@@ -135,22 +227,29 @@ pub fn instantiate(
         None => None,
     };
 
-    validate_duration(msg.unstaking_duration)?;
+    staking::validate_duration(msg.unstaking_duration)?;
+    if let Some(vesting) = &msg.reward_vesting {
+        if vesting.cliff > vesting.duration {
+            return Err(ContractError::InvalidVestingConfig {});
+        }
+    }
+    if let Some(limits) = msg.deposit_limits {
+        deposit::validate_limits(&limits)?;
+        DEPOSIT_LIMITS.save(deps.storage, &limits)?;
+    }
     let config = Config {
         owner,
         manager,
         denom: msg.denom,
         unstaking_duration: msg.unstaking_duration,
+        max_claims: None,
         reward_contract_code_id: msg.reward_contract_code_id,
+        allocate_by_score: msg.allocate_by_score.unwrap_or_default(),
+        reward_vesting: msg.reward_vesting,
     };
     CONFIG.save(deps.storage, &config)?;
-
-    // Initialize state to zero. We do this instead of using
-    // `unwrap_or_default` where this is used as it protects us
-    // against a scenerio where state is cleared by a bad actor and
-    // `unwrap_or_default` carries on.
-    STAKED_TOTAL.save(deps.storage, &Uint128::zero(), env.block.height)?;
-    BALANCE.save(deps.storage, &Uint128::zero())?;
+    DAO.save(deps.storage, &info.sender)?;
+    staking::instantiate(deps.storage, &env)?;
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
@@ -180,25 +279,123 @@ pub fn execute(
             gamm_denom,
         } => _seed_liquidity(deps, env, info, seed_denom, gamm_denom),
         ExecuteMsg::Fund {} => execute_fund(deps, env, info),
-        ExecuteMsg::Stake {} => execute_stake(deps, env, info),
+        ExecuteMsg::Stake { recipient } => execute_stake(deps, env, info, recipient),
         ExecuteMsg::Unstake { amount } => execute_unstake(deps, env, info, amount),
-        ExecuteMsg::Claim {} => execute_claim(deps, env, info),
+        ExecuteMsg::Claim { limit } => execute_claim(deps, env, info, limit),
+        ExecuteMsg::CancelUnbonding { amount } => execute_cancel_unbonding(deps, env, info, amount),
+        ExecuteMsg::ClaimRewards {
+            denoms,
+            min_shares_out,
+        } => execute_claim_rewards(deps, env, info, denoms, min_shares_out),
+        ExecuteMsg::SetCompounding { enabled } => execute_set_compounding(deps, info, enabled),
+        ExecuteMsg::UpdateCompoundRoute { denom, route } => {
+            execute_update_compound_route(deps, info, denom, route)
+        }
         ExecuteMsg::UpdateConfig {
             owner,
             manager,
             duration,
-        } => execute_update_config(info, deps, owner, manager, duration),
+            max_claims,
+        } => execute_update_config(info, deps, owner, manager, duration, max_claims),
         ExecuteMsg::AddHook { addr } => execute_add_hook(deps, env, info, addr),
         ExecuteMsg::RemoveHook { addr } => execute_remove_hook(deps, env, info, addr),
+        ExecuteMsg::UpdateHookGasLimit { gas_limit } => {
+            execute_update_hook_gas_limit(deps, info, gas_limit)
+        }
+        ExecuteMsg::ReplayFailedHook { id } => execute_replay_failed_hook(deps, info, id),
+        ExecuteMsg::ClearFailedHook { id } => execute_clear_failed_hook(deps, info, id),
+        ExecuteMsg::Delegate { to } => execute_delegate(deps, env, info, to),
+        ExecuteMsg::Undelegate {} => execute_undelegate(deps, env, info),
+        ExecuteMsg::SetAcceptStakes { enabled } => execute_set_accept_stakes(deps, info, enabled),
+        ExecuteMsg::Transfer { recipient, amount } => {
+            execute_transfer(deps, env, info, recipient, amount)
+        }
+        ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => execute_send(deps, env, info, contract, amount, msg),
+        ExecuteMsg::StakePosition {} => position::execute_stake_position(deps, &env, info),
+        ExecuteMsg::UnstakePosition { token_id } => {
+            position::execute_unstake_position(deps, &env, info, token_id)
+        }
+        ExecuteMsg::ClaimPosition { token_id } => {
+            position::execute_claim_position(deps, &env, info, token_id)
+        }
+        ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        } => position::execute_transfer_nft(deps, &env, info, recipient, token_id),
+        ExecuteMsg::SendNft {
+            contract,
+            token_id,
+            msg,
+        } => position::execute_send_nft(deps, &env, info, contract, token_id, msg),
+        ExecuteMsg::Approve {
+            spender,
+            token_id,
+            expires,
+        } => position::execute_approve(deps, &env, info, spender, token_id, expires),
+        ExecuteMsg::Revoke { spender, token_id } => {
+            position::execute_revoke(deps, &env, info, spender, token_id)
+        }
+        ExecuteMsg::ApproveAll { operator, expires } => {
+            position::execute_approve_all(deps, &env, info, operator, expires)
+        }
+        ExecuteMsg::RevokeAll { operator } => position::execute_revoke_all(deps, info, operator),
+        ExecuteMsg::UpdateDepositLimits { limits } => {
+            deposit::execute_update_deposit_limits(deps, info, limits)
+        }
+        ExecuteMsg::UpdateAllowlist { add, remove } => {
+            deposit::execute_update_allowlist(deps, info, add, remove)
+        }
+        ExecuteMsg::JoinAllowlist { proof } => deposit::execute_join_allowlist(deps, info, proof),
+        ExecuteMsg::RegisterAirdrop {
+            merkle_root,
+            denom,
+            expiry,
+        } => airdrop::execute_register_airdrop(deps, &env, info, merkle_root, denom, expiry),
+        ExecuteMsg::ClaimAirdrop {
+            index,
+            amount,
+            proof,
+        } => airdrop::execute_claim_airdrop(deps, &env, info, index, amount, proof),
+        ExecuteMsg::ReclaimAirdrop {} => airdrop::execute_reclaim_airdrop(deps, &env, info),
+        ExecuteMsg::ClaimAllocation { denom } => {
+            allocation::execute_claim_allocation(deps, &env, info, denom)
+        }
+        ExecuteMsg::UpdateSeedTarget { denom, target } => {
+            seed::execute_update_seed_target(deps, info, denom, target)
+        }
+        ExecuteMsg::UpdateSeedLock { duration } => {
+            lock::execute_update_seed_lock(deps, info, duration)
+        }
+        ExecuteMsg::UnlockSeedShares {} => lock::execute_unlock_seed_shares(deps, env, info),
+        ExecuteMsg::WithdrawSeededPositions {} => {
+            seed::execute_withdraw_seeded_positions(deps, env, info)
+        }
     }
 }
 
+/// Errors unless `sender` is the owner or the manager.
+pub(crate) fn ensure_owner_or_manager(
+    storage: &dyn Storage,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let config: Config = CONFIG.load(storage)?;
+    if config.owner.as_ref() != Some(sender) && config.manager.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
 pub fn execute_update_config(
     info: MessageInfo,
     deps: DepsMut,
     new_owner: Option<String>,
     new_manager: Option<String>,
     duration: Option<Duration>,
+    max_claims: Option<u64>,
 ) -> Result<Response, ContractError> {
     let new_owner = new_owner
         .map(|new_owner| deps.api.addr_validate(&new_owner))
@@ -206,20 +403,20 @@ pub fn execute_update_config(
     let new_manager = new_manager
         .map(|new_manager| deps.api.addr_validate(&new_manager))
         .transpose()?;
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
     let mut config: Config = CONFIG.load(deps.storage)?;
-    if Some(info.sender.clone()) != config.owner && Some(info.sender.clone()) != config.manager {
-        return Err(ContractError::Unauthorized {});
-    };
     if Some(info.sender) != config.owner && new_owner != config.owner {
         return Err(ContractError::OnlyOwnerCanChangeOwner {});
     };
 
-    validate_duration(duration)?;
+    staking::validate_duration(duration)?;
+    staking::validate_max_claims(max_claims)?;
 
     config.owner = new_owner;
     config.manager = new_manager;
 
     config.unstaking_duration = duration;
+    config.max_claims = max_claims;
 
     CONFIG.save(deps.storage, &config)?;
     Ok(Response::new()
@@ -241,48 +438,25 @@ pub fn execute_update_config(
 }
 
 pub fn execute_stake(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    recipient: Option<String>,
 ) -> Result<Response, ContractError> {
+    let recipient = recipient
+        .map(|recipient| deps.api.addr_validate(&recipient))
+        .transpose()?;
     let config = CONFIG.load(deps.storage)?;
-    let amount = must_pay(&info, &config.denom)?;
-    let balance = BALANCE.load(deps.storage)?;
-    let staked_total = STAKED_TOTAL.load(deps.storage)?;
-    let amount_to_stake = if staked_total == Uint128::zero() || balance == Uint128::zero() {
-        amount
-    } else {
-        staked_total
-            .checked_mul(amount)
-            .map_err(StdError::overflow)?
-            .checked_div(balance)
-            .map_err(StdError::divide_by_zero)?
-    };
-    STAKED_BALANCES.update(
-        deps.storage,
-        &info.sender,
-        env.block.height,
-        |balance| -> StdResult<Uint128> {
-            Ok(balance.unwrap_or_default().checked_add(amount_to_stake)?)
-        },
-    )?;
-    STAKED_TOTAL.update(
-        deps.storage,
-        env.block.height,
-        |total| -> StdResult<Uint128> {
-            Ok(total.unwrap_or_default().checked_add(amount_to_stake)?)
-        },
+    let amount = NativeAsset(config.denom).amount_in(&info, None)?;
+    let res = staking::execute_stake(
+        deps.branch(),
+        &env,
+        info.sender.clone(),
+        recipient.clone(),
+        amount,
     )?;
-    BALANCE.save(
-        deps.storage,
-        &balance.checked_add(amount).map_err(StdError::overflow)?,
-    )?;
-    let hook_msgs = stake_hook_msgs(deps.storage, info.sender.clone(), amount_to_stake)?;
-    Ok(Response::new()
-        .add_submessages(hook_msgs)
-        .add_attribute("action", "stake")
-        .add_attribute("from", info.sender)
-        .add_attribute("amount", amount))
+    deposit::record_deposit(deps.storage, &recipient.unwrap_or(info.sender), amount)?;
+    Ok(res)
 }
 
 pub fn execute_unstake(
@@ -291,97 +465,252 @@ pub fn execute_unstake(
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
+    position::ensure_unlocked(deps.storage, &info.sender, amount)?;
+    deposit::release_deposit(deps.storage, &info.sender, amount)?;
     let config = CONFIG.load(deps.storage)?;
-    let balance = BALANCE.load(deps.storage)?;
-    let staked_total = STAKED_TOTAL.load(deps.storage)?;
-    let amount_to_claim = amount
-        .checked_mul(balance)
-        .map_err(StdError::overflow)?
-        .checked_div(staked_total)
-        .map_err(|_e| ContractError::InvalidUnstakeAmount {})?;
-    STAKED_BALANCES.update(
-        deps.storage,
-        &info.sender,
-        env.block.height,
-        |balance| -> Result<Uint128, ContractError> {
-            balance
-                .unwrap_or_default()
-                .checked_sub(amount)
-                .map_err(|_e| ContractError::InvalidUnstakeAmount {})
-        },
-    )?;
-    STAKED_TOTAL.update(
-        deps.storage,
-        env.block.height,
-        |total| -> Result<Uint128, ContractError> {
-            total
-                .unwrap_or_default()
-                .checked_sub(amount)
-                .map_err(|_e| ContractError::InvalidUnstakeAmount {})
-        },
-    )?;
-    BALANCE.update(deps.storage, |bal| -> Result<Uint128, ContractError> {
-        bal.checked_sub(amount_to_claim)
-            .map_err(|_e| ContractError::InvalidUnstakeAmount {})
-    })?;
-    let hook_msgs = unstake_hook_msgs(deps.storage, info.sender.clone(), amount)?;
-    match config.unstaking_duration {
-        None => {
-            let msg = CosmosMsg::Bank(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: coins(amount_to_claim.u128(), config.denom),
-            });
-            Ok(Response::new()
-                .add_message(msg)
-                .add_submessages(hook_msgs)
-                .add_attribute("action", "unstake")
-                .add_attribute("from", info.sender)
-                .add_attribute("amount", amount)
-                .add_attribute("claim_duration", "None"))
-        }
-        Some(duration) => {
-            let outstanding_claims = CLAIMS.query_claims(deps.as_ref(), &info.sender)?.claims;
-            if outstanding_claims.len() >= MAX_CLAIMS as usize {
-                return Err(ContractError::TooManyClaims {});
-            }
-
-            CLAIMS.create_claim(
-                deps.storage,
-                &info.sender,
-                amount_to_claim,
-                duration.after(&env.block),
-            )?;
-            Ok(Response::new()
-                .add_attribute("action", "unstake")
-                .add_submessages(hook_msgs)
-                .add_attribute("from", info.sender)
-                .add_attribute("amount", amount)
-                .add_attribute("claim_duration", format!("{}", duration)))
-        }
-    }
+    Ok(staking::execute_unstake(
+        deps,
+        &env,
+        &NativeAsset(config.denom),
+        info.sender,
+        amount,
+        config.unstaking_duration,
+        config.max_claims.unwrap_or(MAX_CLAIMS),
+    )?)
 }
 
 pub fn execute_claim(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(staking::execute_claim(
+        deps,
+        &env,
+        &NativeAsset(config.denom),
+        info.sender,
+        limit,
+    )?)
+}
+
+pub fn execute_cancel_unbonding(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let res = staking::execute_cancel_unbonding(deps.branch(), &env, info.sender.clone(), amount)?;
+    deposit::restore_deposit(deps.storage, &info.sender, amount)?;
+    Ok(res)
+}
+
+/// Claims the sender's pending rewards from every registered reward contract,
+/// or only from those of `denoms` if given. Rewards are sent straight to the
+/// sender by each reward contract, unless the sender opted into compounding.
+/// Compounded rewards are claimed by this contract, swapped into an asset of
+/// the staked pool along their compound route if they are not one already,
+/// joined into the pool and restaked for the sender once the join replies.
+/// Rewards which are neither a pool asset nor have a route are paid out.
+pub fn execute_claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denoms: Option<Vec<String>>,
+    min_shares_out: Option<Uint128>,
 ) -> Result<Response, ContractError> {
-    let release = CLAIMS.claim_tokens(deps.storage, &info.sender, &env.block, None)?;
-    if release.is_zero() {
-        return Err(ContractError::NothingToClaim {});
+    let reward_contracts: Vec<(String, Addr)> = match denoms {
+        Some(denoms) => denoms
+            .into_iter()
+            .map(|denom| {
+                let reward_contract = REWARD_CONTRACTS_BY_DENOM
+                    .load(deps.storage, &denom)
+                    .map_err(|_e| ContractError::InvalidDenom {})?;
+                Ok((denom, reward_contract))
+            })
+            .collect::<Result<Vec<_>, ContractError>>()?,
+        None => REWARD_CONTRACTS_BY_DENOM
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+
+    let compounding = COMPOUNDING
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let (mut pending_compound, pool_denoms) = if compounding {
+        let config = CONFIG.load(deps.storage)?;
+        let pool_id = extract_pool_id_from_denom(&config.denom)?;
+        let pending_compound = PendingCompound {
+            staker: info.sender.clone(),
+            pool_id,
+            min_shares_out: min_shares_out.ok_or(ContractError::MinSharesOutRequired {})?,
+            swap_denoms: vec![],
+            joins_left: 0,
+            shares_out: Uint128::zero(),
+        };
+        (
+            Some(pending_compound),
+            query_pool_denoms(deps.as_ref(), pool_id)?,
+        )
+    } else {
+        (None, vec![])
+    };
+
+    let mut msgs: Vec<SubMsg> = vec![];
+    let mut claimed_denoms: Vec<String> = vec![];
+    let mut compounded: Vec<String> = vec![];
+    for (denom, reward_contract) in reward_contracts {
+        // skip contracts with nothing pending, as their claim would fail
+        let pending: lockdrop_rewards::msg::PendingRewardsResponse =
+            deps.querier.query_wasm_smart(
+                &reward_contract,
+                &lockdrop_rewards::msg::QueryMsg::GetPendingRewards {
+                    address: info.sender.to_string(),
+                },
+            )?;
+        if pending.pending_rewards.is_zero() {
+            continue;
+        }
+        let route = match pending_compound {
+            Some(_) if !pool_denoms.contains(&denom) => {
+                COMPOUND_ROUTES.may_load(deps.storage, &denom)?
+            }
+            _ => None,
+        };
+        let compound = match pending_compound.as_mut() {
+            Some(pending_compound) if route.is_some() || pool_denoms.contains(&denom) => {
+                Some(pending_compound)
+            }
+            _ => None,
+        };
+        let recipient = if compound.is_some() {
+            Some(env.contract.address.to_string())
+        } else {
+            None
+        };
+        msgs.push(SubMsg::new(WasmMsg::Execute {
+            contract_addr: reward_contract.to_string(),
+            funds: vec![],
+            msg: to_binary(&lockdrop_rewards::msg::ExecuteMsg::ClaimFor {
+                address: info.sender.to_string(),
+                recipient,
+            })?,
+        }));
+        if let Some(pending_compound) = compound {
+            // the claim above runs first, so the rewards are held by this
+            // contract by the time they are swapped or joined into the pool
+            let token_in = Coin {
+                denom: denom.clone(),
+                amount: pending.pending_rewards.to_string(),
+            };
+            match route {
+                Some(route) => {
+                    let routes: Vec<SwapAmountInRoute> = route
+                        .into_iter()
+                        .map(|hop| SwapAmountInRoute {
+                            pool_id: hop.pool_id,
+                            token_out_denom: hop.token_out_denom,
+                        })
+                        .collect();
+                    if let Some(last) = routes.last() {
+                        pending_compound
+                            .swap_denoms
+                            .push(last.token_out_denom.clone());
+                    }
+                    // slippage is bounded by the minimum shares out of all
+                    // the joins together
+                    let swap_msg: CosmosMsg = MsgSwapExactAmountIn {
+                        sender: env.contract.address.to_string(),
+                        routes,
+                        token_in: Some(token_in),
+                        token_out_min_amount: "1".to_string(),
+                    }
+                    .into();
+                    msgs.push(SubMsg::reply_on_success(swap_msg, COMPOUND_SWAP_REPLY_ID));
+                }
+                None => msgs.push(compound_join_msg(&env, pending_compound, token_in)),
+            }
+            compounded.push(format!("{}{}", pending.pending_rewards, denom));
+        }
+        claimed_denoms.push(denom);
+    }
+    if msgs.is_empty() {
+        return Err(ContractError::Staking(StakingError::NothingToClaim {}));
+    }
+    match pending_compound {
+        // every compounded denom ends up joined, directly or once swapped
+        Some(mut pending_compound) if !compounded.is_empty() => {
+            pending_compound.joins_left = compounded.len() as u64;
+            PENDING_COMPOUND.save(deps.storage, &pending_compound)?
+        }
+        _ => {}
     }
 
-    let config = CONFIG.load(deps.storage)?;
-    let msg = CosmosMsg::Bank(BankMsg::Send {
-        to_address: info.sender.to_string(),
-        amount: coins(release.u128(), config.denom),
-    });
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_attribute("action", "claim_rewards")
+        .add_attribute("from", info.sender)
+        .add_attribute("denoms", claimed_denoms.join(","))
+        .add_attribute("compounded", compounded.join(",")))
+}
 
+/// Vesting rewards are locked up in the rewards contracts, so they can not be
+/// compounded when the lockdrop has a reward vesting schedule.
+pub fn execute_set_compounding(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    if enabled && CONFIG.load(deps.storage)?.reward_vesting.is_some() {
+        return Err(ContractError::CompoundingVested {});
+    }
+    COMPOUNDING.save(deps.storage, &info.sender, &enabled)?;
     Ok(Response::new()
-        .add_message(msg)
-        .add_attribute("action", "claim")
+        .add_attribute("action", "set_compounding")
         .add_attribute("from", info.sender)
-        .add_attribute("amount", release))
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+pub fn execute_update_compound_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    route: Vec<SwapHop>,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    match route.last() {
+        Some(last) => {
+            let config = CONFIG.load(deps.storage)?;
+            let pool_denoms =
+                query_pool_denoms(deps.as_ref(), extract_pool_id_from_denom(&config.denom)?)?;
+            if pool_denoms.contains(&denom) {
+                return Err(ContractError::InvalidCompoundRoute {
+                    reason: format!("{} is joined into the pool directly", denom),
+                });
+            }
+            if !pool_denoms.contains(&last.token_out_denom) {
+                return Err(ContractError::InvalidCompoundRoute {
+                    reason: "route must end in an asset of the staked pool".to_string(),
+                });
+            }
+            COMPOUND_ROUTES.save(deps.storage, &denom, &route)?;
+        }
+        None => COMPOUND_ROUTES.remove(deps.storage, &denom),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "update_compound_route")
+        .add_attribute("denom", denom)
+        .add_attribute("hops", route.len().to_string()))
+}
+
+fn query_pool_denoms(deps: Deps, pool_id: u64) -> StdResult<Vec<String>> {
+    Ok(GammQuerier::new(&deps.querier)
+        .total_pool_liquidity(pool_id)?
+        .liquidity
+        .into_iter()
+        .map(|coin| coin.denom)
+        .collect())
 }
 
 pub fn execute_fund(
@@ -390,14 +719,8 @@ pub fn execute_fund(
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let amount = must_pay(&info, &config.denom)?;
-    BALANCE.update(deps.storage, |balance| -> StdResult<_> {
-        balance.checked_add(amount).map_err(StdError::overflow)
-    })?;
-    Ok(Response::new()
-        .add_attribute("action", "fund")
-        .add_attribute("from", info.sender)
-        .add_attribute("amount", amount))
+    let amount = NativeAsset(config.denom).amount_in(&info, None)?;
+    Ok(staking::execute_fund(deps.storage, info.sender, amount)?)
 }
 
 pub fn execute_add_hook(
@@ -407,14 +730,8 @@ pub fn execute_add_hook(
     addr: String,
 ) -> Result<Response, ContractError> {
     let addr = deps.api.addr_validate(&addr)?;
-    let config: Config = CONFIG.load(deps.storage)?;
-    if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
-        return Err(ContractError::Unauthorized {});
-    };
-    HOOKS.add_hook(deps.storage, addr.clone())?;
-    Ok(Response::new()
-        .add_attribute("action", "add_hook")
-        .add_attribute("hook", addr))
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    Ok(staking::execute_add_hook(deps.storage, addr)?)
 }
 
 pub fn execute_remove_hook(
@@ -424,37 +741,262 @@ pub fn execute_remove_hook(
     addr: String,
 ) -> Result<Response, ContractError> {
     let addr = deps.api.addr_validate(&addr)?;
-    let config: Config = CONFIG.load(deps.storage)?;
-    if config.owner != Some(info.sender.clone()) && config.manager != Some(info.sender) {
-        return Err(ContractError::Unauthorized {});
-    };
-    HOOKS.remove_hook(deps.storage, addr.clone())?;
-    Ok(Response::new()
-        .add_attribute("action", "remove_hook")
-        .add_attribute("hook", addr))
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    Ok(staking::execute_remove_hook(deps.storage, addr)?)
+}
+
+pub fn execute_update_hook_gas_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    gas_limit: Option<u64>,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    Ok(staking::execute_update_hook_gas_limit(
+        deps.storage,
+        gas_limit,
+    )?)
+}
+
+pub fn execute_replay_failed_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    Ok(staking::execute_replay_failed_hook(deps.storage, id)?)
+}
+
+pub fn execute_clear_failed_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    Ok(staking::execute_clear_failed_hook(deps.storage, id)?)
+}
+
+pub fn execute_delegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: String,
+) -> Result<Response, ContractError> {
+    let to = deps.api.addr_validate(&to)?;
+    Ok(delegation::execute_delegate(deps, &env, info.sender, to)?)
+}
+
+pub fn execute_undelegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    Ok(delegation::execute_undelegate(deps, &env, info.sender)?)
+}
+
+pub fn execute_set_accept_stakes(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    Ok(staking::execute_set_accept_stakes(
+        deps.storage,
+        info.sender,
+        enabled,
+    )?)
+}
+
+/// Moves `amount` of the sender's locked position to `recipient`. The
+/// position doubles as a cw20 receipt, so it can be handed over like any
+/// cw20 balance.
+pub fn execute_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    position::ensure_unlocked(deps.storage, &info.sender, amount)?;
+    deposit::transfer_deposit(deps.storage, &info.sender, &recipient, amount)?;
+    Ok(staking::execute_transfer(
+        deps,
+        &env,
+        info.sender,
+        recipient,
+        amount,
+    )?)
+}
+
+pub fn execute_send(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract: String,
+    amount: Uint128,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let contract = deps.api.addr_validate(&contract)?;
+    position::ensure_unlocked(deps.storage, &info.sender, amount)?;
+    deposit::transfer_deposit(deps.storage, &info.sender, &contract, amount)?;
+    Ok(staking::execute_send(
+        deps,
+        &env,
+        info.sender,
+        contract,
+        amount,
+        msg,
+    )?)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::AllRewardContracts {} => to_binary(&query_all_reward_contracts(deps, env)?),
+        QueryMsg::PendingRewards {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_pending_rewards(
+            deps,
+            env,
+            address,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Compounding { address } => to_binary(&query_compounding(deps, address)?),
+        QueryMsg::CompoundRoute { denom } => to_binary(&query_compound_route(deps, denom)?),
         QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
-        QueryMsg::StakedBalanceAtHeight { address, height } => {
-            to_binary(&query_staked_balance_at_height(deps, env, address, height)?)
-        }
+        QueryMsg::StakedBalanceAtHeight { address, height } => to_binary(
+            &staking::query_staked_balance_at_height(deps, &env, address, height)?,
+        ),
         QueryMsg::TotalStakedAtHeight { height } => {
-            to_binary(&query_total_staked_at_height(deps, env, height)?)
+            to_binary(&staking::query_total_staked_at_height(deps, &env, height)?)
+        }
+        QueryMsg::StakedValue { address } => {
+            to_binary(&staking::query_staked_value(deps, address)?)
+        }
+        QueryMsg::TotalValue {} => to_binary(&staking::query_total_value(deps)?),
+        QueryMsg::Claims { address } => to_binary(&staking::query_claims(deps, address)?),
+        QueryMsg::GetHooks {} => to_binary(&staking::query_hooks(deps)?),
+        QueryMsg::FailedHooks { start_after, limit } => {
+            to_binary(&staking::query_failed_hooks(deps, start_after, limit)?)
         }
-        QueryMsg::StakedValue { address } => to_binary(&query_staked_value(deps, env, address)?),
-        QueryMsg::TotalValue {} => to_binary(&query_total_value(deps, env)?),
-        QueryMsg::Claims { address } => to_binary(&query_claims(deps, address)?),
-        QueryMsg::GetHooks {} => to_binary(&query_hooks(deps)?),
         QueryMsg::ListStakers { start_after, limit } => {
-            query_list_stakers(deps, start_after, limit)
+            to_binary(&staking::query_list_stakers(deps, start_after, limit)?)
+        }
+        QueryMsg::VotingPowerAtHeight { address, height } => to_binary(
+            &staking::query_voting_power_at_height(deps, &env, address, height)?,
+        ),
+        QueryMsg::TotalPowerAtHeight { height } => {
+            to_binary(&staking::query_total_power_at_height(deps, &env, height)?)
+        }
+        QueryMsg::Dao {} => to_binary(&staking::query_dao(deps)?),
+        QueryMsg::Info {} => to_binary(&staking::query_info(deps)?),
+        QueryMsg::Balance { address } => to_binary(&staking::query_balance(deps, address)?),
+        QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
+        QueryMsg::OwnerOf {
+            token_id,
+            include_expired,
+        } => to_binary(&position::query_owner_of(
+            deps,
+            &env,
+            token_id,
+            include_expired,
+        )?),
+        QueryMsg::Approval {
+            token_id,
+            spender,
+            include_expired,
+        } => to_binary(&position::query_approval(
+            deps,
+            &env,
+            token_id,
+            spender,
+            include_expired,
+        )?),
+        QueryMsg::Approvals {
+            token_id,
+            include_expired,
+        } => to_binary(&position::query_approvals(
+            deps,
+            &env,
+            token_id,
+            include_expired,
+        )?),
+        QueryMsg::AllOperators {
+            owner,
+            include_expired,
+            start_after,
+            limit,
+        } => to_binary(&position::query_all_operators(
+            deps,
+            &env,
+            owner,
+            include_expired,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::NumTokens {} => to_binary(&position::query_num_tokens(deps)?),
+        QueryMsg::ContractInfo {} => to_binary(&position::query_contract_info(deps)?),
+        QueryMsg::NftInfo { token_id } => {
+            to_binary(&position::query_nft_info(deps, &env, token_id)?)
+        }
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => to_binary(&position::query_all_nft_info(
+            deps,
+            &env,
+            token_id,
+            include_expired,
+        )?),
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&position::query_tokens(deps, owner, start_after, limit)?),
+        QueryMsg::AllTokens { start_after, limit } => {
+            to_binary(&position::query_all_tokens(deps, start_after, limit)?)
+        }
+        QueryMsg::Delegation { address } => {
+            to_binary(&delegation::query_delegation(deps, address)?)
+        }
+        QueryMsg::AcceptsStakes { address } => {
+            to_binary(&staking::query_accepts_stakes(deps, address)?)
+        }
+        QueryMsg::DepositLimits {} => to_binary(&deposit::query_deposit_limits(deps)?),
+        QueryMsg::Allowlisted { address } => to_binary(&deposit::query_allowlisted(deps, address)?),
+        QueryMsg::Deposit { address } => to_binary(&deposit::query_deposit(deps, address)?),
+        QueryMsg::Airdrop {} => to_binary(&airdrop::query_airdrop(deps)?),
+        QueryMsg::AirdropClaimed { index } => {
+            to_binary(&airdrop::query_airdrop_claimed(deps, index)?)
+        }
+        QueryMsg::Score { address } => to_binary(&allocation::query_score(deps, &env, address)?),
+        QueryMsg::ScoreAllocation { address, denom } => to_binary(
+            &allocation::query_score_allocation(deps, &env, address, denom)?,
+        ),
+        QueryMsg::SeedTarget { denom } => to_binary(&seed::query_seed_target(deps, denom)?),
+        QueryMsg::SeededPositions { start_after, limit } => {
+            to_binary(&seed::query_seeded_positions(deps, start_after, limit)?)
+        }
+        QueryMsg::SeedLocks { start_after, limit } => {
+            to_binary(&lock::query_seed_locks(deps, start_after, limit)?)
         }
     }
 }
 
+/// The position receipt is named after the staked denom and, like the
+/// Osmosis LP shares it is usually backed by, has 18 decimals.
+pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(TokenInfoResponse {
+        name: format!("Locked {}", config.denom),
+        symbol: RECEIPT_SYMBOL.to_string(),
+        decimals: RECEIPT_DECIMALS,
+        total_supply: STAKED_TOTAL.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
 pub fn query_all_reward_contracts(deps: Deps, _env: Env) -> StdResult<AllRewardContractsResponse> {
     let all_reward_contracts_by_denom: Vec<String> = REWARD_CONTRACTS_BY_DENOM
         .range(deps.storage, None, None, Order::Descending)
@@ -467,60 +1009,55 @@ pub fn query_all_reward_contracts(deps: Deps, _env: Env) -> StdResult<AllRewardC
     })
 }
 
-pub fn query_staked_balance_at_height(
+pub fn query_pending_rewards(
     deps: Deps,
     _env: Env,
     address: String,
-    height: Option<u64>,
-) -> StdResult<StakedBalanceAtHeightResponse> {
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PendingRewardsResponse> {
     let address = deps.api.addr_validate(&address)?;
-    let height = height.unwrap_or(_env.block.height);
-    let balance = STAKED_BALANCES
-        .may_load_at_height(deps.storage, &address, height)?
-        .unwrap_or_default();
-    Ok(StakedBalanceAtHeightResponse { balance, height })
-}
-
-pub fn query_total_staked_at_height(
-    deps: Deps,
-    _env: Env,
-    height: Option<u64>,
-) -> StdResult<TotalStakedAtHeightResponse> {
-    let height = height.unwrap_or(_env.block.height);
-    let total = STAKED_TOTAL
-        .may_load_at_height(deps.storage, height)?
-        .unwrap_or_default();
-    Ok(TotalStakedAtHeightResponse { total, height })
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+    let pending_rewards = REWARD_CONTRACTS_BY_DENOM
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (denom, reward_contract) = item?;
+            let pending: lockdrop_rewards::msg::PendingRewardsResponse =
+                deps.querier.query_wasm_smart(
+                    &reward_contract,
+                    &lockdrop_rewards::msg::QueryMsg::GetPendingRewards {
+                        address: address.to_string(),
+                    },
+                )?;
+            let info: lockdrop_rewards::msg::InfoResponse = deps
+                .querier
+                .query_wasm_smart(&reward_contract, &lockdrop_rewards::msg::QueryMsg::Info {})?;
+            Ok(DenomPendingRewards {
+                denom,
+                reward_contract: reward_contract.to_string(),
+                pending_rewards: pending.pending_rewards,
+                period_finish: info.reward.period_finish,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(PendingRewardsResponse { pending_rewards })
 }
 
-pub fn query_staked_value(
-    deps: Deps,
-    _env: Env,
-    address: String,
-) -> StdResult<StakedValueResponse> {
+pub fn query_compounding(deps: Deps, address: String) -> StdResult<CompoundingResponse> {
     let address = deps.api.addr_validate(&address)?;
-    let balance = BALANCE.load(deps.storage).unwrap_or_default();
-    let staked = STAKED_BALANCES
-        .load(deps.storage, &address)
+    let enabled = COMPOUNDING
+        .may_load(deps.storage, &address)?
         .unwrap_or_default();
-    let total = STAKED_TOTAL.load(deps.storage)?;
-    if balance == Uint128::zero() || staked == Uint128::zero() || total == Uint128::zero() {
-        Ok(StakedValueResponse {
-            value: Uint128::zero(),
-        })
-    } else {
-        let value = staked
-            .checked_mul(balance)
-            .map_err(StdError::overflow)?
-            .checked_div(total)
-            .map_err(StdError::divide_by_zero)?;
-        Ok(StakedValueResponse { value })
-    }
+    Ok(CompoundingResponse { enabled })
 }
 
-pub fn query_total_value(deps: Deps, _env: Env) -> StdResult<TotalValueResponse> {
-    let balance = BALANCE.load(deps.storage)?;
-    Ok(TotalValueResponse { total: balance })
+pub fn query_compound_route(deps: Deps, denom: String) -> StdResult<CompoundRouteResponse> {
+    let route = COMPOUND_ROUTES
+        .may_load(deps.storage, &denom)?
+        .unwrap_or_default();
+    Ok(CompoundRouteResponse { denom, route })
 }
 
 pub fn query_config(deps: Deps) -> StdResult<Config> {
@@ -528,44 +1065,6 @@ pub fn query_config(deps: Deps) -> StdResult<Config> {
     Ok(config)
 }
 
-pub fn query_claims(deps: Deps, address: String) -> StdResult<ClaimsResponse> {
-    CLAIMS.query_claims(deps, &deps.api.addr_validate(&address)?)
-}
-
-pub fn query_hooks(deps: Deps) -> StdResult<GetHooksResponse> {
-    Ok(GetHooksResponse {
-        hooks: HOOKS.query_hooks(deps)?.hooks,
-    })
-}
-
-pub fn query_list_stakers(
-    deps: Deps,
-    start_after: Option<String>,
-    limit: Option<u32>,
-) -> StdResult<Binary> {
-    let start_at = start_after
-        .map(|addr| deps.api.addr_validate(&addr))
-        .transpose()?;
-
-    let stakers = cw_paginate::paginate_snapshot_map(
-        deps,
-        &STAKED_BALANCES,
-        start_at.as_ref(),
-        limit,
-        cosmwasm_std::Order::Ascending,
-    )?;
-
-    let stakers = stakers
-        .into_iter()
-        .map(|(address, balance)| StakerBalanceResponse {
-            address: address.into_string(),
-            balance,
-        })
-        .collect();
-
-    to_binary(&ListStakersResponse { stakers })
-}
-
 pub fn extract_pool_id_from_denom(denom: &str) -> Result<u64, ContractError> {
     let split: Vec<&str> = denom.split("/").collect();
     if split.len() != 3 {
@@ -591,6 +1090,8 @@ pub fn execute_eject_and_seed_liquidity(
     if Some(info.sender) != config.manager {
         return Err(ContractError::Unauthorized {});
     }
+    // the lockdrop ends with seeding, so do the scores
+    score::close_scores(deps.storage, &env)?;
     // create wasm execute message for ejecting liquidity
     let eject_msg = ExecuteMsg::EjectLiquidity {
         gamm_denom: gamm_denom.clone(),
@@ -637,7 +1138,7 @@ pub fn _seed_liquidity(
     let seed_amount_remainder =
         seed_amount - (seed_amount_per_pool * Uint128::from(asset_count as u128));
 
-    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut msgs: Vec<SubMsg> = vec![];
     let mut pool_creation_fees_to_collect = Uint128::from(asset_count.clone() as u64);
     let pool_creation_fee = Uint128::from(100000000u128);
     let mut osmo_fees_remaining = pool_creation_fee.mul(pool_creation_fees_to_collect);
@@ -659,33 +1160,85 @@ pub fn _seed_liquidity(
             balance.amount.clone()
         };
         pool_creation_fees_to_collect = pool_creation_fees_to_collect - Uint128::from(1u128);
-        let msg_create_balancer_pool: CosmosMsg = MsgCreateBalancerPool {
-            sender: env.contract.address.to_string(),
-            future_pool_governor: "24h".to_string(),
-            pool_params: Some(PoolParams {
-                swap_fee: "3000000000000000".to_string(),
-                exit_fee: "0".to_string(),
-                smooth_weight_change_params: None,
-            }),
-            pool_assets: vec![
-                PoolAsset {
-                    token: Some(Coin {
-                        denom: coin.denom.clone(),
-                        amount: balance.to_string(),
-                    }),
-                    weight: "100".to_string(),
-                },
-                PoolAsset {
-                    token: Some(Coin {
-                        denom: seed_denom.to_string(),
-                        amount: seed_amount_per_pool.to_string(),
+        match seed::seed_target(deps.storage, &coin.denom)? {
+            SeedTarget::Balancer {} => {
+                let msg_create_balancer_pool: CosmosMsg = MsgCreateBalancerPool {
+                    sender: env.contract.address.to_string(),
+                    future_pool_governor: "24h".to_string(),
+                    pool_params: Some(PoolParams {
+                        swap_fee: "3000000000000000".to_string(),
+                        exit_fee: "0".to_string(),
+                        smooth_weight_change_params: None,
                     }),
-                    weight: "100".to_string(),
-                },
-            ],
+                    pool_assets: vec![
+                        PoolAsset {
+                            token: Some(Coin {
+                                denom: coin.denom.clone(),
+                                amount: balance.to_string(),
+                            }),
+                            weight: "100".to_string(),
+                        },
+                        PoolAsset {
+                            token: Some(Coin {
+                                denom: seed_denom.to_string(),
+                                amount: seed_amount_per_pool.to_string(),
+                            }),
+                            weight: "100".to_string(),
+                        },
+                    ],
+                }
+                .into();
+                msgs.push(lock::create_pool_submsg(
+                    deps.storage,
+                    msg_create_balancer_pool,
+                    lock::CREATE_BALANCER_POOL_REPLY_ID,
+                )?);
+            }
+            SeedTarget::Concentrated {
+                tick_spacing,
+                spread_factor,
+                range,
+                max_slippage,
+            } => {
+                msgs.push(seed::create_concentrated_pool(
+                    deps.storage,
+                    &env,
+                    tick_spacing,
+                    spread_factor,
+                    range,
+                    max_slippage,
+                    cosmwasm_std::Coin {
+                        denom: coin.denom.clone(),
+                        amount: balance,
+                    },
+                    cosmwasm_std::Coin {
+                        denom: seed_denom.clone(),
+                        amount: seed_amount_per_pool,
+                    },
+                )?);
+            }
+            SeedTarget::Stableswap {
+                swap_fee,
+                asset_scaling_factor,
+                seed_scaling_factor,
+            } => {
+                msgs.push(seed::create_stableswap_pool(
+                    deps.storage,
+                    &env,
+                    swap_fee,
+                    asset_scaling_factor,
+                    seed_scaling_factor,
+                    cosmwasm_std::Coin {
+                        denom: coin.denom.clone(),
+                        amount: balance,
+                    },
+                    cosmwasm_std::Coin {
+                        denom: seed_denom.clone(),
+                        amount: seed_amount_per_pool,
+                    },
+                )?);
+            }
         }
-        .into();
-        msgs.push(msg_create_balancer_pool);
     }
 
     let bank_transfer_remainder_msgs: Vec<CosmosMsg<Empty>> = if seed_amount_remainder.is_zero() {
@@ -699,7 +1252,7 @@ pub fn _seed_liquidity(
 
     Ok(Response::new()
         .add_attribute("action", "execute_eject_and_seed_liquidity")
-        .add_messages(msgs)
+        .add_submessages(msgs)
         .add_messages(bank_transfer_remainder_msgs))
 }
 
@@ -759,6 +1312,8 @@ pub fn execute_distribute_all_tokens(
     env: Env,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
+    // the shares of matured locks are in the balance distributed below
+    lock::remove_matured_locks(deps.storage, &env)?;
     let bank_balances = deps.querier.query_all_balances(&env.contract.address)?;
     let mut msgs: Vec<CosmosMsg> = vec![];
     let config = CONFIG.load(deps.storage)?;
@@ -793,6 +1348,9 @@ pub fn execute_distribute_token(
         // can later consider allowing this as a means by which to terminate the pools
         return Err(ContractError::Unauthorized {});
     }
+    if config.allocate_by_score {
+        return allocation::execute_allocate(deps, &env, denom);
+    }
     let msgs: Vec<SubMsg> = if !REWARD_CONTRACTS_BY_DENOM.has(deps.storage, &denom) {
         // cannot distribute a token that is already being distributed
         let instantiate_lockdrop_rewards_msg: SubMsg<Empty> = SubMsg::reply_on_success(
@@ -806,10 +1364,12 @@ pub fn execute_distribute_token(
                     staking_contract: env.contract.address.to_string(),
                     reward_token: Denom::Native(denom.clone()),
                     reward_duration: 24,
+                    vesting: config.reward_vesting,
+                    sweep_grace_period: None,
                 })?,
                 funds: vec![],
             }),
-            1,
+            INSTANTIATE_REWARDS_REPLY_ID,
         );
         vec![instantiate_lockdrop_rewards_msg]
     } else {
@@ -0,0 +1,184 @@
+use cosmwasm_std::{
+    Addr, Deps, DepsMut, Empty, MessageInfo, Response, StdError, StdResult, Storage, Uint128,
+};
+
+use crate::contract::ensure_owner_or_manager;
+use crate::merkle;
+use crate::msg::{AllowlistedResponse, DepositResponse};
+use crate::state::{
+    Allowlist, DepositLimits, ALLOWLIST, DEPOSITS, DEPOSIT_LIMITS, STAKED_BALANCES, STAKED_TOTAL,
+};
+use crate::ContractError;
+
+pub fn validate_limits(limits: &DepositLimits) -> Result<(), ContractError> {
+    if let Some(Allowlist::MerkleRoot { root }) = &limits.allowlist {
+        merkle::validate_root(root)?;
+    }
+    Ok(())
+}
+
+/// Checks a deposit of `amount` credited to `recipient` against the deposit
+/// limits and records it. Called once the deposit is staked, so that the hard
+/// cap is checked against the new `STAKED_TOTAL`.
+pub fn record_deposit(
+    storage: &mut dyn Storage,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let limits = DEPOSIT_LIMITS.may_load(storage)?.unwrap_or_default();
+    if let Some(min) = limits.min_stake {
+        if amount < min {
+            return Err(ContractError::BelowMinStake { min });
+        }
+    }
+    restore_deposit(storage, recipient, amount)
+}
+
+/// Records `amount` restaked by `staker`, such as a cancelled unbonding,
+/// against the deposit limits other than the minimum stake.
+pub fn restore_deposit(
+    storage: &mut dyn Storage,
+    staker: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let limits = DEPOSIT_LIMITS.may_load(storage)?.unwrap_or_default();
+    credit_deposit(storage, &limits, staker, amount)?;
+    if let Some(cap) = limits.hard_cap {
+        if STAKED_TOTAL.load(storage)? > cap {
+            return Err(ContractError::HardCapExceeded { cap });
+        }
+    }
+    Ok(())
+}
+
+fn credit_deposit(
+    storage: &mut dyn Storage,
+    limits: &DepositLimits,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if limits.allowlist.is_some() && !ALLOWLIST.has(storage, recipient) {
+        return Err(ContractError::NotAllowlisted {});
+    }
+    let deposit = DEPOSITS
+        .may_load(storage, recipient)?
+        .unwrap_or_default()
+        .checked_add(amount)
+        .map_err(StdError::overflow)?;
+    if let Some(max) = limits.max_deposit {
+        if deposit > max {
+            return Err(ContractError::MaxDepositExceeded { max });
+        }
+    }
+    DEPOSITS.save(storage, recipient, &deposit)?;
+    Ok(())
+}
+
+/// Takes the part of `from`'s deposit backing `shares` of its staked balance
+/// out of its deposit. Called before the shares leave `from`, with an unstake
+/// or a transfer, and returns the amount taken.
+pub fn release_deposit(
+    storage: &mut dyn Storage,
+    from: &Addr,
+    shares: Uint128,
+) -> Result<Uint128, ContractError> {
+    let deposit = DEPOSITS.may_load(storage, from)?.unwrap_or_default();
+    let staked = STAKED_BALANCES.may_load(storage, from)?.unwrap_or_default();
+    if deposit.is_zero() || staked.is_zero() {
+        return Ok(Uint128::zero());
+    }
+    let released = deposit.multiply_ratio(shares.min(staked), staked);
+    let remaining = deposit - released;
+    if remaining.is_zero() {
+        DEPOSITS.remove(storage, from);
+    } else {
+        DEPOSITS.save(storage, from, &remaining)?;
+    }
+    Ok(released)
+}
+
+/// Moves the deposit backing `shares` of `from`'s staked balance to
+/// `recipient`, which has to be within the deposit limits to receive it.
+/// Called before the shares are transferred.
+pub fn transfer_deposit(
+    storage: &mut dyn Storage,
+    from: &Addr,
+    recipient: &Addr,
+    shares: Uint128,
+) -> Result<(), ContractError> {
+    let limits = DEPOSIT_LIMITS.may_load(storage)?.unwrap_or_default();
+    let released = release_deposit(storage, from, shares)?;
+    credit_deposit(storage, &limits, recipient, released)
+}
+
+pub fn execute_update_deposit_limits(
+    deps: DepsMut,
+    info: MessageInfo,
+    limits: DepositLimits,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    validate_limits(&limits)?;
+    DEPOSIT_LIMITS.save(deps.storage, &limits)?;
+    Ok(Response::new().add_attribute("action", "update_deposit_limits"))
+}
+
+pub fn execute_update_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    for addr in add {
+        let addr = deps.api.addr_validate(&addr)?;
+        ALLOWLIST.save(deps.storage, &addr, &Empty {})?;
+    }
+    for addr in remove {
+        let addr = deps.api.addr_validate(&addr)?;
+        ALLOWLIST.remove(deps.storage, &addr);
+    }
+    Ok(Response::new().add_attribute("action", "update_allowlist"))
+}
+
+/// Adds the sender to the allowlist with a proof that its address is a leaf
+/// of the allowlist's Merkle tree.
+pub fn execute_join_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    proof: Vec<String>,
+) -> Result<Response, ContractError> {
+    let limits = DEPOSIT_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+    let root = match limits.allowlist {
+        Some(Allowlist::MerkleRoot { root }) => root,
+        _ => return Err(ContractError::InvalidProof {}),
+    };
+    merkle::verify_proof(&root, info.sender.as_bytes(), &proof)?;
+    ALLOWLIST.save(deps.storage, &info.sender, &Empty {})?;
+    Ok(Response::new()
+        .add_attribute("action", "join_allowlist")
+        .add_attribute("from", info.sender))
+}
+
+pub fn query_deposit_limits(deps: Deps) -> StdResult<DepositLimits> {
+    Ok(DEPOSIT_LIMITS.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn query_allowlisted(deps: Deps, address: String) -> StdResult<AllowlistedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limits = DEPOSIT_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(AllowlistedResponse {
+        allowlisted: limits.allowlist.is_none() || ALLOWLIST.has(deps.storage, &address),
+    })
+}
+
+pub fn query_deposit(deps: Deps, address: String) -> StdResult<DepositResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limits = DEPOSIT_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+    let deposit = DEPOSITS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(DepositResponse {
+        deposit,
+        remaining: limits.max_deposit.map(|max| max.saturating_sub(deposit)),
+    })
+}
@@ -1,5 +1,6 @@
-use cosmwasm_std::{Addr, StdError};
+use cosmwasm_std::{Addr, StdError, Uint128};
 use cw_utils::PaymentError;
+use staking_core::StakingError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -8,24 +9,56 @@ pub enum ContractError {
     Std(#[from] StdError),
     #[error("{0}")]
     PaymentError(#[from] PaymentError),
-    #[error("Nothing to claim")]
-    NothingToClaim {},
-    #[error("Invalid token")]
-    InvalidToken { received: Addr, expected: Addr },
     #[error("Unauthorized")]
     Unauthorized {},
-    #[error("Too many outstanding claims. Claim some tokens before unstaking more.")]
-    TooManyClaims {},
     #[error("No admin configured")]
     NoAdminConfigured {},
     #[error("{0}")]
     HookError(#[from] cw_controllers::HookError),
+    #[error("{0}")]
+    Staking(#[from] StakingError),
     #[error("Only owner can change owner")]
     OnlyOwnerCanChangeOwner {},
-    #[error("Invalid unstaking duration, unstaking duration cannot be 0")]
-    InvalidUnstakingDuration {},
-    #[error("Can only unstake less than or equal to the amount you have staked")]
-    InvalidUnstakeAmount {},
+    #[error("Staked balance held in a position can only be moved with the position")]
+    StakeInPosition {},
+    #[error("Position is already unstaked")]
+    PositionUnstaked {},
+    #[error("Address is not on the allowlist")]
+    NotAllowlisted {},
+    #[error("Invalid Merkle root, expected a hex encoded 32 byte hash")]
+    InvalidMerkleRoot {},
+    #[error("Invalid Merkle proof")]
+    InvalidProof {},
+    #[error("Deposits of an address can not exceed {max}")]
+    MaxDepositExceeded { max: Uint128 },
+    #[error("Total staked can not exceed the hard cap of {cap}")]
+    HardCapExceeded { cap: Uint128 },
+    #[error("Can not stake less than {min}")]
+    BelowMinStake { min: Uint128 },
+    #[error("No open airdrop")]
+    NoAirdrop {},
+    #[error("The open airdrop must be reclaimed before registering another")]
+    AirdropOpen {},
+    #[error("Airdrop has expired")]
+    AirdropExpired {},
+    #[error("Airdrop has not expired yet")]
+    AirdropNotExpired {},
+    #[error("Airdrop already claimed")]
+    AirdropClaimed {},
+    #[error("Tokens can only be allocated by score once liquidity is seeded")]
+    ScoringOpen {},
+    #[error("Compounding rewards requires min_shares_out")]
+    MinSharesOutRequired {},
+    #[error("Less than {min} shares out of compounding")]
+    MinSharesOut { min: Uint128 },
+    #[error("Vesting rewards can not be compounded")]
+    CompoundingVested {},
+    #[error("Invalid compound route: {reason}")]
+    InvalidCompoundRoute { reason: String },
+    #[error("Vesting cliff can not be longer than the vesting duration")]
+    InvalidVestingConfig {},
+    #[error("Invalid seed target: {reason}")]
+    InvalidSeedTarget { reason: String },
     // invalid denom error
     #[error("Invalid denom")]
     InvalidDenom {},
@@ -62,6 +62,9 @@ fn setup() {
                 denom: pool_denom.clone(),
                 unstaking_duration: Some(Duration::Time(60)),
                 reward_contract_code_id: lockdrop_code_id,
+                deposit_limits: None,
+                allocate_by_score: None,
+                reward_vesting: None,
             },
             None,   // contract admin used for migration, not the same as cw1_whitelist admin
             None,   // contract label
@@ -76,7 +79,7 @@ fn setup() {
     // stake
     wasm.execute(
         &contract_addr,
-        &ExecuteMsg::Stake {},
+        &ExecuteMsg::Stake { recipient: None },
         &[Coin::new(1000, pool_denom.clone())],
         &alice,
     )
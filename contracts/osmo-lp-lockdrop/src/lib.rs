@@ -1,7 +1,13 @@
+pub mod airdrop;
+pub mod allocation;
 pub mod contract;
+pub mod deposit;
 mod error;
-pub mod hooks;
+pub mod lock;
+pub mod merkle;
 pub mod msg;
+pub mod position;
+pub mod seed;
 pub mod state;
 
 #[cfg(test)]
@@ -11,3 +17,4 @@ mod tests;
 // mod integration_tests;
 
 pub use crate::error::ContractError;
+pub use staking_core::hooks;
@@ -0,0 +1,188 @@
+use std::convert::TryInto;
+
+use cosmwasm_std::{
+    CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdResult, Storage, SubMsg,
+};
+use cw_storage_plus::Bound;
+use osmosis_std::shim::Duration;
+use osmosis_std::types::cosmos::base::v1beta1::Coin;
+use osmosis_std::types::osmosis::gamm::poolmodels::balancer::v1beta1::MsgCreateBalancerPoolResponse;
+use osmosis_std::types::osmosis::gamm::poolmodels::stableswap::v1beta1::MsgCreateStableswapPoolResponse;
+use osmosis_std::types::osmosis::lockup::{
+    MsgBeginUnlocking, MsgLockTokens, MsgLockTokensResponse,
+};
+use staking_core::staking::{DEFAULT_LIMIT, MAX_LIMIT};
+
+use crate::contract::ensure_owner_or_manager;
+use crate::msg::SeedLocksResponse;
+use crate::state::{SeedLock, LOCKING, SEED_LOCKS, SEED_LOCK_DURATION};
+use crate::ContractError;
+
+pub(crate) const CREATE_BALANCER_POOL_REPLY_ID: u64 = 5;
+pub(crate) const CREATE_STABLESWAP_POOL_REPLY_ID: u64 = 6;
+pub(crate) const LOCK_TOKENS_REPLY_ID: u64 = 7;
+
+/// Sends the pool creation `msg`, replying with `reply_id` to lock the new
+/// shares if seeded pool shares are locked.
+pub(crate) fn create_pool_submsg(
+    storage: &dyn Storage,
+    msg: CosmosMsg,
+    reply_id: u64,
+) -> StdResult<SubMsg> {
+    Ok(match SEED_LOCK_DURATION.may_load(storage)? {
+        Some(_) => SubMsg::reply_on_success(msg, reply_id),
+        None => SubMsg::new(msg),
+    })
+}
+
+pub fn execute_update_seed_lock(
+    deps: DepsMut,
+    info: MessageInfo,
+    duration: Option<u64>,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    match duration {
+        Some(duration) => SEED_LOCK_DURATION.save(deps.storage, &duration)?,
+        None => SEED_LOCK_DURATION.remove(deps.storage),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "update_seed_lock")
+        .add_attribute(
+            "duration",
+            duration
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| "None".to_string()),
+        ))
+}
+
+/// Locks all the contract's shares of the pool created by the replying
+/// message.
+pub fn reply_create_pool(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let pool_id = if msg.id == CREATE_STABLESWAP_POOL_REPLY_ID {
+        let res: MsgCreateStableswapPoolResponse = msg.result.try_into()?;
+        res.pool_id
+    } else {
+        let res: MsgCreateBalancerPoolResponse = msg.result.try_into()?;
+        res.pool_id
+    };
+    let duration = SEED_LOCK_DURATION.load(deps.storage)?;
+    let shares = deps
+        .querier
+        .query_balance(&env.contract.address, format!("gamm/pool/{}", pool_id))?;
+    LOCKING.save(deps.storage, &(pool_id, shares.amount))?;
+    let lock_msg = MsgLockTokens {
+        owner: env.contract.address.to_string(),
+        duration: Some(Duration {
+            seconds: duration as i64,
+            nanos: 0,
+        }),
+        coins: vec![Coin {
+            denom: shares.denom,
+            amount: shares.amount.to_string(),
+        }],
+    };
+    Ok(Response::new()
+        .add_attribute("action", "lock_seed_shares")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("shares", shares.amount)
+        .add_submessage(SubMsg::reply_on_success(lock_msg, LOCK_TOKENS_REPLY_ID)))
+}
+
+pub fn reply_lock_tokens(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let res: MsgLockTokensResponse = msg.result.try_into()?;
+    let (pool_id, shares) = LOCKING.load(deps.storage)?;
+    LOCKING.remove(deps.storage);
+    SEED_LOCKS.save(
+        deps.storage,
+        res.id,
+        &SeedLock {
+            lock_id: res.id,
+            pool_id,
+            shares,
+            duration: SEED_LOCK_DURATION.load(deps.storage)?,
+            unlocks_at: None,
+        },
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "record_seed_lock")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("lock_id", res.id.to_string()))
+}
+
+/// Removes the locks whose shares are back in the contract's balance.
+pub(crate) fn remove_matured_locks(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let matured = SEED_LOCKS
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, lock)) => lock.unlocks_at.map_or(false, |at| at <= env.block.time),
+            Err(_) => true,
+        })
+        .map(|item| item.map(|(lock_id, _)| lock_id))
+        .collect::<StdResult<Vec<_>>>()?;
+    for lock_id in matured {
+        SEED_LOCKS.remove(storage, lock_id);
+    }
+    Ok(())
+}
+
+/// Begins unbonding all locked seeded pool shares. Once their lock duration
+/// has passed, the shares are back in the contract's balance, from where
+/// `DistributeAllTokens` passes them on to stakers.
+fn unlock_seed_shares(storage: &mut dyn Storage, env: &Env) -> StdResult<Vec<CosmosMsg>> {
+    remove_matured_locks(storage, env)?;
+    let locks = SEED_LOCKS
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, lock)) if lock.unlocks_at.is_some()))
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut msgs = vec![];
+    for (lock_id, mut lock) in locks {
+        lock.unlocks_at = Some(env.block.time.plus_seconds(lock.duration));
+        SEED_LOCKS.save(storage, lock_id, &lock)?;
+        // no coins unlocks the whole lock
+        let msg: CosmosMsg = MsgBeginUnlocking {
+            owner: env.contract.address.to_string(),
+            id: lock_id,
+            coins: vec![],
+        }
+        .into();
+        msgs.push(msg);
+    }
+    Ok(msgs)
+}
+
+/// Lets the owner or manager begin unbonding, typically once the lockdrop
+/// is over. Stakers withdrawing do not touch the locks.
+pub fn execute_unlock_seed_shares(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    let msgs = unlock_seed_shares(deps.storage, &env)?;
+    Ok(Response::new()
+        .add_attribute("action", "unlock_seed_shares")
+        .add_attribute("locks", msgs.len().to_string())
+        .add_messages(msgs))
+}
+
+pub fn query_seed_locks(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SeedLocksResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let locks = SEED_LOCKS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, lock)| lock))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(SeedLocksResponse {
+        duration: SEED_LOCK_DURATION.may_load(deps.storage)?,
+        locks,
+    })
+}
@@ -0,0 +1,32 @@
+use sha2::{Digest, Sha256};
+
+use crate::ContractError;
+
+/// Errors unless `root` is a hex encoded 32 byte hash.
+pub fn validate_root(root: &str) -> Result<(), ContractError> {
+    let mut buf = [0u8; 32];
+    hex::decode_to_slice(root, &mut buf).map_err(|_e| ContractError::InvalidMerkleRoot {})
+}
+
+/// Verifies that `leaf` is in the tree with `root`. The leaf is hashed with
+/// SHA-256, then with each hex encoded proof hash in turn, sorting each pair
+/// before hashing it.
+pub fn verify_proof(root: &str, leaf: &[u8], proof: &[String]) -> Result<(), ContractError> {
+    let hash = proof.iter().try_fold(
+        Sha256::digest(leaf).into(),
+        |hash: [u8; 32], p| -> Result<[u8; 32], ContractError> {
+            let mut proof_buf = [0u8; 32];
+            hex::decode_to_slice(p, &mut proof_buf).map_err(|_e| ContractError::InvalidProof {})?;
+            let mut hashes = [hash, proof_buf];
+            hashes.sort_unstable();
+            Ok(Sha256::digest(&hashes.concat()).into())
+        },
+    )?;
+
+    let mut root_buf = [0u8; 32];
+    hex::decode_to_slice(root, &mut root_buf).map_err(|_e| ContractError::InvalidMerkleRoot {})?;
+    if root_buf != hash {
+        return Err(ContractError::InvalidProof {});
+    }
+    Ok(())
+}
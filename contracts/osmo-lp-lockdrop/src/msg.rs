@@ -1,10 +1,24 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Binary, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_utils::Duration;
+pub use cw721::{
+    AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, NftInfoResponse,
+    NumTokensResponse, OperatorsResponse, OwnerOfResponse, TokensResponse,
+};
+use cw_controllers::Claim;
+use cw_utils::{Duration, Expiration};
+use lockdrop_rewards::state::VestingConfig;
 
-pub use cw_controllers::ClaimsResponse;
+use crate::state::{Airdrop, DepositLimits, SeedLock, SeedTarget, SeededPosition, SwapHop};
+
+pub use staking_core::msg::{
+    AcceptsStakesResponse, ClaimsResponse, DelegationResponse, FailedHookResponse,
+    FailedHooksResponse, GetHooksResponse, InfoResponse, ListStakersResponse,
+    StakedBalanceAtHeightResponse, StakedValueResponse, StakerBalanceResponse,
+    TotalPowerAtHeightResponse, TotalStakedAtHeightResponse, TotalValueResponse,
+    VotingPowerAtHeightResponse,
+};
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct InstantiateMsg {
@@ -15,6 +29,12 @@ pub struct InstantiateMsg {
     pub denom: String,
     pub unstaking_duration: Option<Duration>,
     pub reward_contract_code_id: u64,
+    // Allowlist and caps on deposits, none if unset.
+    pub deposit_limits: Option<DepositLimits>,
+    // Allocates distributed tokens by time-weighted score, false if unset.
+    pub allocate_by_score: Option<bool>,
+    // Vesting of rewards claimed from the rewards contracts, none if unset.
+    pub reward_vesting: Option<VestingConfig>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -39,15 +59,41 @@ pub enum ExecuteMsg {
         gamm_denom: String,
     },
     Fund {},
-    Stake {},
+    // Stakes for `recipient` if given, otherwise for the sender.
+    Stake {
+        recipient: Option<String>,
+    },
     Unstake {
         amount: Uint128,
     },
-    Claim {},
+    // Claims at most `limit` matured claims, or all of them.
+    Claim {
+        limit: Option<u32>,
+    },
+    // Restakes unbonding claims which have not matured yet.
+    CancelUnbonding {
+        amount: Uint128,
+    },
+    ClaimRewards {
+        denoms: Option<Vec<String>>,
+        // Minimum LP shares to receive from all compounded reward denoms
+        // together, required when compounding.
+        min_shares_out: Option<Uint128>,
+    },
+    SetCompounding {
+        enabled: bool,
+    },
+    // Sets the swap route compounded rewards of `denom` take into an asset of
+    // the staked pool. An empty route removes it.
+    UpdateCompoundRoute {
+        denom: String,
+        route: Vec<SwapHop>,
+    },
     UpdateConfig {
         owner: Option<String>,
         manager: Option<String>,
         duration: Option<Duration>,
+        max_claims: Option<u64>,
     },
     AddHook {
         addr: String,
@@ -55,6 +101,115 @@ pub enum ExecuteMsg {
     RemoveHook {
         addr: String,
     },
+    UpdateHookGasLimit {
+        gas_limit: Option<u64>,
+    },
+    // Resends the message of a failed hook, removing it from the failure log if it succeeds.
+    ReplayFailedHook {
+        id: u64,
+    },
+    ClearFailedHook {
+        id: u64,
+    },
+    // Delegates the sender's voting power, replacing any earlier delegation.
+    Delegate {
+        to: String,
+    },
+    Undelegate {},
+    // Opts in or out of being staked for by other addresses.
+    SetAcceptStakes {
+        enabled: bool,
+    },
+    // Locked positions are cw20 receipts: moving them moves the staked balance.
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+    },
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    // Stakes the funds as a cw721 position which keeps the current unstaking
+    // duration. Only the holder of the position can unstake and claim it.
+    StakePosition {},
+    UnstakePosition {
+        token_id: String,
+    },
+    ClaimPosition {
+        token_id: String,
+    },
+    TransferNft {
+        recipient: String,
+        token_id: String,
+    },
+    SendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+    Approve {
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+    Revoke {
+        spender: String,
+        token_id: String,
+    },
+    // Lets `operator` transfer, approve and revoke all of the sender's positions.
+    ApproveAll {
+        operator: String,
+        expires: Option<Expiration>,
+    },
+    RevokeAll {
+        operator: String,
+    },
+    UpdateDepositLimits {
+        limits: DepositLimits,
+    },
+    UpdateAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    // Adds the sender to a Merkle root allowlist, proving its address is a leaf.
+    JoinAllowlist {
+        proof: Vec<String>,
+    },
+    // Airdrops the seed tokens sent along to the leaves of the Merkle tree.
+    RegisterAirdrop {
+        merkle_root: String,
+        denom: String,
+        expiry: Expiration,
+    },
+    ClaimAirdrop {
+        // Index of the sender's `index:address:amount` leaf.
+        index: u64,
+        amount: Uint128,
+        proof: Vec<String>,
+    },
+    // Returns the unclaimed tokens of an expired airdrop to the sender.
+    ReclaimAirdrop {},
+    // Claims the sender's share of the tokens allocated by score in `denom`.
+    ClaimAllocation {
+        denom: String,
+    },
+    // Sets the pool the ejected `denom` is seeded into, a balancer pool if unset.
+    UpdateSeedTarget {
+        denom: String,
+        target: Option<SeedTarget>,
+    },
+    // Locks the shares of seeded balancer and stableswap pools for `duration`
+    // seconds, or leaves them unlocked if unset.
+    UpdateSeedLock {
+        duration: Option<u64>,
+    },
+    // Begins unbonding the locked seeded pool shares, ending their incentives.
+    // Staker withdrawals leave the locks in place.
+    UnlockSeedShares {},
+    // Collects the spread rewards and incentives of the seeded concentrated
+    // liquidity positions and withdraws their liquidity.
+    WithdrawSeededPositions {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -68,6 +223,17 @@ pub enum ReceiveMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     AllRewardContracts {},
+    PendingRewards {
+        address: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Compounding {
+        address: String,
+    },
+    CompoundRoute {
+        denom: String,
+    },
     StakedBalanceAtHeight {
         address: String,
         height: Option<u64>,
@@ -84,10 +250,104 @@ pub enum QueryMsg {
         address: String,
     },
     GetHooks {},
+    FailedHooks {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     ListStakers {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    // DAO DAO voting module queries, answered from the staked balances.
+    VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    TotalPowerAtHeight {
+        height: Option<u64>,
+    },
+    Dao {},
+    Info {},
+    Delegation {
+        address: String,
+    },
+    AcceptsStakes {
+        address: String,
+    },
+    // cw20 balance of the position receipt, equal to the current staked balance.
+    Balance {
+        address: String,
+    },
+    // cw20 token info of the position receipt, whose supply is the total staked.
+    TokenInfo {},
+    // cw721 queries for positions.
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    Approval {
+        token_id: String,
+        spender: String,
+        include_expired: Option<bool>,
+    },
+    Approvals {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    AllOperators {
+        owner: String,
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    NumTokens {},
+    ContractInfo {},
+    NftInfo {
+        token_id: String,
+    },
+    AllNftInfo {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    DepositLimits {},
+    Allowlisted {
+        address: String,
+    },
+    Deposit {
+        address: String,
+    },
+    Airdrop {},
+    AirdropClaimed {
+        index: u64,
+    },
+    // Time-weighted score, the integral of the staked balance over time.
+    Score {
+        address: String,
+    },
+    ScoreAllocation {
+        address: String,
+        denom: String,
+    },
+    SeedTarget {
+        denom: String,
+    },
+    SeededPositions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    SeedLocks {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -99,51 +359,111 @@ pub enum MigrateMsg {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct StakedBalanceAtHeightResponse {
-    pub balance: Uint128,
-    pub height: u64,
+pub struct AllRewardContractsResponse {
+    pub reward_contracts: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct AllRewardContractsResponse {
-    pub reward_contracts: Vec<String>,
+pub struct PendingRewardsResponse {
+    pub pending_rewards: Vec<DenomPendingRewards>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct TotalStakedAtHeightResponse {
-    pub total: Uint128,
-    pub height: u64,
+pub struct DenomPendingRewards {
+    pub denom: String,
+    pub reward_contract: String,
+    pub pending_rewards: Uint128,
+    pub period_finish: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CompoundingResponse {
+    pub enabled: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct StakedValueResponse {
+pub struct CompoundRouteResponse {
+    pub denom: String,
+    pub route: Vec<SwapHop>,
+}
+
+/// The `NftInfo` extension of a position. Lockdrop positions have a single
+/// lock tier, the unstaking duration they were minted with.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PositionInfo {
+    pub shares: Uint128,
+    // Staked asset the position is worth, or its claim once unstaked.
     pub value: Uint128,
+    pub unstaking_duration: Option<Duration>,
+    pub claim: Option<Claim>,
+    // Share of its owner's pending rewards earned by the position's shares,
+    // empty once unstaked.
+    pub accrued_rewards: Vec<DenomPendingRewards>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct TotalValueResponse {
-    pub total: Uint128,
+pub struct AllowlistedResponse {
+    pub allowlisted: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct GetHooksResponse {
-    pub hooks: Vec<String>,
+pub struct DepositResponse {
+    pub deposit: Uint128,
+    // Left to deposit before reaching the max deposit, if there is one.
+    pub remaining: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AirdropResponse {
+    pub airdrop: Option<Airdrop>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct ListStakersResponse {
-    pub stakers: Vec<StakerBalanceResponse>,
+pub struct AirdropClaimedResponse {
+    pub claimed: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct StakerBalanceResponse {
-    pub address: String,
-    pub balance: Uint128,
+pub struct ScoreResponse {
+    pub score: Uint128,
+    pub total_score: Uint128,
+    // Time in seconds scoring closed at, if it has.
+    pub end: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ScoreAllocationResponse {
+    pub allocated: Uint128,
+    pub claimed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SeedTargetResponse {
+    pub target: SeedTarget,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SeededPositionsResponse {
+    pub positions: Vec<SeededPosition>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SeedLocksResponse {
+    // Lock duration of newly seeded pools, if any.
+    pub duration: Option<u64>,
+    pub locks: Vec<SeedLock>,
 }
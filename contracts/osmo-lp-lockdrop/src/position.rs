@@ -0,0 +1,629 @@
+use cosmwasm_std::{
+    Addr, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError,
+    StdResult, Storage, SubMsg, Uint128,
+};
+use cw721::{
+    AllNftInfoResponse, Approval, ApprovalResponse, ApprovalsResponse, ContractInfoResponse,
+    Cw721ReceiveMsg, NftInfoResponse, NumTokensResponse, OperatorsResponse, OwnerOfResponse,
+    TokensResponse,
+};
+use cw_controllers::Claim;
+use cw_storage_plus::Bound;
+use cw_utils::Expiration;
+use staking_core::staking::{self, DEFAULT_LIMIT, MAX_LIMIT};
+use staking_core::{NativeAsset, StakingAsset, StakingError};
+
+use crate::contract::{query_pending_rewards, RECEIPT_SYMBOL};
+use crate::deposit;
+use crate::msg::{DenomPendingRewards, PositionInfo};
+use crate::state::{
+    Position, BALANCE, CONFIG, LOCKED_SHARES, NUM_POSITIONS, OPERATORS, OWNER_POSITIONS, POSITIONS,
+    POSITION_COUNT, STAKED_BALANCES, STAKED_TOTAL,
+};
+use crate::ContractError;
+
+/// Errors if `amount` of `staker`'s staked balance would have to come out of
+/// shares held in positions.
+pub fn ensure_unlocked(
+    storage: &dyn Storage,
+    staker: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let locked = LOCKED_SHARES.may_load(storage, staker)?.unwrap_or_default();
+    if locked.is_zero() {
+        return Ok(());
+    }
+    let staked = STAKED_BALANCES
+        .may_load(storage, staker)?
+        .unwrap_or_default();
+    if amount > staked.checked_sub(locked).map_err(StdError::overflow)? {
+        return Err(ContractError::StakeInPosition {});
+    }
+    Ok(())
+}
+
+fn lock_shares(storage: &mut dyn Storage, owner: &Addr, shares: Uint128) -> StdResult<()> {
+    LOCKED_SHARES.update(storage, owner, |locked| -> StdResult<_> {
+        Ok(locked.unwrap_or_default().checked_add(shares)?)
+    })?;
+    Ok(())
+}
+
+fn unlock_shares(storage: &mut dyn Storage, owner: &Addr, shares: Uint128) -> StdResult<()> {
+    LOCKED_SHARES.update(storage, owner, |locked| -> StdResult<_> {
+        Ok(locked.unwrap_or_default().checked_sub(shares)?)
+    })?;
+    Ok(())
+}
+
+fn load_position(storage: &dyn Storage, token_id: &str) -> StdResult<(u64, Position)> {
+    let id: u64 = token_id
+        .parse()
+        .map_err(|_e| StdError::not_found("position"))?;
+    Ok((id, POSITIONS.load(storage, id)?))
+}
+
+fn remove_position(storage: &mut dyn Storage, id: u64, position: &Position) -> StdResult<()> {
+    POSITIONS.remove(storage, id);
+    OWNER_POSITIONS.remove(storage, (&position.owner, id));
+    let count = NUM_POSITIONS.may_load(storage)?.unwrap_or_default();
+    NUM_POSITIONS.save(storage, &count.saturating_sub(1))
+}
+
+fn ensure_owner(position: &Position, sender: &Addr) -> Result<(), ContractError> {
+    if position.owner != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+fn is_operator(storage: &dyn Storage, env: &Env, owner: &Addr, operator: &Addr) -> StdResult<bool> {
+    Ok(matches!(
+        OPERATORS.may_load(storage, (owner, operator))?,
+        Some(expires) if !expires.is_expired(&env.block)
+    ))
+}
+
+/// Errors unless `sender` owns the position or operates all of its owner's
+/// positions.
+fn ensure_owner_or_operator(
+    storage: &dyn Storage,
+    env: &Env,
+    position: &Position,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    if position.owner != *sender && !is_operator(storage, env, &position.owner, sender)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+pub fn execute_stake_position(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let amount = NativeAsset(config.denom).amount_in(&info, None)?;
+    let staked = STAKED_BALANCES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let hook_msgs = staking::stake(deps.storage, env, &info.sender, amount)?;
+    deposit::record_deposit(deps.storage, &info.sender, amount)?;
+    let shares = STAKED_BALANCES
+        .load(deps.storage, &info.sender)?
+        .checked_sub(staked)
+        .map_err(StdError::overflow)?;
+    lock_shares(deps.storage, &info.sender, shares)?;
+
+    let id = POSITION_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    POSITION_COUNT.save(deps.storage, &(id + 1))?;
+    POSITIONS.save(
+        deps.storage,
+        id,
+        &Position {
+            owner: info.sender.clone(),
+            approval: None,
+            shares,
+            unstaking_duration: config.unstaking_duration,
+            claim: None,
+        },
+    )?;
+    OWNER_POSITIONS.save(deps.storage, (&info.sender, id), &Empty {})?;
+    let count = NUM_POSITIONS.may_load(deps.storage)?.unwrap_or_default();
+    NUM_POSITIONS.save(deps.storage, &(count + 1))?;
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_attribute("action", "stake_position")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("token_id", id.to_string()))
+}
+
+/// Burns the shares of a position. Without an unstaking duration the
+/// position is paid out and burned straight away, otherwise it becomes a
+/// claim which its holder can collect once the duration has passed.
+pub fn execute_unstake_position(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let (id, mut position) = load_position(deps.storage, &token_id)?;
+    ensure_owner(&position, &info.sender)?;
+    if position.claim.is_some() {
+        return Err(ContractError::PositionUnstaked {});
+    }
+    unlock_shares(deps.storage, &position.owner, position.shares)?;
+    deposit::release_deposit(deps.storage, &position.owner, position.shares)?;
+    let (amount_to_claim, hook_msgs) =
+        staking::unstake(deps.storage, env, &position.owner, position.shares)?;
+    let res = Response::new()
+        .add_submessages(hook_msgs)
+        .add_attribute("action", "unstake_position")
+        .add_attribute("from", info.sender.clone())
+        .add_attribute("token_id", token_id)
+        .add_attribute("amount", amount_to_claim);
+    match position.unstaking_duration {
+        None => {
+            remove_position(deps.storage, id, &position)?;
+            let config = CONFIG.load(deps.storage)?;
+            Ok(res.add_message(
+                NativeAsset(config.denom).transfer_msg(&info.sender, amount_to_claim)?,
+            ))
+        }
+        Some(duration) => {
+            position.shares = Uint128::zero();
+            position.claim = Some(Claim {
+                amount: amount_to_claim,
+                release_at: duration.after(&env.block),
+            });
+            POSITIONS.save(deps.storage, id, &position)?;
+            Ok(res)
+        }
+    }
+}
+
+/// Pays out an unstaked position whose unstaking duration has passed and
+/// burns it.
+pub fn execute_claim_position(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let (id, position) = load_position(deps.storage, &token_id)?;
+    ensure_owner(&position, &info.sender)?;
+    let claim = match &position.claim {
+        Some(claim) if claim.release_at.is_expired(&env.block) => claim.clone(),
+        _ => return Err(ContractError::Staking(StakingError::NothingToClaim {})),
+    };
+    remove_position(deps.storage, id, &position)?;
+    let config = CONFIG.load(deps.storage)?;
+    Ok(Response::new()
+        .add_message(NativeAsset(config.denom).transfer_msg(&info.sender, claim.amount)?)
+        .add_attribute("action", "claim_position")
+        .add_attribute("from", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attribute("amount", claim.amount))
+}
+
+/// Hands a position to `recipient`, moving its shares, and the rewards and
+/// voting power they earn, along with it.
+fn transfer_position(
+    deps: DepsMut,
+    env: &Env,
+    sender: &Addr,
+    recipient: &Addr,
+    token_id: &str,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let (id, mut position) = load_position(deps.storage, token_id)?;
+    let approved = match &position.approval {
+        Some(approval) => {
+            approval.spender == sender.as_str() && !approval.expires.is_expired(&env.block)
+        }
+        None => false,
+    };
+    if !approved {
+        ensure_owner_or_operator(deps.storage, env, &position, sender)?;
+    }
+
+    let mut hook_msgs = vec![];
+    if !position.shares.is_zero() {
+        unlock_shares(deps.storage, &position.owner, position.shares)?;
+        lock_shares(deps.storage, recipient, position.shares)?;
+        deposit::transfer_deposit(deps.storage, &position.owner, recipient, position.shares)?;
+        hook_msgs = staking::transfer(
+            deps.storage,
+            env,
+            &position.owner,
+            recipient,
+            position.shares,
+        )?;
+    }
+    OWNER_POSITIONS.remove(deps.storage, (&position.owner, id));
+    OWNER_POSITIONS.save(deps.storage, (recipient, id), &Empty {})?;
+    position.owner = recipient.clone();
+    position.approval = None;
+    POSITIONS.save(deps.storage, id, &position)?;
+    Ok(hook_msgs)
+}
+
+pub fn execute_transfer_nft(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    recipient: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let hook_msgs = transfer_position(deps, env, &info.sender, &recipient, &token_id)?;
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_attribute("action", "transfer_nft")
+        .add_attribute("sender", info.sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_send_nft(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    contract: String,
+    token_id: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let contract = deps.api.addr_validate(&contract)?;
+    let hook_msgs = transfer_position(deps, env, &info.sender, &contract, &token_id)?;
+    let receive_msg: CosmosMsg = Cw721ReceiveMsg {
+        sender: info.sender.to_string(),
+        token_id: token_id.clone(),
+        msg,
+    }
+    .into_cosmos_msg(&contract)?;
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_message(receive_msg)
+        .add_attribute("action", "send_nft")
+        .add_attribute("sender", info.sender)
+        .add_attribute("recipient", contract)
+        .add_attribute("token_id", token_id))
+}
+
+/// Lets `spender` transfer the position until `expires`, replacing any
+/// earlier approval. Operators of the owner may approve on their behalf.
+pub fn execute_approve(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+    let (id, mut position) = load_position(deps.storage, &token_id)?;
+    ensure_owner_or_operator(deps.storage, env, &position, &info.sender)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Approval has already expired",
+        )));
+    }
+    position.approval = Some(Approval {
+        spender: spender.to_string(),
+        expires,
+    });
+    POSITIONS.save(deps.storage, id, &position)?;
+    Ok(Response::new()
+        .add_attribute("action", "approve")
+        .add_attribute("sender", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("token_id", token_id))
+}
+
+pub fn execute_revoke(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let (id, mut position) = load_position(deps.storage, &token_id)?;
+    ensure_owner_or_operator(deps.storage, env, &position, &info.sender)?;
+    if matches!(&position.approval, Some(approval) if approval.spender == spender) {
+        position.approval = None;
+        POSITIONS.save(deps.storage, id, &position)?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "revoke")
+        .add_attribute("sender", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("token_id", token_id))
+}
+
+/// Lets `operator` transfer, approve and revoke all of the sender's
+/// positions until `expires`.
+pub fn execute_approve_all(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    operator: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let operator = deps.api.addr_validate(&operator)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Approval has already expired",
+        )));
+    }
+    OPERATORS.save(deps.storage, (&info.sender, &operator), &expires)?;
+    Ok(Response::new()
+        .add_attribute("action", "approve_all")
+        .add_attribute("sender", info.sender)
+        .add_attribute("operator", operator))
+}
+
+pub fn execute_revoke_all(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: String,
+) -> Result<Response, ContractError> {
+    let operator = deps.api.addr_validate(&operator)?;
+    OPERATORS.remove(deps.storage, (&info.sender, &operator));
+    Ok(Response::new()
+        .add_attribute("action", "revoke_all")
+        .add_attribute("sender", info.sender)
+        .add_attribute("operator", operator))
+}
+
+fn approvals(position: &Position, env: &Env, include_expired: Option<bool>) -> Vec<Approval> {
+    let include_expired = include_expired.unwrap_or(false);
+    position
+        .approval
+        .iter()
+        .filter(|approval| include_expired || !approval.expires.is_expired(&env.block))
+        .cloned()
+        .collect()
+}
+
+pub fn query_owner_of(
+    deps: Deps,
+    env: &Env,
+    token_id: String,
+    include_expired: Option<bool>,
+) -> StdResult<OwnerOfResponse> {
+    let (_, position) = load_position(deps.storage, &token_id)?;
+    Ok(OwnerOfResponse {
+        approvals: approvals(&position, env, include_expired),
+        owner: position.owner.into_string(),
+    })
+}
+
+/// The approval of `spender` on the position. Its owner is always approved.
+pub fn query_approval(
+    deps: Deps,
+    env: &Env,
+    token_id: String,
+    spender: String,
+    include_expired: Option<bool>,
+) -> StdResult<ApprovalResponse> {
+    let (_, position) = load_position(deps.storage, &token_id)?;
+    if position.owner.as_str() == spender {
+        return Ok(ApprovalResponse {
+            approval: Approval {
+                spender,
+                expires: Expiration::Never {},
+            },
+        });
+    }
+    approvals(&position, env, include_expired)
+        .into_iter()
+        .find(|approval| approval.spender == spender)
+        .map(|approval| ApprovalResponse { approval })
+        .ok_or_else(|| StdError::not_found("Approval not found"))
+}
+
+pub fn query_approvals(
+    deps: Deps,
+    env: &Env,
+    token_id: String,
+    include_expired: Option<bool>,
+) -> StdResult<ApprovalsResponse> {
+    let (_, position) = load_position(deps.storage, &token_id)?;
+    Ok(ApprovalsResponse {
+        approvals: approvals(&position, env, include_expired),
+    })
+}
+
+pub fn query_all_operators(
+    deps: Deps,
+    env: &Env,
+    owner: String,
+    include_expired: Option<bool>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OperatorsResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let start_after = start_after
+        .map(|operator| deps.api.addr_validate(&operator))
+        .transpose()?;
+    let include_expired = include_expired.unwrap_or(false);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let operators = OPERATORS
+        .prefix(&owner)
+        .range(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .filter(|item| match item {
+            Ok((_, expires)) => include_expired || !expires.is_expired(&env.block),
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| {
+            item.map(|(operator, expires)| Approval {
+                spender: operator.into_string(),
+                expires,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(OperatorsResponse { operators })
+}
+
+pub fn query_num_tokens(deps: Deps) -> StdResult<NumTokensResponse> {
+    Ok(NumTokensResponse {
+        count: NUM_POSITIONS.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn query_contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ContractInfoResponse {
+        name: format!("Locked {} positions", config.denom),
+        symbol: RECEIPT_SYMBOL.to_string(),
+    })
+}
+
+/// The position's share of the rewards its owner has pending, in proportion
+/// to the owner's staked balance held in it.
+fn accrued_rewards(
+    deps: Deps,
+    env: &Env,
+    position: &Position,
+) -> StdResult<Vec<DenomPendingRewards>> {
+    if position.shares.is_zero() {
+        return Ok(vec![]);
+    }
+    let staked = STAKED_BALANCES
+        .may_load(deps.storage, &position.owner)?
+        .unwrap_or_default();
+    if staked.is_zero() {
+        return Ok(vec![]);
+    }
+    let pending = query_pending_rewards(
+        deps,
+        env.clone(),
+        position.owner.to_string(),
+        None,
+        Some(MAX_LIMIT),
+    )?;
+    Ok(pending
+        .pending_rewards
+        .into_iter()
+        .map(|rewards| DenomPendingRewards {
+            pending_rewards: rewards
+                .pending_rewards
+                .multiply_ratio(position.shares, staked),
+            ..rewards
+        })
+        .collect())
+}
+
+pub fn query_nft_info(
+    deps: Deps,
+    env: &Env,
+    token_id: String,
+) -> StdResult<NftInfoResponse<PositionInfo>> {
+    let (_, position) = load_position(deps.storage, &token_id)?;
+    nft_info(deps, env, position)
+}
+
+fn nft_info(deps: Deps, env: &Env, position: Position) -> StdResult<NftInfoResponse<PositionInfo>> {
+    let value = match &position.claim {
+        Some(claim) => claim.amount,
+        None => {
+            let balance = BALANCE.load(deps.storage)?;
+            let staked_total = STAKED_TOTAL.load(deps.storage)?;
+            if staked_total.is_zero() {
+                Uint128::zero()
+            } else {
+                position.shares.multiply_ratio(balance, staked_total)
+            }
+        }
+    };
+    Ok(NftInfoResponse {
+        token_uri: None,
+        extension: PositionInfo {
+            accrued_rewards: accrued_rewards(deps, env, &position)?,
+            shares: position.shares,
+            value,
+            unstaking_duration: position.unstaking_duration,
+            claim: position.claim,
+        },
+    })
+}
+
+pub fn query_all_nft_info(
+    deps: Deps,
+    env: &Env,
+    token_id: String,
+    include_expired: Option<bool>,
+) -> StdResult<AllNftInfoResponse<PositionInfo>> {
+    let (_, position) = load_position(deps.storage, &token_id)?;
+    Ok(AllNftInfoResponse {
+        access: OwnerOfResponse {
+            owner: position.owner.to_string(),
+            approvals: approvals(&position, env, include_expired),
+        },
+        info: nft_info(deps, env, position)?,
+    })
+}
+
+pub fn query_tokens(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let start_after = start_after
+        .map(|token_id| {
+            token_id
+                .parse::<u64>()
+                .map_err(|_e| StdError::generic_err("Invalid token id"))
+        })
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let tokens = OWNER_POSITIONS
+        .prefix(&owner)
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|id| id.map(|id| id.to_string()))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(TokensResponse { tokens })
+}
+
+pub fn query_all_tokens(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let start_after = start_after
+        .map(|token_id| {
+            token_id
+                .parse::<u64>()
+                .map_err(|_e| StdError::generic_err("Invalid token id"))
+        })
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let tokens = POSITIONS
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|id| id.map(|id| id.to_string()))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(TokensResponse { tokens })
+}
@@ -0,0 +1,341 @@
+use std::convert::TryInto;
+use std::str::FromStr;
+
+use cosmwasm_std::{
+    Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdError,
+    StdResult, Storage, SubMsg, Uint128,
+};
+use cw_storage_plus::Bound;
+use osmosis_std::types::cosmos::base::v1beta1::Coin as ProtoCoin;
+use osmosis_std::types::osmosis::concentratedliquidity::poolmodel::concentrated::v1beta1::{
+    MsgCreateConcentratedPool, MsgCreateConcentratedPoolResponse,
+};
+use osmosis_std::types::osmosis::concentratedliquidity::v1beta1::{
+    MsgCollectIncentives, MsgCollectSpreadRewards, MsgCreatePosition, MsgCreatePositionResponse,
+    MsgWithdrawPosition,
+};
+use osmosis_std::types::osmosis::gamm::poolmodels::stableswap::v1beta1::{
+    MsgCreateStableswapPool, PoolParams as StableswapPoolParams,
+};
+use staking_core::staking::{DEFAULT_LIMIT, MAX_LIMIT};
+
+use crate::contract::ensure_owner_or_manager;
+use crate::lock::{self, CREATE_STABLESWAP_POOL_REPLY_ID};
+use crate::msg::{SeedTargetResponse, SeededPositionsResponse};
+use crate::state::{
+    PendingPosition, SeedTarget, SeededPosition, TickRange, PENDING_POSITIONS, SEEDED_POSITIONS,
+    SEEDING_POSITION, SEED_TARGETS,
+};
+use crate::ContractError;
+
+pub(crate) const CREATE_CONCENTRATED_POOL_REPLY_ID: u64 = 3;
+pub(crate) const CREATE_POSITION_REPLY_ID: u64 = 4;
+
+/// Ticks bounding a full range concentrated liquidity position.
+pub const MIN_TICK: i64 = -108_000_000;
+pub const MAX_TICK: i64 = 342_000_000;
+
+/// Slippage allowed when creating a seeded position in percent, if its target
+/// sets none.
+pub const DEFAULT_MAX_SLIPPAGE_PERCENT: u64 = 1;
+
+/// One as a protobuf encoded decimal, which has 18 decimal places.
+const PROTO_DEC_ONE: u128 = 1_000_000_000_000_000_000;
+
+fn invalid(reason: &str) -> ContractError {
+    ContractError::InvalidSeedTarget {
+        reason: reason.to_string(),
+    }
+}
+
+/// Errors unless `value` is a protobuf encoded decimal below one.
+fn validate_fraction(value: &str, name: &str) -> Result<(), ContractError> {
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid(&format!(
+            "{} must be a protobuf encoded decimal",
+            name
+        )));
+    }
+    match Uint128::from_str(value) {
+        Ok(dec) if dec < Uint128::new(PROTO_DEC_ONE) => Ok(()),
+        _ => Err(invalid(&format!("{} must be below one", name))),
+    }
+}
+
+pub fn validate_target(target: &SeedTarget) -> Result<(), ContractError> {
+    match target {
+        SeedTarget::Balancer {} => Ok(()),
+        SeedTarget::Concentrated {
+            tick_spacing,
+            spread_factor,
+            range,
+            max_slippage,
+        } => {
+            if *tick_spacing == 0 {
+                return Err(invalid("tick spacing must be positive"));
+            }
+            validate_fraction(spread_factor, "spread factor")?;
+            if matches!(max_slippage, Some(slippage) if *slippage >= Decimal::one()) {
+                return Err(invalid("max slippage must be below one"));
+            }
+            if let Some(TickRange {
+                lower_tick,
+                upper_tick,
+            }) = range
+            {
+                if lower_tick >= upper_tick || *lower_tick < MIN_TICK || *upper_tick > MAX_TICK {
+                    return Err(invalid("tick range out of bounds"));
+                }
+                let spacing = *tick_spacing as i64;
+                if lower_tick % spacing != 0 || upper_tick % spacing != 0 {
+                    return Err(invalid("ticks must be multiples of the tick spacing"));
+                }
+            }
+            Ok(())
+        }
+        SeedTarget::Stableswap {
+            swap_fee,
+            asset_scaling_factor,
+            seed_scaling_factor,
+        } => {
+            if *asset_scaling_factor == 0 || *seed_scaling_factor == 0 {
+                return Err(invalid("scaling factors must be positive"));
+            }
+            validate_fraction(swap_fee, "swap fee")?;
+            Ok(())
+        }
+    }
+}
+
+pub fn execute_update_seed_target(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    target: Option<SeedTarget>,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    match target {
+        Some(target) => {
+            validate_target(&target)?;
+            SEED_TARGETS.save(deps.storage, &denom, &target)?;
+        }
+        None => SEED_TARGETS.remove(deps.storage, &denom),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "update_seed_target")
+        .add_attribute("denom", denom))
+}
+
+pub fn seed_target(storage: &dyn Storage, denom: &str) -> StdResult<SeedTarget> {
+    Ok(SEED_TARGETS.may_load(storage, denom)?.unwrap_or_default())
+}
+
+/// Creates a concentrated liquidity pool of `asset` and `seed`. The position
+/// is created with both once the pool replies with its id.
+pub(crate) fn create_concentrated_pool(
+    storage: &mut dyn Storage,
+    env: &Env,
+    tick_spacing: u64,
+    spread_factor: String,
+    range: Option<TickRange>,
+    max_slippage: Option<Decimal>,
+    asset: Coin,
+    seed: Coin,
+) -> StdResult<SubMsg> {
+    let msg = MsgCreateConcentratedPool {
+        sender: env.contract.address.to_string(),
+        denom0: asset.denom.clone(),
+        denom1: seed.denom.clone(),
+        tick_spacing,
+        spread_factor,
+    };
+    let range = range.unwrap_or(TickRange {
+        lower_tick: MIN_TICK,
+        upper_tick: MAX_TICK,
+    });
+    let mut pending = PENDING_POSITIONS.may_load(storage)?.unwrap_or_default();
+    pending.push(PendingPosition {
+        tokens: vec![asset, seed],
+        range,
+        max_slippage: max_slippage
+            .unwrap_or_else(|| Decimal::percent(DEFAULT_MAX_SLIPPAGE_PERCENT)),
+    });
+    PENDING_POSITIONS.save(storage, &pending)?;
+    Ok(SubMsg::reply_on_success(
+        msg,
+        CREATE_CONCENTRATED_POOL_REPLY_ID,
+    ))
+}
+
+/// Creates a stableswap pool of `asset` and `seed`, scaled by their factors.
+pub(crate) fn create_stableswap_pool(
+    storage: &dyn Storage,
+    env: &Env,
+    swap_fee: String,
+    asset_scaling_factor: u64,
+    seed_scaling_factor: u64,
+    asset: Coin,
+    seed: Coin,
+) -> StdResult<SubMsg> {
+    // the liquidity is sorted by denom on chain, with the factors in its order
+    let mut liquidity = vec![(asset, asset_scaling_factor), (seed, seed_scaling_factor)];
+    liquidity.sort_by(|(a, _), (b, _)| a.denom.cmp(&b.denom));
+    let msg = MsgCreateStableswapPool {
+        sender: env.contract.address.to_string(),
+        pool_params: Some(StableswapPoolParams {
+            swap_fee,
+            exit_fee: "0".to_string(),
+        }),
+        initial_pool_liquidity: liquidity
+            .iter()
+            .map(|(coin, _)| ProtoCoin {
+                denom: coin.denom.clone(),
+                amount: coin.amount.to_string(),
+            })
+            .collect(),
+        scaling_factors: liquidity.iter().map(|(_, factor)| *factor).collect(),
+        future_pool_governor: "24h".to_string(),
+        scaling_factor_controller: String::new(),
+    };
+    lock::create_pool_submsg(storage, msg.into(), CREATE_STABLESWAP_POOL_REPLY_ID)
+}
+
+pub fn reply_create_concentrated_pool(
+    deps: DepsMut,
+    env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    let res: MsgCreateConcentratedPoolResponse = msg.result.try_into()?;
+    let mut pending = PENDING_POSITIONS.load(deps.storage)?;
+    if pending.is_empty() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "no pending position",
+        )));
+    }
+    let position = pending.remove(0);
+    PENDING_POSITIONS.save(deps.storage, &pending)?;
+    SEEDING_POSITION.save(deps.storage, &(res.pool_id, position.range.clone()))?;
+
+    // coins are sorted by denom on chain
+    let mut tokens = position.tokens;
+    tokens.sort_by(|a, b| a.denom.cmp(&b.denom));
+    let min_amounts: Vec<String> = tokens
+        .iter()
+        .map(|coin| (coin.amount * (Decimal::one() - position.max_slippage)).to_string())
+        .collect();
+    let create_position_msg = MsgCreatePosition {
+        pool_id: res.pool_id,
+        sender: env.contract.address.to_string(),
+        lower_tick: position.range.lower_tick,
+        upper_tick: position.range.upper_tick,
+        tokens_provided: tokens
+            .into_iter()
+            .map(|coin| ProtoCoin {
+                denom: coin.denom,
+                amount: coin.amount.to_string(),
+            })
+            .collect(),
+        token_min_amount0: min_amounts[0].clone(),
+        token_min_amount1: min_amounts[1].clone(),
+    };
+    Ok(Response::new()
+        .add_attribute("action", "create_concentrated_pool")
+        .add_attribute("pool_id", res.pool_id.to_string())
+        .add_submessage(SubMsg::reply_on_success(
+            create_position_msg,
+            CREATE_POSITION_REPLY_ID,
+        )))
+}
+
+pub fn reply_create_position(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let res: MsgCreatePositionResponse = msg.result.try_into()?;
+    let (pool_id, range) = SEEDING_POSITION.load(deps.storage)?;
+    SEEDING_POSITION.remove(deps.storage);
+    SEEDED_POSITIONS.save(
+        deps.storage,
+        res.position_id,
+        &SeededPosition {
+            position_id: res.position_id,
+            pool_id,
+            range,
+            liquidity: res.liquidity_created,
+        },
+    )?;
+    Ok(Response::new()
+        .add_attribute("action", "create_position")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("position_id", res.position_id.to_string()))
+}
+
+/// Collects the spread rewards and incentives of all seeded positions and
+/// withdraws their liquidity. The tokens return to the contract's balance,
+/// from where `DistributeAllTokens` passes them on to stakers.
+pub fn execute_withdraw_seeded_positions(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    ensure_owner_or_manager(deps.storage, &info.sender)?;
+    let positions = SEEDED_POSITIONS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    if positions.is_empty() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "no seeded positions",
+        )));
+    }
+    let sender = env.contract.address.to_string();
+    let position_ids: Vec<u64> = positions.iter().map(|(id, _)| *id).collect();
+    let mut msgs: Vec<CosmosMsg> = vec![
+        MsgCollectSpreadRewards {
+            position_ids: position_ids.clone(),
+            sender: sender.clone(),
+        }
+        .into(),
+        MsgCollectIncentives {
+            position_ids: position_ids.clone(),
+            sender: sender.clone(),
+        }
+        .into(),
+    ];
+    for (position_id, position) in positions {
+        SEEDED_POSITIONS.remove(deps.storage, position_id);
+        msgs.push(
+            MsgWithdrawPosition {
+                position_id,
+                sender: sender.clone(),
+                liquidity_amount: position.liquidity,
+            }
+            .into(),
+        );
+    }
+    Ok(Response::new()
+        .add_attribute("action", "withdraw_seeded_positions")
+        .add_attribute("positions", position_ids.len().to_string())
+        .add_messages(msgs))
+}
+
+pub fn query_seed_target(deps: Deps, denom: String) -> StdResult<SeedTargetResponse> {
+    Ok(SeedTargetResponse {
+        target: seed_target(deps.storage, &denom)?,
+    })
+}
+
+pub fn query_seeded_positions(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SeededPositionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let positions = SEEDED_POSITIONS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, position)| position))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(SeededPositionsResponse { positions })
+}
@@ -1,11 +1,17 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
-use cw_controllers::Claims;
-use cw_controllers::Hooks;
-use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
-use cw_utils::Duration;
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Timestamp, Uint128};
+use cw721::Approval;
+use cw_controllers::Claim;
+use cw_storage_plus::{Item, Map};
+use cw_utils::{Duration, Expiration};
+use lockdrop_rewards::state::VestingConfig;
+
+pub use staking_core::state::{
+    FailedHook, BALANCE, CLAIMS, DAO, FAILED_HOOKS, FAILED_HOOK_COUNT, HOOKS, HOOK_GAS_LIMIT,
+    MAX_CLAIMS, SCORE_END, STAKED_BALANCES, STAKED_TOTAL,
+};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Config {
@@ -13,33 +19,236 @@ pub struct Config {
     pub manager: Option<Addr>,
     pub denom: String,
     pub unstaking_duration: Option<Duration>,
+    // Outstanding claims allowed per staker, `MAX_CLAIMS` if unset.
+    pub max_claims: Option<u64>,
     pub reward_contract_code_id: u64,
+    // Distributed tokens are allocated by time-weighted score instead of
+    // streamed by staked balance through reward contracts.
+    #[serde(default)]
+    pub allocate_by_score: bool,
+    // Vesting applied by the rewards contracts instantiated for distributed
+    // tokens.
+    #[serde(default)]
+    pub reward_vesting: Option<VestingConfig>,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
-pub const STAKED_BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
-    "staked_balances",
-    "staked_balance__checkpoints",
-    "staked_balance__changelog",
-    Strategy::EveryBlock,
-);
+pub const REWARD_CONTRACTS_BY_DENOM: Map<&str, Addr> = Map::new("reward_contracts_by_denom");
 
-pub const STAKED_TOTAL: SnapshotItem<Uint128> = SnapshotItem::new(
-    "total_staked",
-    "total_staked__checkpoints",
-    "total_staked__changelog",
-    Strategy::EveryBlock,
-);
+/// Stakers who opted into having their claimed rewards joined into the
+/// staked pool and restaked.
+pub const COMPOUNDING: Map<&Addr, bool> = Map::new("compounding");
 
-/// The maximum number of claims that may be outstanding.
-pub const MAX_CLAIMS: u64 = 100;
+/// A compounding claim in progress, read back when its swap and join pool
+/// submessages reply.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingCompound {
+    pub staker: Addr,
+    pub pool_id: u64,
+    // Minimum shares out of all joins together.
+    pub min_shares_out: Uint128,
+    // Denoms the swapped rewards end in, in the order the swaps reply.
+    pub swap_denoms: Vec<String>,
+    pub joins_left: u64,
+    pub shares_out: Uint128,
+}
 
-pub const CLAIMS: Claims = Claims::new("claims");
+pub const PENDING_COMPOUND: Item<PendingCompound> = Item::new("pending_compound");
 
-pub const BALANCE: Item<Uint128> = Item::new("balance");
+/// One hop of a swap route, through `pool_id` into `token_out_denom`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct SwapHop {
+    pub pool_id: u64,
+    pub token_out_denom: String,
+}
 
-// Hooks to contracts that will receive staking and unstaking messages
-pub const HOOKS: Hooks = Hooks::new("hooks");
+/// Swap routes into an asset of the staked pool for compounded rewards which
+/// are not one of its assets, by reward denom.
+pub const COMPOUND_ROUTES: Map<&str, Vec<SwapHop>> = Map::new("compound_routes");
 
-pub const REWARD_CONTRACTS_BY_DENOM: Map<&str, Addr> = Map::new("reward_contracts_by_denom");
+/// A stake minted as a cw721 position. Its shares are part of the owner's
+/// staked balance, but can only be unstaked or moved with the position.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Position {
+    pub owner: Addr,
+    pub approval: Option<Approval>,
+    pub shares: Uint128,
+    // The unstaking duration when the position was minted, kept for the
+    // position's lifetime.
+    pub unstaking_duration: Option<Duration>,
+    // Set once the position is unstaked, and paid out to its holder on claim.
+    pub claim: Option<Claim>,
+}
+
+pub const POSITIONS: Map<u64, Position> = Map::new("positions");
+
+pub const POSITION_COUNT: Item<u64> = Item::new("position_count");
+
+pub const OWNER_POSITIONS: Map<(&Addr, u64), Empty> = Map::new("owner_positions");
+
+/// Positions not yet burned.
+pub const NUM_POSITIONS: Item<u64> = Item::new("num_positions");
+
+/// Operators each owner lets handle all of their positions, until they
+/// expire.
+pub const OPERATORS: Map<(&Addr, &Addr), Expiration> = Map::new("operators");
+
+/// Shares of each staker held in positions.
+pub const LOCKED_SHARES: Map<&Addr, Uint128> = Map::new("locked_shares");
+
+/// Addresses allowed to stake when the lockdrop has an allowlist.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Allowlist {
+    // Addresses join with a proof against this hex encoded SHA-256 Merkle root.
+    MerkleRoot { root: String },
+    // Only addresses added by the owner or manager.
+    Addresses {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct DepositLimits {
+    pub allowlist: Option<Allowlist>,
+    // Most an address can have deposited at once.
+    pub max_deposit: Option<Uint128>,
+    // Most `STAKED_TOTAL` can reach through deposits.
+    pub hard_cap: Option<Uint128>,
+    // Least amount a single deposit can stake.
+    pub min_stake: Option<Uint128>,
+}
+
+pub const DEPOSIT_LIMITS: Item<DepositLimits> = Item::new("deposit_limits");
+
+pub const ALLOWLIST: Map<&Addr, Empty> = Map::new("allowlist");
+
+/// Total deposited by each address, credited to the stake's recipient.
+/// Unstaking, transferring and `UnstakePosition` release it pro rata to the
+/// shares leaving the address, and transfers credit that part to the
+/// recipient.
+pub const DEPOSITS: Map<&Addr, Uint128> = Map::new("deposits");
+
+/// Seed tokens airdropped to the leaves of a Merkle tree.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Airdrop {
+    pub id: u64,
+    // Hex encoded SHA-256 root of a tree with `index:address:amount` leaves.
+    pub merkle_root: String,
+    pub denom: String,
+    pub expiry: Expiration,
+    pub total: Uint128,
+    pub claimed: Uint128,
+}
+
+/// The open airdrop, removed once its unclaimed tokens are reclaimed.
+pub const AIRDROP: Item<Airdrop> = Item::new("airdrop");
+
+pub const AIRDROP_COUNT: Item<u64> = Item::new("airdrop_count");
+
+/// Claimed leaf indices of each airdrop, 64 per bitmap word.
+pub const AIRDROP_CLAIMED: Map<(u64, u64), u64> = Map::new("airdrop_claimed");
+
+/// Tokens of a denom allocated to stakers in proportion to their final score.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct ScoreAllocation {
+    pub total: Uint128,
+    pub claimed: Uint128,
+}
+
+pub const SCORE_ALLOCATIONS: Map<&str, ScoreAllocation> = Map::new("score_allocations");
+
+pub const SCORE_ALLOCATION_CLAIMS: Map<(&Addr, &str), Uint128> =
+    Map::new("score_allocation_claims");
+
+/// Ticks a concentrated liquidity position spans.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TickRange {
+    pub lower_tick: i64,
+    pub upper_tick: i64,
+}
+
+/// Pool an ejected asset is seeded into along with its share of the seed
+/// tokens.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedTarget {
+    // Balancer pool weighting both tokens equally.
+    Balancer {},
+    // Concentrated liquidity pool with a position over `range`, the full
+    // range if unset. The spread factor is a protobuf encoded decimal. The
+    // position has to take at least `1 - max_slippage` of each token
+    // provided, with a max slippage of 1% if unset.
+    Concentrated {
+        tick_spacing: u64,
+        spread_factor: String,
+        range: Option<TickRange>,
+        max_slippage: Option<Decimal>,
+    },
+    // Stableswap pool for pegged tokens, scaling the ejected asset and the
+    // seed token by their factors. The swap fee is a protobuf encoded decimal.
+    // Osmosis stableswap pools have no amplification parameter, the scaling
+    // factors are all there is to configure the curve.
+    Stableswap {
+        swap_fee: String,
+        asset_scaling_factor: u64,
+        seed_scaling_factor: u64,
+    },
+}
+
+impl Default for SeedTarget {
+    fn default() -> Self {
+        SeedTarget::Balancer {}
+    }
+}
+
+/// Seed target of each ejected asset, `SeedTarget::Balancer` if unset.
+pub const SEED_TARGETS: Map<&str, SeedTarget> = Map::new("seed_targets");
+
+/// A position to create in a concentrated liquidity pool being seeded.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingPosition {
+    pub tokens: Vec<Coin>,
+    pub range: TickRange,
+    pub max_slippage: Decimal,
+}
+
+/// Positions to create, in the order their pools are created. The pool
+/// creation replies pop them.
+pub const PENDING_POSITIONS: Item<Vec<PendingPosition>> = Item::new("pending_positions");
+
+/// Pool and range of the position being created, read back by its reply.
+pub const SEEDING_POSITION: Item<(u64, TickRange)> = Item::new("seeding_position");
+
+/// A concentrated liquidity position created by seeding, held by the
+/// contract on behalf of the stakers.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SeededPosition {
+    pub position_id: u64,
+    pub pool_id: u64,
+    pub range: TickRange,
+    pub liquidity: String,
+}
+
+pub const SEEDED_POSITIONS: Map<u64, SeededPosition> = Map::new("seeded_positions");
+
+/// Seconds seeded pool shares are locked for in the lockup module to earn
+/// incentives. Shares are not locked if unset.
+pub const SEED_LOCK_DURATION: Item<u64> = Item::new("seed_lock_duration");
+
+/// Pool and amount of the shares being locked, read back by the lock's reply.
+pub const LOCKING: Item<(u64, Uint128)> = Item::new("locking");
+
+/// Seeded pool shares locked by the contract.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SeedLock {
+    pub lock_id: u64,
+    pub pool_id: u64,
+    pub shares: Uint128,
+    // Lock duration in seconds.
+    pub duration: u64,
+    // When the shares return to the contract, set once unbonding begins. The
+    // lock is removed once this has passed.
+    pub unlocks_at: Option<Timestamp>,
+}
+
+pub const SEED_LOCKS: Map<u64, SeedLock> = Map::new("seed_locks");
@@ -1,23 +1,64 @@
 use std::borrow::BorrowMut;
+use std::marker::PhantomData;
 
 use crate::msg::{
-    ExecuteMsg, InstantiateMsg, ListStakersResponse, QueryMsg, StakedBalanceAtHeightResponse,
-    StakedValueResponse, StakerBalanceResponse, TotalStakedAtHeightResponse, TotalValueResponse,
+    AirdropClaimedResponse, AirdropResponse, AllNftInfoResponse, AllRewardContractsResponse,
+    AllowlistedResponse, ApprovalResponse, ApprovalsResponse, CompoundRouteResponse,
+    CompoundingResponse, ContractInfoResponse, DepositResponse, ExecuteMsg, FailedHooksResponse,
+    InfoResponse, InstantiateMsg, ListStakersResponse, NftInfoResponse, NumTokensResponse,
+    OperatorsResponse, OwnerOfResponse, PendingRewardsResponse, PositionInfo, QueryMsg,
+    SeedLocksResponse, SeedTargetResponse, SeededPositionsResponse, StakedBalanceAtHeightResponse,
+    StakedValueResponse, StakerBalanceResponse, TokensResponse, TotalPowerAtHeightResponse,
+    TotalStakedAtHeightResponse, TotalValueResponse, VotingPowerAtHeightResponse,
 };
-use crate::state::Config;
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{coins, Addr, Coin, Empty, Uint128};
+use crate::state::{
+    Allowlist, Config, DepositLimits, SeedLock, SeedTarget, SeededPosition, SwapHop, TickRange,
+    PENDING_COMPOUND, REWARD_CONTRACTS_BY_DENOM,
+};
+use crate::ContractError;
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    coins, from_binary, from_slice, to_binary, Addr, BankMsg, Binary, Coin, ContractResult,
+    CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, OwnedDeps, Querier, QuerierResult, QueryRequest,
+    Reply, ReplyOn, SubMsgResponse, SubMsgResult, SystemResult, Uint128, WasmQuery,
+};
+use cw20::{BalanceResponse, Denom, TokenInfoResponse};
+use cw721::Approval;
 use cw_controllers::ClaimsResponse;
 use cw_multi_test::{
-    custom_app, next_block, App, AppResponse, Contract, ContractWrapper, Executor,
+    custom_app, next_block, App, AppResponse, BankSudo, Contract, ContractWrapper, Executor,
+    SudoMsg,
+};
+use cw_utils::{Duration, Expiration};
+use lockdrop_rewards::state::VestingConfig;
+use osmosis_std::types::cosmos::base::v1beta1::Coin as ProtoCoin;
+use osmosis_std::types::osmosis::concentratedliquidity::poolmodel::concentrated::v1beta1::MsgCreateConcentratedPoolResponse;
+use osmosis_std::types::osmosis::concentratedliquidity::v1beta1::{
+    MsgCollectIncentives, MsgCollectSpreadRewards, MsgCreatePosition, MsgCreatePositionResponse,
+    MsgWithdrawPosition,
 };
-use cw_utils::Duration;
+use osmosis_std::types::osmosis::gamm::poolmodels::balancer::v1beta1::MsgCreateBalancerPoolResponse;
+use osmosis_std::types::osmosis::gamm::poolmodels::stableswap::v1beta1::{
+    MsgCreateStableswapPool, PoolParams as StableswapPoolParams,
+};
+use osmosis_std::types::osmosis::gamm::v1beta1::{
+    MsgJoinSwapExternAmountIn, MsgJoinSwapExternAmountInResponse, QueryTotalPoolLiquidityResponse,
+};
+use osmosis_std::types::osmosis::lockup::{MsgBeginUnlocking, MsgLockTokensResponse};
+use osmosis_std::types::osmosis::poolmanager::v1beta1::{
+    MsgSwapExactAmountIn, MsgSwapExactAmountInResponse, SwapAmountInRoute,
+};
+use staking_core::StakingError;
 
 const DAO_ADDR: &str = "dao";
 const ADDR1: &str = "addr1";
 const ADDR2: &str = "addr2";
 const DENOM: &str = "ujuno";
 const INVALID_DENOM: &str = "uinvalid";
+const REWARD_DENOM: &str = "useed";
+const OTHER_REWARD_DENOM: &str = "uother";
 
 fn query_staked_balance<T: Into<String>, U: Into<String>>(
     app: &App,
@@ -63,7 +104,8 @@ fn staking_contract() -> Box<dyn Contract<Empty>> {
         crate::contract::execute,
         crate::contract::instantiate,
         crate::contract::query,
-    );
+    )
+    .with_reply(crate::contract::reply);
     Box::new(contract)
 }
 
@@ -151,7 +193,7 @@ fn stake_tokens(
     app.execute_contract(
         Addr::unchecked(sender),
         staking_addr.clone(),
-        &ExecuteMsg::Stake {},
+        &ExecuteMsg::Stake { recipient: None },
         &coins(amount, denom),
     )
 }
@@ -176,7 +218,7 @@ fn claim(app: &mut App, staking_addr: Addr, sender: &str) -> anyhow::Result<AppR
     app.execute_contract(
         Addr::unchecked(sender),
         staking_addr,
-        &ExecuteMsg::Claim {},
+        &ExecuteMsg::Claim { limit: None },
         &[],
     )
 }
@@ -196,6 +238,7 @@ fn update_config(
             owner,
             manager,
             duration,
+            max_claims: None,
         },
         &[],
     )
@@ -217,6 +260,59 @@ fn get_balance(app: &App, address: &str, denom: &str) -> Uint128 {
     app.wrap().query_balance(address, denom).unwrap().amount
 }
 
+fn distribute_rewards(app: &mut App, staking_addr: &Addr, denom: &str, amount: u128) {
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: staking_addr.to_string(),
+        amount: coins(amount, denom),
+    }))
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(DAO_ADDR),
+        staking_addr.clone(),
+        &ExecuteMsg::DistributeToken {
+            denom: denom.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+fn query_pending_rewards(
+    app: &App,
+    staking_addr: &Addr,
+    address: &str,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> PendingRewardsResponse {
+    app.wrap()
+        .query_wasm_smart(
+            staking_addr,
+            &QueryMsg::PendingRewards {
+                address: address.to_string(),
+                start_after,
+                limit,
+            },
+        )
+        .unwrap()
+}
+
+fn claim_rewards(
+    app: &mut App,
+    staking_addr: &Addr,
+    sender: &str,
+    denoms: Option<Vec<String>>,
+) -> anyhow::Result<AppResponse> {
+    app.execute_contract(
+        Addr::unchecked(sender),
+        staking_addr.clone(),
+        &ExecuteMsg::ClaimRewards {
+            denoms,
+            min_shares_out: None,
+        },
+        &[],
+    )
+}
+
 #[test]
 fn test_instantiate() {
     let mut app = mock_app();
@@ -232,6 +328,9 @@ fn test_instantiate() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -245,6 +344,9 @@ fn test_instantiate() {
             denom: DENOM.to_string(),
             unstaking_duration: None,
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 }
@@ -264,6 +366,9 @@ fn test_instantiate_dao_owner() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -288,6 +393,9 @@ fn test_instantiate_invalid_unstaking_duration() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(0)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
     let reward_id = app.store_code(reward_contract());
@@ -302,6 +410,9 @@ fn test_instantiate_invalid_unstaking_duration() {
             denom: DENOM.to_string(),
             unstaking_duration: None,
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 }
@@ -321,6 +432,9 @@ fn test_stake_invalid_denom() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -342,6 +456,9 @@ fn test_stake_valid_denom() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -365,6 +482,9 @@ fn test_unstake_none_staked() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -386,6 +506,9 @@ fn test_unstake_invalid_balance() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -411,6 +534,9 @@ fn test_unstake() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -448,6 +574,9 @@ fn test_unstake_no_unstaking_duration() {
             denom: DENOM.to_string(),
             unstaking_duration: None,
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -488,6 +617,9 @@ fn test_claim_no_claims() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -510,6 +642,9 @@ fn test_claim_claim_not_reached() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -540,6 +675,9 @@ fn test_claim() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -594,6 +732,9 @@ fn test_update_config_invalid_sender() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -624,6 +765,9 @@ fn test_update_config_non_owner_changes_owner() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -645,6 +789,9 @@ fn test_update_config_as_owner() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -665,8 +812,11 @@ fn test_update_config_as_owner() {
             owner: Some(Addr::unchecked(ADDR1)),
             manager: Some(Addr::unchecked(DAO_ADDR)),
             unstaking_duration: Some(Duration::Height(10)),
+            max_claims: None,
             denom: DENOM.to_string(),
             reward_contract_code_id: reward_id,
+            allocate_by_score: false,
+            reward_vesting: None,
         },
         config
     );
@@ -686,6 +836,9 @@ fn test_update_config_as_manager() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -706,8 +859,11 @@ fn test_update_config_as_manager() {
             owner: Some(Addr::unchecked(DAO_ADDR)),
             manager: Some(Addr::unchecked(ADDR2)),
             unstaking_duration: Some(Duration::Height(10)),
+            max_claims: None,
             denom: DENOM.to_string(),
             reward_contract_code_id: reward_id,
+            allocate_by_score: false,
+            reward_vesting: None,
         },
         config
     );
@@ -728,6 +884,9 @@ fn test_update_config_invalid_duration() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -758,6 +917,9 @@ fn test_query_claims() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -796,6 +958,9 @@ fn test_query_get_config() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -806,8 +971,11 @@ fn test_query_get_config() {
             owner: Some(Addr::unchecked(DAO_ADDR)),
             manager: Some(Addr::unchecked(ADDR1)),
             unstaking_duration: Some(Duration::Height(5)),
+            max_claims: None,
             denom: DENOM.to_string(),
-            reward_contract_code_id: reward_id
+            reward_contract_code_id: reward_id,
+            allocate_by_score: false,
+            reward_vesting: None,
         }
     )
 }
@@ -827,6 +995,9 @@ fn test_query_list_stakers() {
             denom: DENOM.to_string(),
             unstaking_duration: Some(Duration::Height(5)),
             reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
 
@@ -899,6 +1070,272 @@ fn test_query_list_stakers() {
     assert_eq!(stakers, ListStakersResponse { stakers: vec![] });
 }
 
+#[test]
+fn test_claim_rewards() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let reward_contract_code_id = app.store_code(reward_contract());
+
+    let addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: Some(ADDR1.to_string()),
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    );
+
+    stake_tokens(&mut app, &addr, ADDR1, 100, DENOM).unwrap();
+    stake_tokens(&mut app, &addr, ADDR2, 100, DENOM).unwrap();
+
+    // Nothing has been distributed yet
+    let err: ContractError = claim_rewards(&mut app, &addr, ADDR1, None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Staking(StakingError::NothingToClaim {}));
+
+    // 2400 over 24 blocks is 100 per block, split between both stakers
+    distribute_rewards(&mut app, &addr, REWARD_DENOM, 2400);
+    app.update_block(|b| b.height += 2);
+
+    claim_rewards(&mut app, &addr, ADDR1, None).unwrap();
+    assert_eq!(get_balance(&app, ADDR1, REWARD_DENOM), Uint128::new(100));
+    assert_eq!(
+        get_balance(&app, addr.as_str(), REWARD_DENOM),
+        Uint128::zero()
+    );
+
+    // Claimed rewards can't be claimed twice
+    let err: ContractError = claim_rewards(&mut app, &addr, ADDR1, None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Staking(StakingError::NothingToClaim {}));
+
+    // Unknown denoms are rejected
+    let err: ContractError = claim_rewards(
+        &mut app,
+        &addr,
+        ADDR2,
+        Some(vec![INVALID_DENOM.to_string()]),
+    )
+    .unwrap_err()
+    .downcast()
+    .unwrap();
+    assert_eq!(err, ContractError::InvalidDenom {});
+
+    claim_rewards(&mut app, &addr, ADDR2, Some(vec![REWARD_DENOM.to_string()])).unwrap();
+    assert_eq!(get_balance(&app, ADDR2, REWARD_DENOM), Uint128::new(100));
+}
+
+#[test]
+fn test_reward_vesting() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let reward_contract_code_id = app.store_code(reward_contract());
+    let mut msg = InstantiateMsg {
+        owner: Some(DAO_ADDR.to_string()),
+        manager: Some(ADDR1.to_string()),
+        denom: DENOM.to_string(),
+        unstaking_duration: None,
+        reward_contract_code_id,
+        deposit_limits: None,
+        allocate_by_score: None,
+        reward_vesting: Some(VestingConfig {
+            cliff: 25,
+            duration: 24,
+        }),
+    };
+    let err: ContractError = app
+        .instantiate_contract(
+            staking_id,
+            Addr::unchecked(DAO_ADDR),
+            &msg,
+            &[],
+            "Staking",
+            None,
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidVestingConfig {});
+
+    msg.reward_vesting = Some(VestingConfig {
+        cliff: 0,
+        duration: 24,
+    });
+    let addr = instantiate_staking(&mut app, staking_id, msg);
+    stake_tokens(&mut app, &addr, ADDR1, 100, DENOM).unwrap();
+    stake_tokens(&mut app, &addr, ADDR2, 100, DENOM).unwrap();
+    distribute_rewards(&mut app, &addr, REWARD_DENOM, 2400);
+    app.update_block(|b| b.height += 2);
+
+    // Claimed rewards vest in the rewards contract instead of being paid out
+    claim_rewards(&mut app, &addr, ADDR1, None).unwrap();
+    assert_eq!(get_balance(&app, ADDR1, REWARD_DENOM), Uint128::zero());
+    let reward_contracts: AllRewardContractsResponse = app
+        .wrap()
+        .query_wasm_smart(&addr, &QueryMsg::AllRewardContracts {})
+        .unwrap();
+    let reward_addr = reward_contracts.reward_contracts[0].clone();
+    let vesting: lockdrop_rewards::msg::VestingResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &reward_addr,
+            &lockdrop_rewards::msg::QueryMsg::GetVesting {
+                address: ADDR1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(vesting.unvested, Uint128::new(100));
+
+    // and are withdrawn from it as they unlock
+    app.update_block(|b| b.height += 12);
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        Addr::unchecked(reward_addr),
+        &lockdrop_rewards::msg::ExecuteMsg::WithdrawVested {},
+        &[],
+    )
+    .unwrap();
+    assert_eq!(get_balance(&app, ADDR1, REWARD_DENOM), Uint128::new(50));
+}
+
+#[test]
+fn test_query_pending_rewards() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let reward_contract_code_id = app.store_code(reward_contract());
+
+    let addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: Some(ADDR1.to_string()),
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    );
+
+    stake_tokens(&mut app, &addr, ADDR1, 100, DENOM).unwrap();
+    let res = query_pending_rewards(&app, &addr, ADDR1, None, None);
+    assert!(res.pending_rewards.is_empty());
+
+    // 100 and 200 per block over 24 blocks
+    distribute_rewards(&mut app, &addr, REWARD_DENOM, 2400);
+    distribute_rewards(&mut app, &addr, OTHER_REWARD_DENOM, 4800);
+    let period_finish = app.block_info().height + 24;
+    app.update_block(|b| b.height += 2);
+
+    let res = query_pending_rewards(&app, &addr, ADDR1, None, None);
+    let pending: Vec<(String, Uint128, u64)> = res
+        .pending_rewards
+        .iter()
+        .map(|r| (r.denom.clone(), r.pending_rewards, r.period_finish))
+        .collect();
+    assert_eq!(
+        pending,
+        vec![
+            (
+                OTHER_REWARD_DENOM.to_string(),
+                Uint128::new(400),
+                period_finish
+            ),
+            (REWARD_DENOM.to_string(), Uint128::new(200), period_finish),
+        ]
+    );
+
+    // Paginate over denoms
+    let res = query_pending_rewards(&app, &addr, ADDR1, None, Some(1));
+    assert_eq!(res.pending_rewards.len(), 1);
+    assert_eq!(res.pending_rewards[0].denom, OTHER_REWARD_DENOM);
+    let res = query_pending_rewards(
+        &app,
+        &addr,
+        ADDR1,
+        Some(OTHER_REWARD_DENOM.to_string()),
+        None,
+    );
+    assert_eq!(res.pending_rewards.len(), 1);
+    assert_eq!(res.pending_rewards[0].denom, REWARD_DENOM);
+
+    // Non stakers have nothing pending
+    let res = query_pending_rewards(&app, &addr, ADDR2, None, None);
+    assert!(res
+        .pending_rewards
+        .iter()
+        .all(|r| r.pending_rewards.is_zero()));
+}
+
+#[test]
+fn test_set_compounding() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let reward_contract_code_id = app.store_code(reward_contract());
+
+    let addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: Some(ADDR1.to_string()),
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    );
+
+    let query_compounding = |app: &App, address: &str| -> bool {
+        let res: CompoundingResponse = app
+            .wrap()
+            .query_wasm_smart(
+                addr.clone(),
+                &QueryMsg::Compounding {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.enabled
+    };
+
+    // Compounding is opt-in
+    assert!(!query_compounding(&app, ADDR1));
+
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        addr.clone(),
+        &ExecuteMsg::SetCompounding { enabled: true },
+        &[],
+    )
+    .unwrap();
+    assert!(query_compounding(&app, ADDR1));
+    assert!(!query_compounding(&app, ADDR2));
+
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        addr.clone(),
+        &ExecuteMsg::SetCompounding { enabled: false },
+        &[],
+    )
+    .unwrap();
+    assert!(!query_compounding(&app, ADDR1));
+}
+
 fn mock_compounding_app() -> App {
     custom_app(|r, _a, s| {
         r.bank
@@ -971,6 +1408,9 @@ fn test_auto_compounding_staking() {
             denom: DENOM.to_string(),
             unstaking_duration: None,
             reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
         },
     );
     app.update_block(next_block);
@@ -1167,3 +1607,2003 @@ fn test_auto_compounding_staking() {
         "Balance should be 65 after unstaking"
     );
 }
+
+#[test]
+fn test_voting_module_queries() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let reward_id = app.store_code(reward_contract());
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: None,
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id: reward_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    );
+
+    stake_tokens(&mut app, &staking_addr, ADDR1, 100, DENOM).unwrap();
+    let staked_height = app.block_info().height;
+    app.update_block(next_block);
+    stake_tokens(&mut app, &staking_addr, ADDR2, 50, DENOM).unwrap();
+    app.update_block(next_block);
+
+    // Power is snapshotted like staked balances, so a stake counts from the
+    // block after it
+    let power: VotingPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::VotingPowerAtHeight {
+                address: ADDR1.to_string(),
+                height: Some(staked_height),
+            },
+        )
+        .unwrap();
+    assert_eq!(power.power, Uint128::zero());
+    let power: VotingPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::VotingPowerAtHeight {
+                address: ADDR1.to_string(),
+                height: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(power.power, Uint128::new(100));
+    assert_eq!(power.height, app.block_info().height);
+
+    let total: TotalPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::TotalPowerAtHeight {
+                height: Some(staked_height + 1),
+            },
+        )
+        .unwrap();
+    assert_eq!(total.power, Uint128::new(100));
+    let total: TotalPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::TotalPowerAtHeight { height: None },
+        )
+        .unwrap();
+    assert_eq!(total.power, Uint128::new(150));
+
+    let dao: Addr = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::Dao {})
+        .unwrap();
+    assert_eq!(dao, Addr::unchecked(DAO_ADDR));
+    let info: InfoResponse = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::Info {})
+        .unwrap();
+    assert_eq!(info.info.contract, crate::contract::CONTRACT_NAME);
+    assert_eq!(info.info.version, crate::contract::CONTRACT_VERSION);
+}
+
+#[test]
+fn test_transfer_position() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let reward_contract_code_id = app.store_code(reward_contract());
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    );
+    // Not a contract, so every hook fails and is logged with its message
+    app.execute_contract(
+        Addr::unchecked(DAO_ADDR),
+        staking_addr.clone(),
+        &ExecuteMsg::AddHook {
+            addr: "hook".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    stake_tokens(&mut app, &staking_addr, ADDR1, 100, DENOM).unwrap();
+    app.execute_contract(
+        Addr::unchecked(ADDR2),
+        staking_addr.clone(),
+        &ExecuteMsg::Delegate {
+            to: DAO_ADDR.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(next_block);
+
+    for amount in [0, 101] {
+        let err: ContractError = app
+            .execute_contract(
+                Addr::unchecked(ADDR1),
+                staking_addr.clone(),
+                &ExecuteMsg::Transfer {
+                    recipient: ADDR2.to_string(),
+                    amount: Uint128::new(amount),
+                },
+                &[],
+            )
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(
+            err,
+            ContractError::Staking(StakingError::InvalidTransferAmount {})
+        );
+    }
+
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::Transfer {
+            recipient: ADDR2.to_string(),
+            amount: Uint128::new(40),
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(next_block);
+
+    let balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::Balance {
+                address: ADDR1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance, Uint128::new(60));
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR2),
+        Uint128::new(40)
+    );
+    assert_eq!(query_total_staked(&app, &staking_addr), Uint128::new(100));
+    let token_info: TokenInfoResponse = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(
+        token_info,
+        TokenInfoResponse {
+            name: format!("Locked {}", DENOM),
+            symbol: "LOCKED".to_string(),
+            decimals: 18,
+            total_supply: Uint128::new(100),
+        }
+    );
+    // The recipient's delegation carries over to the received position
+    let power: VotingPowerAtHeightResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::VotingPowerAtHeight {
+                address: DAO_ADDR.to_string(),
+                height: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(power.power, Uint128::new(40));
+
+    // Both sides of the transfer are reported to the hooks
+    let failed: FailedHooksResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::FailedHooks {
+                start_after: Some(0),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(failed.failed_hooks.len(), 2);
+    let msgs: Vec<String> = failed
+        .failed_hooks
+        .into_iter()
+        .map(|failed_hook| String::from_utf8(failed_hook.msg.to_vec()).unwrap())
+        .collect();
+    assert!(msgs[0].contains("unstake") && msgs[0].contains(ADDR1));
+    assert!(msgs[1].contains("\"stake\"") && msgs[1].contains(ADDR2));
+
+    // The recipient can unstake the received position
+    unstake_tokens(&mut app, &staking_addr, ADDR2, 40).unwrap();
+    assert_eq!(get_balance(&app, ADDR2, DENOM), Uint128::new(10040));
+}
+
+#[test]
+fn test_position_nft() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let reward_contract_code_id = app.store_code(reward_contract());
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: Some(Duration::Height(5)),
+            reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    );
+
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::StakePosition {},
+        &coins(100, DENOM),
+    )
+    .unwrap();
+    stake_tokens(&mut app, &staking_addr, ADDR1, 50, DENOM).unwrap();
+    app.update_block(next_block);
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR1),
+        Uint128::new(150)
+    );
+
+    let tokens: TokensResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::Tokens {
+                owner: ADDR1.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(tokens.tokens, vec!["0".to_string()]);
+    let info: NftInfoResponse<PositionInfo> = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::NftInfo {
+                token_id: "0".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(info.extension.shares, Uint128::new(100));
+    assert_eq!(info.extension.value, Uint128::new(100));
+    assert_eq!(info.extension.unstaking_duration, Some(Duration::Height(5)));
+
+    // Shares in the position can't leave through the fungible balance
+    let err: ContractError = unstake_tokens(&mut app, &staking_addr, ADDR1, 60)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::StakeInPosition {});
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADDR1),
+            staking_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: ADDR2.to_string(),
+                amount: Uint128::new(51),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::StakeInPosition {});
+
+    // Only the holder, or an approved spender, can move or unstake it
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADDR2),
+            staking_addr.clone(),
+            &ExecuteMsg::UnstakePosition {
+                token_id: "0".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::Approve {
+            spender: ADDR2.to_string(),
+            token_id: "0".to_string(),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(ADDR2),
+        staking_addr.clone(),
+        &ExecuteMsg::TransferNft {
+            recipient: ADDR2.to_string(),
+            token_id: "0".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(next_block);
+
+    let owner: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::OwnerOf {
+                token_id: "0".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(owner.owner, ADDR2.to_string());
+    assert!(owner.approvals.is_empty());
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR1),
+        Uint128::new(50)
+    );
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR2),
+        Uint128::new(100)
+    );
+
+    app.execute_contract(
+        Addr::unchecked(ADDR2),
+        staking_addr.clone(),
+        &ExecuteMsg::UnstakePosition {
+            token_id: "0".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_total_staked(&app, &staking_addr), Uint128::new(50));
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADDR2),
+            staking_addr.clone(),
+            &ExecuteMsg::ClaimPosition {
+                token_id: "0".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Staking(StakingError::NothingToClaim {}));
+
+    // The position keeps its unstaking duration and is burned once claimed
+    app.update_block(|b| b.height += 5);
+    app.execute_contract(
+        Addr::unchecked(ADDR2),
+        staking_addr.clone(),
+        &ExecuteMsg::ClaimPosition {
+            token_id: "0".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(get_balance(&app, ADDR2, DENOM), Uint128::new(10100));
+    let tokens: TokensResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::Tokens {
+                owner: ADDR2.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(tokens.tokens.is_empty());
+}
+
+#[test]
+fn test_position_operators() {
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let reward_contract_code_id = app.store_code(reward_contract());
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    );
+    for amount in [100, 50] {
+        app.execute_contract(
+            Addr::unchecked(ADDR1),
+            staking_addr.clone(),
+            &ExecuteMsg::StakePosition {},
+            &coins(amount, DENOM),
+        )
+        .unwrap();
+    }
+    stake_tokens(&mut app, &staking_addr, ADDR1, 50, DENOM).unwrap();
+    app.update_block(next_block);
+
+    let info: ContractInfoResponse = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::ContractInfo {})
+        .unwrap();
+    assert_eq!(info.name, format!("Locked {} positions", DENOM));
+    assert_eq!(info.symbol, "LOCKED");
+    let num: NumTokensResponse = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::NumTokens {})
+        .unwrap();
+    assert_eq!(num.count, 2);
+    let tokens: TokensResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::AllTokens {
+                start_after: Some("0".to_string()),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(tokens.tokens, vec!["1".to_string()]);
+
+    // 100 per block over 24 blocks, the first position holds half the shares
+    distribute_rewards(&mut app, &staking_addr, REWARD_DENOM, 2400);
+    app.update_block(|b| b.height += 2);
+    let info: AllNftInfoResponse<PositionInfo> = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::AllNftInfo {
+                token_id: "0".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(info.access.owner, ADDR1.to_string());
+    assert_eq!(info.info.extension.accrued_rewards.len(), 1);
+    assert_eq!(info.info.extension.accrued_rewards[0].denom, REWARD_DENOM);
+    assert_eq!(
+        info.info.extension.accrued_rewards[0].pending_rewards,
+        Uint128::new(100)
+    );
+
+    // An operator can approve and transfer all of the owner's positions
+    let transfer = |app: &mut App, sender: &str, token_id: &str| {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            staking_addr.clone(),
+            &ExecuteMsg::TransferNft {
+                recipient: ADDR2.to_string(),
+                token_id: token_id.to_string(),
+            },
+            &[],
+        )
+    };
+    let err: ContractError = transfer(&mut app, ADDR2, "0")
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::ApproveAll {
+            operator: ADDR2.to_string(),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let operators: OperatorsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::AllOperators {
+                owner: ADDR1.to_string(),
+                include_expired: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        operators.operators,
+        vec![Approval {
+            spender: ADDR2.to_string(),
+            expires: Expiration::Never {},
+        }]
+    );
+    app.execute_contract(
+        Addr::unchecked(ADDR2),
+        staking_addr.clone(),
+        &ExecuteMsg::Approve {
+            spender: DAO_ADDR.to_string(),
+            token_id: "1".to_string(),
+            expires: Some(Expiration::AtHeight(app.block_info().height + 10)),
+        },
+        &[],
+    )
+    .unwrap();
+    let approval: ApprovalResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::Approval {
+                token_id: "1".to_string(),
+                spender: DAO_ADDR.to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        approval.approval.expires,
+        Expiration::AtHeight(app.block_info().height + 10)
+    );
+    let approvals: ApprovalsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::Approvals {
+                token_id: "0".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert!(approvals.approvals.is_empty());
+    transfer(&mut app, ADDR2, "0").unwrap();
+    assert_eq!(
+        query_staked_balance(&app, &staking_addr, ADDR2),
+        Uint128::new(100)
+    );
+
+    // Revoked operators lose access
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::RevokeAll {
+            operator: ADDR2.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    let err: ContractError = transfer(&mut app, ADDR2, "1")
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Burned positions no longer count
+    app.execute_contract(
+        Addr::unchecked(ADDR2),
+        staking_addr.clone(),
+        &ExecuteMsg::UnstakePosition {
+            token_id: "0".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    let num: NumTokensResponse = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::NumTokens {})
+        .unwrap();
+    assert_eq!(num.count, 1);
+    let tokens: TokensResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &staking_addr,
+            &QueryMsg::AllTokens {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(tokens.tokens, vec!["1".to_string()]);
+}
+
+#[test]
+fn test_deposit_limits() {
+    // Merkle tree with the leaves ADDR1 and ADDR2
+    const ROOT: &str = "cb1d1e04b7fab019b312e6e2eec60bf5ad1323fcc3f3adc43163e315204b5bff";
+    const ADDR1_PROOF: &str = "c0152f12bc5c339eaf36577e92516be86f21ada60004f84cea7b0b2e048f8678";
+    const ADDR2_PROOF: &str = "ae2f402b5bb9a32b5306f9ceb578f30e46ad5719a27beea243ae1cf22652f634";
+
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let reward_contract_code_id = app.store_code(reward_contract());
+    let limits = DepositLimits {
+        allowlist: Some(Allowlist::MerkleRoot {
+            root: ROOT.to_string(),
+        }),
+        max_deposit: Some(Uint128::new(500)),
+        hard_cap: Some(Uint128::new(800)),
+        min_stake: Some(Uint128::new(10)),
+    };
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id,
+            deposit_limits: Some(limits.clone()),
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    );
+    let limits_resp: DepositLimits = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::DepositLimits {})
+        .unwrap();
+    assert_eq!(limits_resp, limits);
+
+    let join = |app: &mut App, sender: &str, proof: &str| {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            staking_addr.clone(),
+            &ExecuteMsg::JoinAllowlist {
+                proof: vec![proof.to_string()],
+            },
+            &[],
+        )
+    };
+    let allowlisted = |app: &App, address: &str| {
+        let resp: AllowlistedResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &staking_addr,
+                &QueryMsg::Allowlisted {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        resp.allowlisted
+    };
+
+    // Only allowlisted addresses can stake, joining with a valid proof
+    let err: ContractError = stake_tokens(&mut app, &staking_addr, ADDR1, 100, DENOM)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NotAllowlisted {});
+    let err: ContractError = join(&mut app, ADDR1, ADDR2_PROOF)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidProof {});
+    assert!(!allowlisted(&app, ADDR1));
+    join(&mut app, ADDR1, ADDR1_PROOF).unwrap();
+    join(&mut app, ADDR2, ADDR2_PROOF).unwrap();
+    assert!(allowlisted(&app, ADDR1));
+
+    let err: ContractError = stake_tokens(&mut app, &staking_addr, ADDR1, 5, DENOM)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::BelowMinStake {
+            min: Uint128::new(10)
+        }
+    );
+
+    // Unstaking frees up room under the max deposit
+    let deposit_of = |app: &App, address: &str| {
+        let resp: DepositResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &staking_addr,
+                &QueryMsg::Deposit {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        resp
+    };
+    stake_tokens(&mut app, &staking_addr, ADDR1, 400, DENOM).unwrap();
+    unstake_tokens(&mut app, &staking_addr, ADDR1, 100).unwrap();
+    assert_eq!(deposit_of(&app, ADDR1).deposit, Uint128::new(300));
+    stake_tokens(&mut app, &staking_addr, ADDR1, 200, DENOM).unwrap();
+    let err: ContractError = stake_tokens(&mut app, &staking_addr, ADDR1, 10, DENOM)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::MaxDepositExceeded {
+            max: Uint128::new(500)
+        }
+    );
+    assert_eq!(
+        deposit_of(&app, ADDR1),
+        DepositResponse {
+            deposit: Uint128::new(500),
+            remaining: Some(Uint128::zero()),
+        }
+    );
+
+    // 500 is staked, so the hard cap leaves room for 300
+    let err: ContractError = stake_tokens(&mut app, &staking_addr, ADDR2, 301, DENOM)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::HardCapExceeded {
+            cap: Uint128::new(800)
+        }
+    );
+    stake_tokens(&mut app, &staking_addr, ADDR2, 300, DENOM).unwrap();
+    app.update_block(next_block);
+    assert_eq!(query_total_staked(&app, &staking_addr), Uint128::new(800));
+
+    // Transfers count against the recipient's max deposit
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADDR2),
+            staking_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: ADDR1.to_string(),
+                amount: Uint128::new(100),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::MaxDepositExceeded {
+            max: Uint128::new(500)
+        }
+    );
+
+    // The owner can switch to an explicit address allowlist
+    let update_limits = |app: &mut App, sender: &str, limits: DepositLimits| {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            staking_addr.clone(),
+            &ExecuteMsg::UpdateDepositLimits { limits },
+            &[],
+        )
+    };
+    let limits = DepositLimits {
+        allowlist: Some(Allowlist::Addresses {}),
+        ..DepositLimits::default()
+    };
+    let err: ContractError = update_limits(&mut app, ADDR1, limits.clone())
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+    update_limits(&mut app, DAO_ADDR, limits).unwrap();
+    app.execute_contract(
+        Addr::unchecked(DAO_ADDR),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateAllowlist {
+            add: vec![DAO_ADDR.to_string()],
+            remove: vec![ADDR2.to_string()],
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(!allowlisted(&app, ADDR2));
+    let err: ContractError = stake_tokens(&mut app, &staking_addr, ADDR2, 10, DENOM)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NotAllowlisted {});
+
+    // Neither staked balances nor positions can be handed to an address off
+    // the allowlist, and transfers move the deposit with the shares
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::StakePosition {},
+        &coins(10, DENOM),
+    )
+    .unwrap();
+    let transfer = |app: &mut App, recipient: &str| {
+        app.execute_contract(
+            Addr::unchecked(ADDR1),
+            staking_addr.clone(),
+            &ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: Uint128::new(102),
+            },
+            &[],
+        )
+    };
+    let err: ContractError = transfer(&mut app, ADDR2).unwrap_err().downcast().unwrap();
+    assert_eq!(err, ContractError::NotAllowlisted {});
+    let err: ContractError = app
+        .execute_contract(
+            Addr::unchecked(ADDR1),
+            staking_addr.clone(),
+            &ExecuteMsg::TransferNft {
+                recipient: ADDR2.to_string(),
+                token_id: "0".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NotAllowlisted {});
+    transfer(&mut app, DAO_ADDR).unwrap();
+    assert_eq!(deposit_of(&app, ADDR1).deposit, Uint128::new(408));
+    assert_eq!(deposit_of(&app, DAO_ADDR).deposit, Uint128::new(102));
+    stake_tokens(&mut app, &staking_addr, DAO_ADDR, 1000, DENOM).unwrap();
+    assert_eq!(deposit_of(&app, DAO_ADDR).deposit, Uint128::new(1102));
+}
+
+#[test]
+fn test_airdrop() {
+    // Merkle tree with the leaves 0:addr1:100, 1:addr2:250, 2:addr3:300 and
+    // 3:addr4:350
+    const ROOT: &str = "b0413c92a7009a7d3b42234ac64a897c6475021953f6887aa0e25d6900d964f9";
+    const ADDR1_PROOF: [&str; 2] = [
+        "d018f3cd6263f0cb3f8281128001c3e6d4e04288c8ba955cf70fe6a0db3c79a0",
+        "a72dab0a3518e0a584cdd7e9d63c1364af3d7235514c1641352429cf7d6e544c",
+    ];
+    const ADDR2_PROOF: [&str; 2] = [
+        "73e40f66dd1d9254ab0a652f8a23afc577b571897ef31e93f0d243682373d735",
+        "a72dab0a3518e0a584cdd7e9d63c1364af3d7235514c1641352429cf7d6e544c",
+    ];
+
+    let mut app = mock_app();
+    let staking_id = app.store_code(staking_contract());
+    let reward_contract_code_id = app.store_code(reward_contract());
+    let staking_addr = instantiate_staking(
+        &mut app,
+        staking_id,
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: Some(ADDR2.to_string()),
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    );
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: DAO_ADDR.to_string(),
+        amount: coins(2000, REWARD_DENOM),
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: ADDR1.to_string(),
+        amount: coins(1000, REWARD_DENOM),
+    }))
+    .unwrap();
+
+    let register = |app: &mut App, sender: &str, expiry: Expiration| {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            staking_addr.clone(),
+            &ExecuteMsg::RegisterAirdrop {
+                merkle_root: ROOT.to_string(),
+                denom: REWARD_DENOM.to_string(),
+                expiry,
+            },
+            &coins(1000, REWARD_DENOM),
+        )
+    };
+    let claim_airdrop =
+        |app: &mut App, sender: &str, index: u64, amount: u128, proof: [&str; 2]| {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                staking_addr.clone(),
+                &ExecuteMsg::ClaimAirdrop {
+                    index,
+                    amount: Uint128::new(amount),
+                    proof: proof.iter().map(|p| p.to_string()).collect(),
+                },
+                &[],
+            )
+        };
+    let claimed = |app: &App, index: u64| {
+        let resp: AirdropClaimedResponse = app
+            .wrap()
+            .query_wasm_smart(&staking_addr, &QueryMsg::AirdropClaimed { index })
+            .unwrap();
+        resp.claimed
+    };
+    let reclaim = |app: &mut App| {
+        app.execute_contract(
+            Addr::unchecked(ADDR2),
+            staking_addr.clone(),
+            &ExecuteMsg::ReclaimAirdrop {},
+            &[],
+        )
+    };
+
+    let expiry = Expiration::AtHeight(app.block_info().height + 100);
+    let err: ContractError = register(&mut app, ADDR1, expiry)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+    register(&mut app, DAO_ADDR, expiry).unwrap();
+    let err: ContractError = register(&mut app, DAO_ADDR, expiry)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::AirdropOpen {});
+
+    // Only the amount of the sender's own leaf can be claimed, once
+    let err: ContractError = claim_airdrop(&mut app, ADDR1, 0, 101, ADDR1_PROOF)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidProof {});
+    let err: ContractError = claim_airdrop(&mut app, ADDR2, 0, 100, ADDR1_PROOF)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidProof {});
+    claim_airdrop(&mut app, ADDR1, 0, 100, ADDR1_PROOF).unwrap();
+    assert_eq!(get_balance(&app, ADDR1, REWARD_DENOM), Uint128::new(1100));
+    assert!(claimed(&app, 0));
+    assert!(!claimed(&app, 1));
+    let err: ContractError = claim_airdrop(&mut app, ADDR1, 0, 100, ADDR1_PROOF)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::AirdropClaimed {});
+
+    // Distributing the airdrop denom leaves the unclaimed tokens in escrow
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: staking_addr.to_string(),
+        amount: coins(500, REWARD_DENOM),
+    }))
+    .unwrap();
+    app.execute_contract(
+        Addr::unchecked(ADDR1),
+        staking_addr.clone(),
+        &ExecuteMsg::DistributeAllTokens {},
+        &[],
+    )
+    .unwrap();
+    let reward_contracts: AllRewardContractsResponse = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::AllRewardContracts {})
+        .unwrap();
+    assert_eq!(
+        get_balance(&app, &reward_contracts.reward_contracts[0], REWARD_DENOM),
+        Uint128::new(500)
+    );
+    assert_eq!(
+        get_balance(&app, staking_addr.as_str(), REWARD_DENOM),
+        Uint128::new(900)
+    );
+
+    claim_airdrop(&mut app, ADDR2, 1, 250, ADDR2_PROOF).unwrap();
+    assert_eq!(get_balance(&app, ADDR2, REWARD_DENOM), Uint128::new(250));
+
+    let err: ContractError = reclaim(&mut app).unwrap_err().downcast().unwrap();
+    assert_eq!(err, ContractError::AirdropNotExpired {});
+
+    // Once expired, the unclaimed tokens go back to the owner, even when the
+    // manager reclaims them
+    app.update_block(|b| b.height += 100);
+    let err: ContractError = claim_airdrop(&mut app, ADDR2, 1, 250, ADDR2_PROOF)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::AirdropExpired {});
+    reclaim(&mut app).unwrap();
+    assert_eq!(
+        get_balance(&app, DAO_ADDR, REWARD_DENOM),
+        Uint128::new(1650)
+    );
+    assert_eq!(get_balance(&app, ADDR2, REWARD_DENOM), Uint128::new(250));
+    let airdrop: AirdropResponse = app
+        .wrap()
+        .query_wasm_smart(&staking_addr, &QueryMsg::Airdrop {})
+        .unwrap();
+    assert_eq!(airdrop.airdrop, None);
+
+    // A new airdrop starts with nothing claimed
+    let expiry = Expiration::AtHeight(app.block_info().height + 100);
+    register(&mut app, DAO_ADDR, expiry).unwrap();
+    assert!(!claimed(&app, 0));
+}
+
+#[test]
+fn test_score_allocation() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let start = env.block.time.seconds();
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(DAO_ADDR, &[]),
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: Some(DAO_ADDR.to_string()),
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id: 0,
+            deposit_limits: None,
+            allocate_by_score: Some(true),
+            reward_vesting: None,
+        },
+    )
+    .unwrap();
+    let execute = |deps: DepsMut, env: &Env, sender: &str, funds: &[Coin], msg: ExecuteMsg| {
+        crate::contract::execute(deps, env.clone(), mock_info(sender, funds), msg)
+    };
+    let score = |deps: Deps, env: &Env, address: &str| -> ScoreResponse {
+        from_binary(
+            &crate::contract::query(
+                deps,
+                env.clone(),
+                QueryMsg::Score {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    // Scores integrate the staked balance over time
+    let stake = ExecuteMsg::Stake { recipient: None };
+    execute(
+        deps.as_mut(),
+        &env,
+        ADDR1,
+        &coins(100, DENOM),
+        stake.clone(),
+    )
+    .unwrap();
+    env.block.time = env.block.time.plus_seconds(100);
+    execute(deps.as_mut(), &env, ADDR2, &coins(100, DENOM), stake).unwrap();
+    env.block.time = env.block.time.plus_seconds(100);
+    let unstake = ExecuteMsg::Unstake {
+        amount: Uint128::new(50),
+    };
+    execute(deps.as_mut(), &env, ADDR1, &[], unstake).unwrap();
+    env.block.time = env.block.time.plus_seconds(100);
+    assert_eq!(
+        score(deps.as_ref(), &env, ADDR1),
+        ScoreResponse {
+            score: Uint128::new(25000),
+            total_score: Uint128::new(45000),
+            end: None,
+        }
+    );
+    assert_eq!(score(deps.as_ref(), &env, ADDR2).score, Uint128::new(20000));
+
+    // Nothing is allocated until seeding closes the scores
+    let distribute = ExecuteMsg::DistributeToken {
+        denom: REWARD_DENOM.to_string(),
+    };
+    let err = execute(deps.as_mut(), &env, DAO_ADDR, &[], distribute.clone()).unwrap_err();
+    assert_eq!(err, ContractError::ScoringOpen {});
+    staking_core::score::close_scores(deps.as_mut().storage, &env).unwrap();
+    env.block.time = env.block.time.plus_seconds(100);
+    assert_eq!(
+        score(deps.as_ref(), &env, ADDR1),
+        ScoreResponse {
+            score: Uint128::new(25000),
+            total_score: Uint128::new(45000),
+            end: Some(start + 300),
+        }
+    );
+
+    deps.querier
+        .update_balance(env.contract.address.as_str(), coins(900, REWARD_DENOM));
+    execute(deps.as_mut(), &env, DAO_ADDR, &[], distribute.clone()).unwrap();
+    let claim = ExecuteMsg::ClaimAllocation {
+        denom: REWARD_DENOM.to_string(),
+    };
+    let res = execute(deps.as_mut(), &env, ADDR1, &[], claim.clone()).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: ADDR1.to_string(),
+            amount: coins(500, REWARD_DENOM),
+        })
+    );
+    let err = execute(deps.as_mut(), &env, ADDR1, &[], claim).unwrap_err();
+    assert_eq!(err, ContractError::Staking(StakingError::NothingToClaim {}));
+
+    // Tokens received later are allocated on top, leaving unclaimed ones be
+    deps.querier
+        .update_balance(env.contract.address.as_str(), coins(490, REWARD_DENOM));
+    execute(deps.as_mut(), &env, DAO_ADDR, &[], distribute).unwrap();
+    let allocation: ScoreAllocationResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::ScoreAllocation {
+                address: ADDR2.to_string(),
+                denom: REWARD_DENOM.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        allocation,
+        ScoreAllocationResponse {
+            allocated: Uint128::new(440),
+            claimed: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn test_concentrated_seed_target() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(DAO_ADDR, &[]),
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: Some(DAO_ADDR.to_string()),
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id: 0,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    )
+    .unwrap();
+    let update = |range: Option<TickRange>| ExecuteMsg::UpdateSeedTarget {
+        denom: "uatom".to_string(),
+        target: Some(SeedTarget::Concentrated {
+            tick_spacing: 100,
+            spread_factor: "2000000000000000".to_string(),
+            range,
+            max_slippage: None,
+        }),
+    };
+    let seed_target = |deps: Deps| -> SeedTarget {
+        let res: SeedTargetResponse = from_binary(
+            &crate::contract::query(
+                deps,
+                mock_env(),
+                QueryMsg::SeedTarget {
+                    denom: "uatom".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.target
+    };
+    assert_eq!(seed_target(deps.as_ref()), SeedTarget::Balancer {});
+
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &[]),
+        update(None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(DAO_ADDR, &[]),
+        update(Some(TickRange {
+            lower_tick: -150,
+            upper_tick: 1000,
+        })),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidSeedTarget {
+            reason: "ticks must be multiples of the tick spacing".to_string()
+        }
+    );
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(DAO_ADDR, &[]),
+        update(None),
+    )
+    .unwrap();
+    assert_eq!(
+        seed_target(deps.as_ref()),
+        SeedTarget::Concentrated {
+            tick_spacing: 100,
+            spread_factor: "2000000000000000".to_string(),
+            range: None,
+            max_slippage: None,
+        }
+    );
+
+    // The spread factor and max slippage have to be below one
+    for (spread_factor, max_slippage, reason) in [
+        (
+            "0.002",
+            None,
+            "spread factor must be a protobuf encoded decimal",
+        ),
+        (
+            "1000000000000000000",
+            None,
+            "spread factor must be below one",
+        ),
+        (
+            "2000000000000000",
+            Some(Decimal::one()),
+            "max slippage must be below one",
+        ),
+    ] {
+        let err = crate::seed::validate_target(&SeedTarget::Concentrated {
+            tick_spacing: 100,
+            spread_factor: spread_factor.to_string(),
+            range: None,
+            max_slippage,
+        })
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidSeedTarget {
+                reason: reason.to_string()
+            }
+        );
+    }
+
+    // The pool creation reply creates a full range position, recorded by its
+    // own reply
+    crate::seed::create_concentrated_pool(
+        deps.as_mut().storage,
+        &env,
+        100,
+        "2000000000000000".to_string(),
+        None,
+        None,
+        Coin::new(1000, "uatom"),
+        Coin::new(500, REWARD_DENOM),
+    )
+    .unwrap();
+    let reply = |id: u64, data: Binary| Reply {
+        id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(data),
+        }),
+    };
+    let res = crate::contract::reply(
+        deps.as_mut(),
+        env.clone(),
+        reply(3, MsgCreateConcentratedPoolResponse { pool_id: 9 }.into()),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, 4);
+    // the position takes at least 99% of each token
+    let create_position: CosmosMsg = MsgCreatePosition {
+        pool_id: 9,
+        sender: env.contract.address.to_string(),
+        lower_tick: crate::seed::MIN_TICK,
+        upper_tick: crate::seed::MAX_TICK,
+        tokens_provided: vec![
+            ProtoCoin {
+                denom: "uatom".to_string(),
+                amount: "1000".to_string(),
+            },
+            ProtoCoin {
+                denom: REWARD_DENOM.to_string(),
+                amount: "500".to_string(),
+            },
+        ],
+        token_min_amount0: "990".to_string(),
+        token_min_amount1: "495".to_string(),
+    }
+    .into();
+    assert_eq!(res.messages[0].msg, create_position);
+    crate::contract::reply(
+        deps.as_mut(),
+        env.clone(),
+        reply(
+            4,
+            MsgCreatePositionResponse {
+                position_id: 1,
+                liquidity_created: "1000".to_string(),
+                ..Default::default()
+            }
+            .into(),
+        ),
+    )
+    .unwrap();
+    let res: SeededPositionsResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SeededPositions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.positions,
+        vec![SeededPosition {
+            position_id: 1,
+            pool_id: 9,
+            range: TickRange {
+                lower_tick: crate::seed::MIN_TICK,
+                upper_tick: crate::seed::MAX_TICK,
+            },
+            liquidity: "1000".to_string(),
+        }]
+    );
+
+    // Stakers withdrawing leave the position in place
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &coins(100, DENOM)),
+        ExecuteMsg::Stake { recipient: None },
+    )
+    .unwrap();
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &[]),
+        ExecuteMsg::Unstake {
+            amount: Uint128::new(1),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let res: SeededPositionsResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SeededPositions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.positions.len(), 1);
+
+    // Withdrawing collects the position's rewards and returns its liquidity
+    // to the contract
+    let withdraw = |deps: DepsMut, sender: &str| {
+        crate::contract::execute(
+            deps,
+            mock_env(),
+            mock_info(sender, &[]),
+            ExecuteMsg::WithdrawSeededPositions {},
+        )
+    };
+    let err = withdraw(deps.as_mut(), ADDR1).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let res = withdraw(deps.as_mut(), DAO_ADDR).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "positions" && attr.value == "1"));
+    let contract = env.contract.address.to_string();
+    let expected: Vec<CosmosMsg> = vec![
+        MsgCollectSpreadRewards {
+            position_ids: vec![1],
+            sender: contract.clone(),
+        }
+        .into(),
+        MsgCollectIncentives {
+            position_ids: vec![1],
+            sender: contract.clone(),
+        }
+        .into(),
+        MsgWithdrawPosition {
+            position_id: 1,
+            sender: contract,
+            liquidity_amount: "1000".to_string(),
+        }
+        .into(),
+    ];
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<_>>(),
+        expected
+    );
+    let res: SeededPositionsResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            env,
+            QueryMsg::SeededPositions {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.positions.is_empty());
+    withdraw(deps.as_mut(), DAO_ADDR).unwrap_err();
+}
+
+#[test]
+fn test_stableswap_seed_target() {
+    let target = |swap_fee: &str, asset_scaling_factor: u64| SeedTarget::Stableswap {
+        swap_fee: swap_fee.to_string(),
+        asset_scaling_factor,
+        seed_scaling_factor: 1,
+    };
+    for (target, reason) in [
+        (
+            target("500000000000000", 0),
+            "scaling factors must be positive",
+        ),
+        (
+            target("0.0005", 1_000_000),
+            "swap fee must be a protobuf encoded decimal",
+        ),
+        (
+            target("1000000000000000000", 1_000_000),
+            "swap fee must be below one",
+        ),
+    ] {
+        assert_eq!(
+            crate::seed::validate_target(&target).unwrap_err(),
+            ContractError::InvalidSeedTarget {
+                reason: reason.to_string()
+            }
+        );
+    }
+    crate::seed::validate_target(&target("500000000000000", 1_000_000)).unwrap();
+
+    // The liquidity is sorted by denom, and the scaling factors with it
+    let env = mock_env();
+    let deps = mock_dependencies();
+    let msg = crate::seed::create_stableswap_pool(
+        deps.as_ref().storage,
+        &env,
+        "500000000000000".to_string(),
+        1_000_000,
+        1,
+        Coin::new(1000, "uusdc"),
+        Coin::new(500, REWARD_DENOM),
+    )
+    .unwrap();
+    let expected: CosmosMsg = MsgCreateStableswapPool {
+        sender: env.contract.address.to_string(),
+        pool_params: Some(StableswapPoolParams {
+            swap_fee: "500000000000000".to_string(),
+            exit_fee: "0".to_string(),
+        }),
+        initial_pool_liquidity: vec![
+            ProtoCoin {
+                denom: REWARD_DENOM.to_string(),
+                amount: "500".to_string(),
+            },
+            ProtoCoin {
+                denom: "uusdc".to_string(),
+                amount: "1000".to_string(),
+            },
+        ],
+        scaling_factors: vec![1, 1_000_000],
+        future_pool_governor: "24h".to_string(),
+        scaling_factor_controller: String::new(),
+    }
+    .into();
+    assert_eq!(msg.msg, expected);
+}
+
+#[test]
+fn test_seed_locks() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(DAO_ADDR, &[]),
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: Some(DAO_ADDR.to_string()),
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id: 0,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    )
+    .unwrap();
+    let execute = |deps: DepsMut, sender: &str, msg: ExecuteMsg| {
+        crate::contract::execute(deps, mock_env(), mock_info(sender, &[]), msg)
+    };
+    let update = ExecuteMsg::UpdateSeedLock {
+        duration: Some(1209600),
+    };
+    let err = execute(deps.as_mut(), ADDR1, update.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Pools are created without a reply until shares are locked
+    let create: CosmosMsg = BankMsg::Send {
+        to_address: ADDR1.to_string(),
+        amount: coins(1, DENOM),
+    }
+    .into();
+    let msg = crate::lock::create_pool_submsg(deps.as_ref().storage, create.clone(), 5).unwrap();
+    assert_eq!(msg.reply_on, ReplyOn::Never);
+    execute(deps.as_mut(), DAO_ADDR, update).unwrap();
+    let msg = crate::lock::create_pool_submsg(deps.as_ref().storage, create, 5).unwrap();
+    assert_eq!(msg.reply_on, ReplyOn::Success);
+
+    // The pool creation reply locks the new shares, recorded by the lock reply
+    deps.querier
+        .update_balance(env.contract.address.as_str(), coins(100, "gamm/pool/9"));
+    let reply = |id: u64, data: Binary| Reply {
+        id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(data),
+        }),
+    };
+    let res = crate::contract::reply(
+        deps.as_mut(),
+        env.clone(),
+        reply(5, MsgCreateBalancerPoolResponse { pool_id: 9 }.into()),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, 7);
+    crate::contract::reply(
+        deps.as_mut(),
+        env.clone(),
+        reply(7, MsgLockTokensResponse { id: 3 }.into()),
+    )
+    .unwrap();
+    let locks = |deps: Deps| -> SeedLocksResponse {
+        from_binary(
+            &crate::contract::query(
+                deps,
+                mock_env(),
+                QueryMsg::SeedLocks {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    let mut lock = SeedLock {
+        lock_id: 3,
+        pool_id: 9,
+        shares: Uint128::new(100),
+        duration: 1209600,
+        unlocks_at: None,
+    };
+    assert_eq!(
+        locks(deps.as_ref()),
+        SeedLocksResponse {
+            duration: Some(1209600),
+            locks: vec![lock.clone()],
+        }
+    );
+
+    // Stakers withdrawing, even in full, leave the locks earning incentives
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &coins(100, DENOM)),
+        ExecuteMsg::Stake { recipient: None },
+    )
+    .unwrap();
+    let begin_unlocking: CosmosMsg = MsgBeginUnlocking {
+        owner: env.contract.address.to_string(),
+        id: 3,
+        coins: vec![],
+    }
+    .into();
+    for amount in [1, 99] {
+        let res = execute(
+            deps.as_mut(),
+            ADDR1,
+            ExecuteMsg::Unstake {
+                amount: Uint128::new(amount),
+            },
+        )
+        .unwrap();
+        assert!(!res.messages.iter().any(|msg| msg.msg == begin_unlocking));
+        assert_eq!(locks(deps.as_ref()).locks, vec![lock.clone()]);
+    }
+
+    // Only the owner or manager begins unbonding, once per lock
+    let err = execute(deps.as_mut(), ADDR1, ExecuteMsg::UnlockSeedShares {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let res = execute(deps.as_mut(), DAO_ADDR, ExecuteMsg::UnlockSeedShares {}).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].msg, begin_unlocking);
+    let res = execute(deps.as_mut(), DAO_ADDR, ExecuteMsg::UnlockSeedShares {}).unwrap();
+    assert!(res.messages.is_empty());
+    lock.unlocks_at = Some(env.block.time.plus_seconds(1209600));
+    assert_eq!(locks(deps.as_ref()).locks, vec![lock]);
+
+    // The lock is removed once its shares are back to be distributed
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(1209599);
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &[]),
+        ExecuteMsg::DistributeAllTokens {},
+    )
+    .unwrap();
+    assert_eq!(locks(deps.as_ref()).locks.len(), 1);
+    env.block.time = env.block.time.plus_seconds(1);
+    crate::contract::execute(
+        deps.as_mut(),
+        env,
+        mock_info(ADDR1, &[]),
+        ExecuteMsg::DistributeAllTokens {},
+    )
+    .unwrap();
+    assert!(locks(deps.as_ref()).locks.is_empty());
+}
+
+/// Answers the pool liquidity query, which `MockQuerier` can not.
+struct PoolQuerier {
+    base: MockQuerier,
+    liquidity: Vec<ProtoCoin>,
+}
+
+impl Querier for PoolQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        match from_slice::<QueryRequest<Empty>>(bin_request) {
+            Ok(QueryRequest::Stargate { .. }) => SystemResult::Ok(ContractResult::Ok(
+                to_binary(&QueryTotalPoolLiquidityResponse {
+                    liquidity: self.liquidity.clone(),
+                })
+                .unwrap(),
+            )),
+            _ => self.base.raw_query(bin_request),
+        }
+    }
+}
+
+#[test]
+fn test_compound_rewards() {
+    let env = mock_env();
+    let mut base = MockQuerier::default();
+    let height = env.block.height;
+    // Every reward contract has 100 pending
+    base.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } => SystemResult::Ok(ContractResult::Ok(
+            to_binary(&lockdrop_rewards::msg::PendingRewardsResponse {
+                address: ADDR1.to_string(),
+                pending_rewards: Uint128::new(100),
+                denom: Denom::Native(contract_addr.trim_start_matches("rewards_").to_string()),
+                last_update_block: height,
+            })
+            .unwrap(),
+        )),
+        _ => unimplemented!(),
+    });
+    let liquidity = ["uosmo", "uatom"]
+        .iter()
+        .map(|denom| ProtoCoin {
+            denom: denom.to_string(),
+            amount: "1000".to_string(),
+        })
+        .collect();
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: PoolQuerier { base, liquidity },
+        custom_query_type: PhantomData,
+    };
+    crate::contract::instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(DAO_ADDR, &[]),
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: None,
+            denom: "gamm/pool/1".to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id: 0,
+            deposit_limits: Some(DepositLimits {
+                max_deposit: Some(Uint128::new(100)),
+                min_stake: Some(Uint128::new(200)),
+                ..DepositLimits::default()
+            }),
+            allocate_by_score: None,
+            reward_vesting: None,
+        },
+    )
+    .unwrap();
+    for denom in ["uion", "ujuno", "uosmo"] {
+        REWARD_CONTRACTS_BY_DENOM
+            .save(
+                deps.as_mut().storage,
+                denom,
+                &Addr::unchecked(format!("rewards_{}", denom)),
+            )
+            .unwrap();
+    }
+
+    // Routes are set by the owner and must end in an asset of the pool
+    let update_route = |denom: &str, token_out_denom: &str| ExecuteMsg::UpdateCompoundRoute {
+        denom: denom.to_string(),
+        route: vec![SwapHop {
+            pool_id: 2,
+            token_out_denom: token_out_denom.to_string(),
+        }],
+    };
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &[]),
+        update_route("uion", "uatom"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(DAO_ADDR, &[]),
+        update_route("uion", "ujuno"),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidCompoundRoute { .. }));
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(DAO_ADDR, &[]),
+        update_route("uosmo", "uatom"),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidCompoundRoute { .. }));
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(DAO_ADDR, &[]),
+        update_route("uion", "uatom"),
+    )
+    .unwrap();
+    let res: CompoundRouteResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::CompoundRoute {
+                denom: "uion".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.route.len(), 1);
+
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &[]),
+        ExecuteMsg::SetCompounding { enabled: true },
+    )
+    .unwrap();
+    let claim_rewards = |min_shares_out: Option<u128>| ExecuteMsg::ClaimRewards {
+        denoms: None,
+        min_shares_out: min_shares_out.map(Uint128::new),
+    };
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &[]),
+        claim_rewards(None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MinSharesOutRequired {});
+
+    // uion is swapped into uatom, ujuno has no route and is paid out, uosmo
+    // is joined directly
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &[]),
+        claim_rewards(Some(50)),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 5);
+    let swap: CosmosMsg = MsgSwapExactAmountIn {
+        sender: env.contract.address.to_string(),
+        routes: vec![SwapAmountInRoute {
+            pool_id: 2,
+            token_out_denom: "uatom".to_string(),
+        }],
+        token_in: Some(ProtoCoin {
+            denom: "uion".to_string(),
+            amount: "100".to_string(),
+        }),
+        token_out_min_amount: "1".to_string(),
+    }
+    .into();
+    assert_eq!(res.messages[1].msg, swap);
+    assert_eq!(res.messages[1].id, 8);
+    assert_eq!(
+        res.messages[2].msg,
+        CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute {
+            contract_addr: "rewards_ujuno".to_string(),
+            funds: vec![],
+            msg: to_binary(&lockdrop_rewards::msg::ExecuteMsg::ClaimFor {
+                address: ADDR1.to_string(),
+                recipient: None,
+            })
+            .unwrap(),
+        })
+    );
+    assert_eq!(res.messages[4].id, 2);
+
+    let reply = |id: u64, data: Binary| Reply {
+        id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(data),
+        }),
+    };
+    let res = crate::contract::reply(
+        deps.as_mut(),
+        env.clone(),
+        reply(
+            8,
+            MsgSwapExactAmountInResponse {
+                token_out_amount: "90".to_string(),
+            }
+            .into(),
+        ),
+    )
+    .unwrap();
+    let join: CosmosMsg = MsgJoinSwapExternAmountIn {
+        sender: env.contract.address.to_string(),
+        pool_id: 1,
+        token_in: Some(ProtoCoin {
+            denom: "uatom".to_string(),
+            amount: "90".to_string(),
+        }),
+        share_out_min_amount: "1".to_string(),
+    }
+    .into();
+    assert_eq!(res.messages[0].msg, join);
+
+    // The joined shares are staked once the last join replies, below the
+    // min stake and without counting towards the max deposit
+    let join_reply = |shares: &str| {
+        reply(
+            2,
+            MsgJoinSwapExternAmountInResponse {
+                share_out_amount: shares.to_string(),
+            }
+            .into(),
+        )
+    };
+    let mut next = env.clone();
+    next.block.height += 1;
+    let staked = |deps: Deps| -> Uint128 {
+        let res: StakedBalanceAtHeightResponse = from_binary(
+            &crate::contract::query(
+                deps,
+                next.clone(),
+                QueryMsg::StakedBalanceAtHeight {
+                    address: ADDR1.to_string(),
+                    height: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.balance
+    };
+    let res = crate::contract::reply(deps.as_mut(), env.clone(), join_reply("30")).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(staked(deps.as_ref()).is_zero());
+    crate::contract::reply(deps.as_mut(), env.clone(), join_reply("40")).unwrap();
+    assert_eq!(staked(deps.as_ref()), Uint128::new(70));
+    assert!(PENDING_COMPOUND
+        .may_load(deps.as_ref().storage)
+        .unwrap()
+        .is_none());
+    let res: DepositResponse = from_binary(
+        &crate::contract::query(
+            deps.as_ref(),
+            next.clone(),
+            QueryMsg::Deposit {
+                address: ADDR1.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.deposit.is_zero());
+
+    // The minimum applies to the shares of all joins together
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &[]),
+        claim_rewards(Some(100)),
+    )
+    .unwrap();
+    crate::contract::reply(deps.as_mut(), env.clone(), join_reply("60")).unwrap();
+    let err = crate::contract::reply(deps.as_mut(), env, join_reply("30")).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MinSharesOut {
+            min: Uint128::new(100)
+        }
+    );
+}
+
+#[test]
+fn test_compounding_vested_rewards() {
+    let mut deps = mock_dependencies();
+    crate::contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(DAO_ADDR, &[]),
+        InstantiateMsg {
+            owner: Some(DAO_ADDR.to_string()),
+            manager: None,
+            denom: DENOM.to_string(),
+            unstaking_duration: None,
+            reward_contract_code_id: 0,
+            deposit_limits: None,
+            allocate_by_score: None,
+            reward_vesting: Some(VestingConfig {
+                duration: 100,
+                cliff: 0,
+            }),
+        },
+    )
+    .unwrap();
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADDR1, &[]),
+        ExecuteMsg::SetCompounding { enabled: true },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::CompoundingVested {});
+}
@@ -1,10 +1,10 @@
-use std::vec;
+use std::str::FromStr;
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coins, to_binary, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Reply,
-    Response, StdError, StdResult, Uint128, WasmMsg,
+    coins, to_binary, BankMsg, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Reply,
+    Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
 use cw_utils::must_pay;
@@ -12,50 +12,78 @@ use osmosis_std::types::{
     cosmos::base::v1beta1::Coin,
     osmosis::gamm::{
         poolmodels::balancer::v1beta1::{MsgCreateBalancerPool, MsgCreateBalancerPoolResponse},
-        v1beta1::{
-            GammQuerier, MsgExitPool, MsgExitPoolResponse, Pool, PoolAsset, PoolParams,
-            QueryPoolResponse, QueryTotalPoolLiquidityResponse,
-        },
+        v1beta1::{GammQuerier, MsgExitPool, MsgExitPoolResponse, PoolAsset, PoolParams},
     },
 };
 
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::{error::ContractError, msg};
+use crate::error::ContractError;
+use crate::msg::{
+    EjectedResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SeededPoolsResponse,
+};
+use crate::state::{Config, CONFIG, EJECTED, SEEDED_POOLS};
+use crate::transform;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:osmo-lp-transfomer";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const EXIT_POOL_REPLY_ID: u64 = 1;
+const CREATE_POOL_REPLY_ID: u64 = 2;
+pub(crate) const TRANSFORM_EXIT_REPLY_ID: u64 = 3;
+pub(crate) const TRANSFORM_SWAP_REPLY_ID: u64 = 4;
+pub(crate) const TRANSFORM_JOIN_REPLY_ID: u64 = 5;
+
+const DEFAULT_FUTURE_POOL_GOVERNOR: &str = "24h";
+
+/// Shortfall allowed on the pro rata tokens out of an exit, in percent.
+const EXIT_SLIPPAGE_PERCENT: u128 = 1;
+
 /// Handling contract instantiation
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    // With `Response` type, it is possible to dispatch message to invoke external logic.
-    // See: https://github.com/CosmWasm/cosmwasm/blob/main/SEMANTICS.md#dispatching-messages
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+    let config = Config {
+        owner,
+        pool_id: msg.pool_id,
+        seed_denom: msg.seed_denom,
+        swap_fee: pool_fee(msg.swap_fee.unwrap_or_else(|| Decimal::permille(3)))?,
+        exit_fee: pool_fee(msg.exit_fee.unwrap_or_default())?,
+        future_pool_governor: msg
+            .future_pool_governor
+            .unwrap_or_else(|| DEFAULT_FUTURE_POOL_GOVERNOR.to_string()),
+    };
+    CONFIG.save(deps.storage, &config)?;
+    SEEDED_POOLS.save(deps.storage, &vec![])?;
+
     Ok(Response::new()
         .add_attribute("method", "instantiate")
-        .add_attribute("owner", info.sender))
+        .add_attribute("owner", config.owner)
+        .add_attribute("pool_id", config.pool_id.to_string()))
+}
+
+/// Encodes `fee` as a protobuf decimal, the integer of its 18 decimals.
+fn pool_fee(fee: Decimal) -> Result<String, ContractError> {
+    if fee >= Decimal::one() {
+        return Err(ContractError::InvalidFee {});
+    }
+    Ok(fee.atomics().to_string())
 }
 
 /// Handling contract migration
-/// To make a contract migratable, you need
-/// - this entry_point implemented
-/// - only contract admin can migrate, so admin has to be set at contract initiation time
-/// Handling contract execution
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
-    match msg {
-        // Find matched incoming message variant and execute them with your custom logic.
-        //
-        // With `Response` type, it is possible to dispatch message to invoke external logic.
-        // See: https://github.com/CosmWasm/cosmwasm/blob/main/SEMANTICS.md#dispatching-messages
-    }
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new().add_attribute("method", "migrate"))
 }
 
 /// Handling contract execution
@@ -70,33 +98,91 @@ pub fn execute(
         ExecuteMsg::EjectAndSeedLiquidity {} => execute_eject_and_seed_liquidity(deps, env, info),
         ExecuteMsg::EjectLiquidity {} => _execute_eject(deps, env, info),
         ExecuteMsg::SeedLiquidity {} => _seed_liquidity(deps, env, info),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            pool_id,
+            seed_denom,
+        } => execute_update_config(deps, info, owner, pool_id, seed_denom),
+        ExecuteMsg::Transform {
+            target_pool_id,
+            min_shares_out,
+        } => transform::execute_transform(deps, env, info, target_pool_id, min_shares_out),
     }
 }
 
 /// Handling contract query
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(_deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        // Find matched incoming message variant and query them your custom logic
-        // and then construct your query response with the type usually defined
-        // `msg.rs` alongside with the query message itself.
-        //
-        // use `cosmwasm_std::to_binary` to serialize query response to json binary.
+        QueryMsg::Config {} => to_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::Ejected {} => to_binary(&EjectedResponse {
+            ejected: EJECTED.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::SeededPools {} => to_binary(&SeededPoolsResponse {
+            pool_ids: SEEDED_POOLS.load(deps.storage)?,
+        }),
     }
 }
 
 /// Handling submessage reply.
 /// For more info on submessage and reply, see https://github.com/CosmWasm/cosmwasm/blob/main/SEMANTICS.md#submessages
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(_deps: DepsMut, _env: Env, _msg: Reply) -> Result<Response, ContractError> {
-    // With `Response` type, it is still possible to dispatch message to invoke external logic.
-    // See: https://github.com/CosmWasm/cosmwasm/blob/main/SEMANTICS.md#dispatching-messages
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        EXIT_POOL_REPLY_ID => reply_exit_pool(deps, msg),
+        CREATE_POOL_REPLY_ID => reply_create_pool(deps, env, msg),
+        TRANSFORM_EXIT_REPLY_ID => transform::reply_exit(deps, env, msg),
+        TRANSFORM_SWAP_REPLY_ID => transform::reply_swap(deps, env, msg),
+        TRANSFORM_JOIN_REPLY_ID => transform::reply_join(deps, msg),
+        id => Err(ContractError::Std(StdError::generic_err(format!(
+            "unknown reply id: {}",
+            id
+        )))),
+    }
+}
 
-    todo!()
+fn reply_exit_pool(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let res: MsgExitPoolResponse = msg.result.try_into()?;
+    let ejected = res
+        .token_out
+        .into_iter()
+        .map(|coin| -> StdResult<cosmwasm_std::Coin> {
+            Ok(cosmwasm_std::Coin {
+                denom: coin.denom,
+                amount: Uint128::from_str(&coin.amount)?,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    EJECTED.save(deps.storage, &ejected)?;
+    Ok(Response::new().add_attribute("action", "reply_exit_pool"))
+}
+
+// The seeded pool's shares are minted to the contract, pass them on to the owner
+fn reply_create_pool(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let res: MsgCreateBalancerPoolResponse = msg.result.try_into()?;
+    let config = CONFIG.load(deps.storage)?;
+    SEEDED_POOLS.update(deps.storage, |mut pool_ids| -> StdResult<_> {
+        pool_ids.push(res.pool_id);
+        Ok(pool_ids)
+    })?;
+    let shares = deps
+        .querier
+        .query_balance(&env.contract.address, pool_denom(res.pool_id))?;
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: config.owner.to_string(),
+            amount: vec![shares],
+        })
+        .add_attribute("action", "reply_create_pool")
+        .add_attribute("pool_id", res.pool_id.to_string()))
+}
+
+pub fn pool_denom(pool_id: u64) -> String {
+    format!("gamm/pool/{}", pool_id)
 }
 
 pub fn extract_pool_id_from_denom(denom: &str) -> Result<u64, ContractError> {
-    let split: Vec<&str> = denom.split("/").collect();
+    let split: Vec<&str> = denom.split('/').collect();
     if split.len() != 3 {
         return Err(ContractError::Std(StdError::generic_err(
             "invalid pool denom",
@@ -108,11 +194,44 @@ pub fn extract_pool_id_from_denom(denom: &str) -> Result<u64, ContractError> {
     Ok(pool_id)
 }
 
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    pool_id: Option<u64>,
+    seed_denom: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_validate(&owner)?;
+    }
+    if let Some(pool_id) = pool_id {
+        config.pool_id = pool_id;
+    }
+    if let Some(seed_denom) = seed_denom {
+        config.seed_denom = seed_denom;
+    }
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("owner", config.owner)
+        .add_attribute("pool_id", config.pool_id.to_string())
+        .add_attribute("seed_denom", config.seed_denom))
+}
+
 pub fn execute_eject_and_seed_liquidity(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // only owner can execute this
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
     // create wasm execute message for ejecting liquidity
     let eject_msg = ExecuteMsg::EjectLiquidity {};
     let eject_msg = to_binary(&eject_msg)?;
@@ -121,11 +240,12 @@ pub fn execute_eject_and_seed_liquidity(
         funds: vec![],
         msg: eject_msg,
     });
+    // the seed tokens are passed along to seeding
     let seed_msg = ExecuteMsg::SeedLiquidity {};
     let seed_msg = to_binary(&seed_msg)?;
     let seed_msg = CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: env.contract.address.to_string(),
-        funds: vec![],
+        funds: info.funds,
         msg: seed_msg,
     });
     Ok(Response::new()
@@ -143,61 +263,61 @@ pub fn _seed_liquidity(
     if info.sender != env.contract.address {
         return Err(ContractError::Unauthorized {});
     }
-    let seed_denom = "uosmo";
-    let denom = "gamm/poolexample/5";
-    let seed_amount = must_pay(&info, &seed_denom).map_err(|e| ContractError::PaymentError(e))?;
-    let pool_id = extract_pool_id_from_denom(denom)?;
-    let gamm_querier = GammQuerier::new(&deps.querier);
-    let pool: QueryTotalPoolLiquidityResponse = gamm_querier.total_pool_liquidity(pool_id)?;
-    let asset_count = pool.liquidity.len();
-    let seed_amount_per_pool = seed_amount / Uint128::from(asset_count as u128);
-    let seed_amount_remainder =
-        seed_amount - (seed_amount_per_pool * Uint128::from(asset_count as u128));
-    let msgs = pool
-        .liquidity
-        .iter()
-        .map(|coin| -> Result<CosmosMsg, StdError> {
-            let balance = deps
-                .querier
-                .query_balance(&env.contract.address, &coin.denom)?;
-            let msg_create_balancer_pool: CosmosMsg = MsgCreateBalancerPool {
+    let config = CONFIG.load(deps.storage)?;
+    let ejected = EJECTED.may_load(deps.storage)?.unwrap_or_default();
+    if ejected.is_empty() {
+        return Err(ContractError::NothingEjected {});
+    }
+    EJECTED.remove(deps.storage);
+    let seed_amount = must_pay(&info, &config.seed_denom)?;
+    let asset_count = Uint128::from(ejected.len() as u128);
+    let seed_amount_per_pool = seed_amount / asset_count;
+    let seed_amount_remainder = seed_amount - seed_amount_per_pool * asset_count;
+
+    // pool creation fees are paid from the contract's balance, apart from
+    // the ejected tokens
+    let msgs = ejected
+        .into_iter()
+        .map(|coin| {
+            let msg_create_balancer_pool = MsgCreateBalancerPool {
                 sender: env.contract.address.to_string(),
-                future_pool_governor: "24h".to_string(),
+                future_pool_governor: config.future_pool_governor.clone(),
                 pool_params: Some(PoolParams {
-                    swap_fee: "0.003000000000000000".to_string(),
-                    exit_fee: "0.000000000000000000".to_string(),
+                    swap_fee: config.swap_fee.clone(),
+                    exit_fee: config.exit_fee.clone(),
                     smooth_weight_change_params: None,
                 }),
                 pool_assets: vec![
                     PoolAsset {
                         token: Some(Coin {
-                            denom: balance.denom,
-                            amount: balance.amount.to_string(),
+                            denom: coin.denom,
+                            amount: coin.amount.to_string(),
                         }),
-                        weight: "536870912000000".to_string(),
+                        weight: "100".to_string(),
                     },
                     PoolAsset {
                         token: Some(Coin {
-                            denom: seed_denom.to_string(),
+                            denom: config.seed_denom.clone(),
                             amount: seed_amount_per_pool.to_string(),
                         }),
-                        weight: "536870912000000".to_string(),
+                        weight: "100".to_string(),
                     },
                 ],
-            }
-            .into();
-            Ok(msg_create_balancer_pool)
+            };
+            SubMsg::reply_on_success(msg_create_balancer_pool, CREATE_POOL_REPLY_ID)
         })
-        .map(|msg| msg)
-        .collect::<Result<Vec<_>, _>>()?;
-    let bank_transfer_remainder: CosmosMsg<Empty> = CosmosMsg::Bank(BankMsg::Send {
-        to_address: info.sender.to_string(),
-        amount: coins(seed_amount_remainder.u128(), seed_denom),
-    });
-    Ok(Response::new()
-        .add_attribute("action", "execute_eject_and_seed_liquidity")
-        .add_messages(msgs)
-        .add_message(bank_transfer_remainder))
+        .collect::<Vec<_>>();
+
+    let mut res = Response::new()
+        .add_attribute("action", "execute_seed_liquidity")
+        .add_submessages(msgs);
+    if !seed_amount_remainder.is_zero() {
+        res = res.add_message(BankMsg::Send {
+            to_address: config.owner.to_string(),
+            amount: coins(seed_amount_remainder.u128(), config.seed_denom),
+        });
+    }
+    Ok(res)
 }
 
 pub fn _execute_eject(
@@ -209,14 +329,53 @@ pub fn _execute_eject(
     if info.sender != env.contract.address {
         return Err(ContractError::Unauthorized {});
     }
-    let denom = "gamm/poolexample/5";
-    let balance = deps.querier.query_balance(&env.contract.address, denom)?;
-    let msg_exit_pool: CosmosMsg = MsgExitPool {
-        sender: env.contract.address.to_string(),
-        pool_id: 1,
-        share_in_amount: balance.amount.to_string(),
-        token_out_mins: vec![],
+    let config = CONFIG.load(deps.storage)?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, pool_denom(config.pool_id))?;
+    if balance.amount.is_zero() {
+        return Err(ContractError::NoLiquidity {});
     }
-    .into();
-    Ok(Response::new().add_message(msg_exit_pool))
+    let msg_exit_pool = exit_pool_msg(deps.as_ref(), &env, config.pool_id, balance.amount)?;
+    Ok(Response::new()
+        .add_attribute("action", "execute_eject")
+        .add_attribute("amount", balance.amount)
+        .add_submessage(SubMsg::reply_on_success(msg_exit_pool, EXIT_POOL_REPLY_ID)))
+}
+
+/// Exits `pool_id` with `shares`. Exiting is pro rata, but Osmosis truncates
+/// the share ratio and takes the pool's exit fee, so up to
+/// `EXIT_SLIPPAGE_PERCENT` less than that share of each asset is accepted.
+pub(crate) fn exit_pool_msg(
+    deps: Deps,
+    env: &Env,
+    pool_id: u64,
+    shares: Uint128,
+) -> StdResult<MsgExitPool> {
+    let gamm_querier = GammQuerier::new(&deps.querier);
+    let pool_liquidity = gamm_querier.total_pool_liquidity(pool_id)?;
+    let total_shares = gamm_querier
+        .total_shares(pool_id)?
+        .total_shares
+        .ok_or_else(|| StdError::generic_err("failed to load total shares"))?;
+    let total_shares = Uint128::from_str(&total_shares.amount)?;
+    let token_out_mins = pool_liquidity
+        .liquidity
+        .into_iter()
+        .map(|coin| -> StdResult<Coin> {
+            let token_out_min = Uint128::from_str(&coin.amount)?
+                .multiply_ratio(shares, total_shares)
+                .multiply_ratio(100 - EXIT_SLIPPAGE_PERCENT, 100u128);
+            Ok(Coin {
+                denom: coin.denom,
+                amount: token_out_min.to_string(),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(MsgExitPool {
+        sender: env.contract.address.to_string(),
+        pool_id,
+        share_in_amount: shares.to_string(),
+        token_out_mins,
+    })
 }
@@ -1,8 +1,8 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use cw_utils::PaymentError;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
@@ -13,8 +13,24 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
-    #[error("Custom Error val: {val:?}")]
-    CustomError { val: String },
-    // Add any other custom errors you like here.
-    // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+    #[error("Pool fees must be less than 1")]
+    InvalidFee {},
+
+    #[error("No LP shares to eject")]
+    NoLiquidity {},
+
+    #[error("No ejected liquidity to seed")]
+    NothingEjected {},
+
+    #[error("Cannot transform shares into the same pool")]
+    SamePool {},
+
+    #[error("Pools have no asset in common")]
+    NoCommonAsset {},
+
+    #[error("Exiting the source pool returned no assets")]
+    EmptyExit {},
+
+    #[error("Less than {min} shares out")]
+    MinSharesOut { min: Uint128 },
 }
@@ -0,0 +1,110 @@
+use cosmwasm_std::{Coin, Uint128};
+use osmosis_std::types::cosmos::bank::v1beta1::QueryBalanceRequest;
+use osmosis_testing::{Account, Bank, Gamm, Module, OsmosisTestApp, Wasm};
+
+use crate::msg::{ExecuteMsg, InstantiateMsg};
+
+#[test]
+#[ignore = "needs artifacts/osmo_lp_transfomer.wasm"]
+fn transform() {
+    let app = OsmosisTestApp::default();
+    let alice = app
+        .init_account(&[
+            Coin::new(1_000_000_000_000, "uatom"),
+            Coin::new(1_000_000_000_000, "uion"),
+            Coin::new(1_000_000_000_000, "uosmo"),
+        ])
+        .unwrap();
+    let gamm = Gamm::new(&app);
+    let wasm = Wasm::new(&app);
+    let bank = Bank::new(&app);
+
+    // Pool 1 holds uatom and uosmo, pool 2 uosmo and uion
+    let source_pool_id = gamm
+        .create_basic_pool(
+            &[Coin::new(1_000_000, "uatom"), Coin::new(1_000_000, "uosmo")],
+            &alice,
+        )
+        .unwrap()
+        .data
+        .pool_id;
+    let target_pool_id = gamm
+        .create_basic_pool(
+            &[Coin::new(1_000_000, "uosmo"), Coin::new(1_000_000, "uion")],
+            &alice,
+        )
+        .unwrap()
+        .data
+        .pool_id;
+
+    let wasm_byte_code = std::fs::read("./artifacts/osmo_lp_transfomer.wasm").unwrap();
+    let code_id = wasm
+        .store_code(&wasm_byte_code, None, &alice)
+        .unwrap()
+        .data
+        .code_id;
+    let contract_addr = wasm
+        .instantiate(
+            code_id,
+            &InstantiateMsg {
+                owner: None,
+                pool_id: source_pool_id,
+                seed_denom: "uion".to_string(),
+                swap_fee: None,
+                exit_fee: None,
+                future_pool_governor: None,
+            },
+            None,
+            None,
+            &[],
+            &alice,
+        )
+        .unwrap()
+        .data
+        .address;
+
+    let balance = |denom: String| -> Uint128 {
+        bank.query_balance(&QueryBalanceRequest {
+            address: alice.address(),
+            denom,
+        })
+        .unwrap()
+        .balance
+        .map(|coin| coin.amount.parse().unwrap())
+        .unwrap_or_default()
+    };
+    let source_denom = format!("gamm/pool/{}", source_pool_id);
+    let target_denom = format!("gamm/pool/{}", target_pool_id);
+    let source_shares = balance(source_denom.clone());
+    let target_shares = balance(target_denom.clone());
+
+    // A tenth of the source pool, exited, its uatom swapped into uosmo and
+    // both joined into the target pool, is worth a little under a tenth of it
+    let shares_in = source_shares.multiply_ratio(1u128, 10u128);
+    let min_shares_out = target_shares.multiply_ratio(1u128, 20u128);
+    let err = wasm
+        .execute(
+            &contract_addr,
+            &ExecuteMsg::Transform {
+                target_pool_id,
+                min_shares_out: target_shares,
+            },
+            &[Coin::new(shares_in.u128(), source_denom.clone())],
+            &alice,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("shares out"));
+    wasm.execute(
+        &contract_addr,
+        &ExecuteMsg::Transform {
+            target_pool_id,
+            min_shares_out,
+        },
+        &[Coin::new(shares_in.u128(), source_denom.clone())],
+        &alice,
+    )
+    .unwrap();
+
+    assert_eq!(balance(source_denom), source_shares - shares_in);
+    assert!(balance(target_denom) >= target_shares + min_shares_out);
+}
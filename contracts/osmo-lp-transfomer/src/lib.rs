@@ -2,5 +2,12 @@ pub mod contract;
 mod error;
 pub mod msg;
 pub mod state;
+pub mod transform;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+mod integration_tests;
 
 pub use crate::error::ContractError;
@@ -1,34 +1,69 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Coin, Decimal, Uint128};
+
+use crate::state::Config;
 
 /// Message type for `instantiate` entry_point
 #[cw_serde]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    // Can update the config and eject and seed liquidity, the sender if unset.
+    pub owner: Option<String>,
+    // Pool whose LP shares are converted.
+    pub pool_id: u64,
+    // Token paired with each asset of the pool in the seeded pools.
+    pub seed_denom: String,
+    // Swap fee of the seeded pools, 0.3% if unset.
+    pub swap_fee: Option<Decimal>,
+    // Exit fee of the seeded pools, none if unset.
+    pub exit_fee: Option<Decimal>,
+    // Future governor of the seeded pools, "24h" if unset.
+    pub future_pool_governor: Option<String>,
+}
 
 /// Message type for `execute` entry_point
 #[cw_serde]
 pub enum ExecuteMsg {
+    // Ejects the contract's LP shares and seeds new pools with the tokens out
+    // and the seed tokens sent along. Owner only.
     EjectAndSeedLiquidity {},
     EjectLiquidity {},
     SeedLiquidity {},
+    UpdateConfig {
+        owner: Option<String>,
+        pool_id: Option<u64>,
+        seed_denom: Option<String>,
+    },
+    // Converts the `gamm/pool/N` shares sent along into shares of
+    // `target_pool_id`, returned to the sender. Open to anyone.
+    Transform {
+        target_pool_id: u64,
+        min_shares_out: Uint128,
+    },
 }
 
 /// Message type for `migrate` entry_point
 #[cw_serde]
-pub enum MigrateMsg {}
+pub struct MigrateMsg {}
 
 /// Message type for `query` entry_point
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    // This example query variant indicates that any client can query the contract
-    // using `YourQuery` and it will return `YourQueryResponse`
-    // This `returns` information will be included in contract's schema
-    // which is used for client code generation.
-    //
-    // #[returns(YourQueryResponse)]
-    // YourQuery {},
+    #[returns(Config)]
+    Config {},
+    // Tokens ejected and not seeded yet.
+    #[returns(EjectedResponse)]
+    Ejected {},
+    #[returns(SeededPoolsResponse)]
+    SeededPools {},
 }
 
-// We define a custom struct for each query response
-// #[cw_serde]
-// pub struct YourQueryResponse {}
+#[cw_serde]
+pub struct EjectedResponse {
+    pub ejected: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct SeededPoolsResponse {
+    pub pool_ids: Vec<u64>,
+}
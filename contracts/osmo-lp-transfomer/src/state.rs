@@ -1,2 +1,41 @@
-// use `cw_storage_plus` to create ORM-like interface to storage
-// see: https://crates.io/crates/cw-storage-plus
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, Uint128};
+use cw_storage_plus::Item;
+
+#[cw_serde]
+pub struct Config {
+    pub owner: Addr,
+    // Pool whose LP shares held by the contract are ejected.
+    pub pool_id: u64,
+    // Token paired with each ejected asset in the seeded pools.
+    pub seed_denom: String,
+    // Parameters of the seeded pools, as protobuf encoded decimals.
+    pub swap_fee: String,
+    pub exit_fee: String,
+    pub future_pool_governor: String,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Tokens out of the last eject, read back by the seeding which follows it.
+pub const EJECTED: Item<Vec<Coin>> = Item::new("ejected");
+
+/// Pools created by seeding, in order of creation.
+pub const SEEDED_POOLS: Item<Vec<u64>> = Item::new("seeded_pools");
+
+/// A `Transform` in progress, read back by its submessage replies.
+#[cw_serde]
+pub struct PendingTransform {
+    pub sender: Addr,
+    pub source_pool_id: u64,
+    pub target_pool_id: u64,
+    pub target_denoms: Vec<String>,
+    // Asset of both pools which the other ejected assets are swapped into.
+    pub swap_denom: String,
+    pub min_shares_out: Uint128,
+    // Joins of the target pool yet to reply.
+    pub joins_left: u64,
+    pub shares_out: Uint128,
+}
+
+pub const TRANSFORM: Item<PendingTransform> = Item::new("transform");
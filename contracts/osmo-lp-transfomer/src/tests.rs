@@ -0,0 +1,441 @@
+use std::marker::PhantomData;
+
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    coins, from_binary, from_slice, to_binary, Addr, BankMsg, ContractResult, CosmosMsg, Decimal,
+    Empty, OwnedDeps, Querier, QuerierResult, QueryRequest, Reply, StdError, SubMsgResponse,
+    SubMsgResult, SystemResult, Uint128,
+};
+use osmosis_std::types::cosmos::base::v1beta1::Coin;
+use osmosis_std::types::osmosis::gamm::poolmodels::balancer::v1beta1::MsgCreateBalancerPoolResponse;
+use osmosis_std::types::osmosis::gamm::v1beta1::{
+    MsgExitPool, MsgExitPoolResponse, MsgJoinSwapExternAmountInResponse,
+    QueryTotalPoolLiquidityResponse, QueryTotalSharesResponse,
+};
+use osmosis_std::types::osmosis::poolmanager::v1beta1::MsgSwapExactAmountInResponse;
+
+use crate::contract::{execute, exit_pool_msg, instantiate, query, reply};
+use crate::msg::{EjectedResponse, ExecuteMsg, InstantiateMsg, QueryMsg, SeededPoolsResponse};
+use crate::state::{Config, PendingTransform, TRANSFORM};
+use crate::ContractError;
+
+const OWNER: &str = "owner";
+const ADDR1: &str = "addr1";
+const SEED_DENOM: &str = "useed";
+
+fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            owner: None,
+            pool_id: 1,
+            seed_denom: SEED_DENOM.to_string(),
+            swap_fee: None,
+            exit_fee: None,
+            future_pool_governor: None,
+        },
+    )
+    .unwrap();
+    deps
+}
+
+fn reply_with(id: u64, data: cosmwasm_std::Binary) -> Reply {
+    Reply {
+        id,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(data),
+        }),
+    }
+}
+
+fn eject(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+    let token_out = vec![
+        Coin {
+            denom: "uosmo".to_string(),
+            amount: "1000".to_string(),
+        },
+        Coin {
+            denom: "uatom".to_string(),
+            amount: "500".to_string(),
+        },
+    ];
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        reply_with(1, MsgExitPoolResponse { token_out }.into()),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_instantiate() {
+    let deps = setup();
+    let config: Config =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        Config {
+            owner: Addr::unchecked(OWNER),
+            pool_id: 1,
+            seed_denom: SEED_DENOM.to_string(),
+            swap_fee: "3000000000000000".to_string(),
+            exit_fee: "0".to_string(),
+            future_pool_governor: "24h".to_string(),
+        }
+    );
+
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        owner: None,
+        pool_id: 1,
+        seed_denom: SEED_DENOM.to_string(),
+        swap_fee: Some(Decimal::percent(1)),
+        exit_fee: Some(Decimal::one()),
+        future_pool_governor: None,
+    };
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFee {});
+    let msg = InstantiateMsg {
+        exit_fee: Some(Decimal::permille(5)),
+        ..msg
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    let config: Config =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.swap_fee, "10000000000000000");
+    assert_eq!(config.exit_fee, "5000000000000000");
+}
+
+#[test]
+fn test_update_config() {
+    let mut deps = setup();
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some(ADDR1.to_string()),
+        pool_id: Some(2),
+        seed_denom: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(ADDR1, &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    let config: Config =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, Addr::unchecked(ADDR1));
+    assert_eq!(config.pool_id, 2);
+    assert_eq!(config.seed_denom, SEED_DENOM);
+}
+
+#[test]
+fn test_eject_and_seed_liquidity() {
+    let mut deps = setup();
+    let env = mock_env();
+    let contract = env.contract.address.as_str();
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &coins(1001, SEED_DENOM)),
+        ExecuteMsg::EjectAndSeedLiquidity {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(OWNER, &coins(1001, SEED_DENOM)),
+        ExecuteMsg::EjectAndSeedLiquidity {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+
+    // Only the contract itself ejects and seeds
+    for msg in [ExecuteMsg::EjectLiquidity {}, ExecuteMsg::SeedLiquidity {}] {
+        let err = execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(contract, &[]),
+        ExecuteMsg::EjectLiquidity {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoLiquidity {});
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(contract, &coins(1001, SEED_DENOM)),
+        ExecuteMsg::SeedLiquidity {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NothingEjected {});
+
+    // The exit reply records the tokens out for seeding
+    eject(&mut deps);
+    let ejected: EjectedResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Ejected {}).unwrap()).unwrap();
+    assert_eq!(
+        ejected.ejected,
+        vec![
+            cosmwasm_std::Coin::new(1000, "uosmo"),
+            cosmwasm_std::Coin::new(500, "uatom")
+        ]
+    );
+
+    // One pool per ejected asset, with the seed remainder returned
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(contract, &coins(1001, SEED_DENOM)),
+        ExecuteMsg::SeedLiquidity {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(res.messages[0].id, 2);
+    assert_eq!(res.messages[1].id, 2);
+    assert_eq!(
+        res.messages[2].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: OWNER.to_string(),
+            amount: coins(1, SEED_DENOM),
+        })
+    );
+    let ejected: EjectedResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Ejected {}).unwrap()).unwrap();
+    assert!(ejected.ejected.is_empty());
+
+    // The create reply passes the new pool's shares on to the owner
+    deps.querier
+        .update_balance(contract, coins(100, "gamm/pool/7"));
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        reply_with(2, MsgCreateBalancerPoolResponse { pool_id: 7 }.into()),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: OWNER.to_string(),
+            amount: coins(100, "gamm/pool/7"),
+        })
+    );
+    let seeded: SeededPoolsResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::SeededPools {}).unwrap()).unwrap();
+    assert_eq!(seeded.pool_ids, vec![7]);
+}
+
+#[test]
+fn test_transform() {
+    let mut deps = setup();
+    let env = mock_env();
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &coins(100, "uosmo")),
+        ExecuteMsg::Transform {
+            target_pool_id: 2,
+            min_shares_out: Uint128::new(1),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("invalid pool denom"))
+    );
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(ADDR1, &coins(100, "gamm/pool/2")),
+        ExecuteMsg::Transform {
+            target_pool_id: 2,
+            min_shares_out: Uint128::new(1),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::SamePool {});
+
+    // Pool 1 holds uosmo and uatom, pool 2 uosmo and uion
+    let pending = PendingTransform {
+        sender: Addr::unchecked(ADDR1),
+        source_pool_id: 1,
+        target_pool_id: 2,
+        target_denoms: vec!["uosmo".to_string(), "uion".to_string()],
+        swap_denom: "uosmo".to_string(),
+        min_shares_out: Uint128::new(100),
+        joins_left: 0,
+        shares_out: Uint128::zero(),
+    };
+    TRANSFORM.save(deps.as_mut().storage, &pending).unwrap();
+
+    // An exit without assets out can't be joined
+    let err = reply(
+        deps.as_mut(),
+        env.clone(),
+        reply_with(
+            3,
+            MsgExitPoolResponse {
+                token_out: vec![Coin {
+                    denom: "uosmo".to_string(),
+                    amount: "0".to_string(),
+                }],
+            }
+            .into(),
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EmptyExit {});
+
+    let token_out = vec![
+        Coin {
+            denom: "uosmo".to_string(),
+            amount: "1000".to_string(),
+        },
+        Coin {
+            denom: "uatom".to_string(),
+            amount: "500".to_string(),
+        },
+    ];
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        reply_with(3, MsgExitPoolResponse { token_out }.into()),
+    )
+    .unwrap();
+    // uosmo joins directly, uatom is swapped into uosmo first
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.messages[0].id, 5);
+    assert_eq!(res.messages[1].id, 4);
+
+    let join = |shares: &str| {
+        reply_with(
+            5,
+            MsgJoinSwapExternAmountInResponse {
+                share_out_amount: shares.to_string(),
+            }
+            .into(),
+        )
+    };
+    let res = reply(deps.as_mut(), env.clone(), join("60")).unwrap();
+    assert!(res.messages.is_empty());
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        reply_with(
+            4,
+            MsgSwapExactAmountInResponse {
+                token_out_amount: "450".to_string(),
+            }
+            .into(),
+        ),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, 5);
+
+    // The last join checks the minimum, reverting the whole transform
+    let mut deps_short = setup();
+    TRANSFORM
+        .save(
+            deps_short.as_mut().storage,
+            &PendingTransform {
+                joins_left: 1,
+                shares_out: Uint128::new(60),
+                ..pending
+            },
+        )
+        .unwrap();
+    let err = reply(deps_short.as_mut(), env.clone(), join("30")).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MinSharesOut {
+            min: Uint128::new(100)
+        }
+    );
+
+    let res = reply(deps.as_mut(), env, join("50")).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: ADDR1.to_string(),
+            amount: coins(110, "gamm/pool/2"),
+        })
+    );
+    assert!(TRANSFORM.may_load(deps.as_ref().storage).unwrap().is_none());
+}
+
+/// Answers the pool liquidity and total shares queries, which `MockQuerier`
+/// can not.
+struct PoolQuerier {
+    base: MockQuerier,
+    liquidity: Vec<Coin>,
+    total_shares: Coin,
+}
+
+impl Querier for PoolQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let res = match from_slice::<QueryRequest<Empty>>(bin_request) {
+            Ok(QueryRequest::Stargate { path, .. }) if path.ends_with("TotalPoolLiquidity") => {
+                to_binary(&QueryTotalPoolLiquidityResponse {
+                    liquidity: self.liquidity.clone(),
+                })
+            }
+            Ok(QueryRequest::Stargate { path, .. }) if path.ends_with("TotalShares") => {
+                to_binary(&QueryTotalSharesResponse {
+                    total_shares: Some(self.total_shares.clone()),
+                })
+            }
+            _ => return self.base.raw_query(bin_request),
+        };
+        SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+    }
+}
+
+#[test]
+fn test_exit_pool_slippage() {
+    let coin = |denom: &str, amount: &str| Coin {
+        denom: denom.to_string(),
+        amount: amount.to_string(),
+    };
+    let deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: PoolQuerier {
+            base: MockQuerier::default(),
+            liquidity: vec![coin("uatom", "1001"), coin("uosmo", "30000")],
+            total_shares: coin("gamm/pool/1", "3000"),
+        },
+        custom_query_type: PhantomData,
+    };
+    let env = mock_env();
+    // A third of the pool is 333.67 uatom and 10000 uosmo, of which up to 1%
+    // may be lost to rounding and the exit fee
+    let msg = exit_pool_msg(deps.as_ref(), &env, 1, Uint128::new(1000)).unwrap();
+    assert_eq!(
+        msg,
+        MsgExitPool {
+            sender: env.contract.address.to_string(),
+            pool_id: 1,
+            share_in_amount: "1000".to_string(),
+            token_out_mins: vec![coin("uatom", "329"), coin("uosmo", "9900")],
+        }
+    );
+}
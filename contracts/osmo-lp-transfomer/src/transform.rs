@@ -0,0 +1,173 @@
+use std::str::FromStr;
+
+use cosmwasm_std::{
+    coins, BankMsg, DepsMut, Env, MessageInfo, Reply, Response, StdError, SubMsg, Uint128,
+};
+use cw_utils::one_coin;
+use osmosis_std::types::{
+    cosmos::base::v1beta1::Coin,
+    osmosis::{
+        gamm::v1beta1::{
+            GammQuerier, MsgExitPoolResponse, MsgJoinSwapExternAmountIn,
+            MsgJoinSwapExternAmountInResponse,
+        },
+        poolmanager::v1beta1::{
+            MsgSwapExactAmountIn, MsgSwapExactAmountInResponse, SwapAmountInRoute,
+        },
+    },
+};
+
+use crate::contract::{
+    exit_pool_msg, extract_pool_id_from_denom, pool_denom, TRANSFORM_EXIT_REPLY_ID,
+    TRANSFORM_JOIN_REPLY_ID, TRANSFORM_SWAP_REPLY_ID,
+};
+use crate::state::{PendingTransform, TRANSFORM};
+use crate::ContractError;
+
+fn join_msg(env: &Env, transform: &PendingTransform, token_in: Coin) -> SubMsg {
+    // the shares out are checked against `min_shares_out` once all joins reply
+    let msg = MsgJoinSwapExternAmountIn {
+        sender: env.contract.address.to_string(),
+        pool_id: transform.target_pool_id,
+        token_in: Some(token_in),
+        share_out_min_amount: "1".to_string(),
+    };
+    SubMsg::reply_on_success(msg, TRANSFORM_JOIN_REPLY_ID)
+}
+
+/// Exits the pool of the shares sent along, then joins `target_pool_id` with
+/// each asset out, swapping those it does not hold through the source pool
+/// into an asset it does. The target pool's shares are returned to the
+/// sender once the last join replies.
+pub fn execute_transform(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    target_pool_id: u64,
+    min_shares_out: Uint128,
+) -> Result<Response, ContractError> {
+    let shares = one_coin(&info)?;
+    let source_pool_id = extract_pool_id_from_denom(&shares.denom)?;
+    if shares.denom != pool_denom(source_pool_id) {
+        return Err(ContractError::Std(StdError::generic_err(
+            "invalid pool denom",
+        )));
+    }
+    if source_pool_id == target_pool_id {
+        return Err(ContractError::SamePool {});
+    }
+
+    let gamm_querier = GammQuerier::new(&deps.querier);
+    let source_denoms: Vec<String> = gamm_querier
+        .total_pool_liquidity(source_pool_id)?
+        .liquidity
+        .into_iter()
+        .map(|coin| coin.denom)
+        .collect();
+    let target_denoms: Vec<String> = gamm_querier
+        .total_pool_liquidity(target_pool_id)?
+        .liquidity
+        .into_iter()
+        .map(|coin| coin.denom)
+        .collect();
+    let swap_denom = source_denoms
+        .into_iter()
+        .find(|denom| target_denoms.contains(denom))
+        .ok_or(ContractError::NoCommonAsset {})?;
+
+    TRANSFORM.save(
+        deps.storage,
+        &PendingTransform {
+            sender: info.sender.clone(),
+            source_pool_id,
+            target_pool_id,
+            target_denoms,
+            swap_denom,
+            min_shares_out,
+            joins_left: 0,
+            shares_out: Uint128::zero(),
+        },
+    )?;
+    let msg_exit_pool = exit_pool_msg(deps.as_ref(), &env, source_pool_id, shares.amount)?;
+    Ok(Response::new()
+        .add_attribute("action", "transform")
+        .add_attribute("from", info.sender)
+        .add_attribute("source_pool_id", source_pool_id.to_string())
+        .add_attribute("target_pool_id", target_pool_id.to_string())
+        .add_attribute("amount", shares.amount)
+        .add_submessage(SubMsg::reply_on_success(
+            msg_exit_pool,
+            TRANSFORM_EXIT_REPLY_ID,
+        )))
+}
+
+pub fn reply_exit(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let res: MsgExitPoolResponse = msg.result.try_into()?;
+    let mut transform = TRANSFORM.load(deps.storage)?;
+    let mut msgs = vec![];
+    for coin in res.token_out {
+        if Uint128::from_str(&coin.amount)?.is_zero() {
+            continue;
+        }
+        if transform.target_denoms.contains(&coin.denom) {
+            msgs.push(join_msg(&env, &transform, coin));
+        } else {
+            let msg = MsgSwapExactAmountIn {
+                sender: env.contract.address.to_string(),
+                routes: vec![SwapAmountInRoute {
+                    pool_id: transform.source_pool_id,
+                    token_out_denom: transform.swap_denom.clone(),
+                }],
+                token_in: Some(coin),
+                token_out_min_amount: "1".to_string(),
+            };
+            msgs.push(SubMsg::reply_on_success(msg, TRANSFORM_SWAP_REPLY_ID));
+        }
+    }
+    if msgs.is_empty() {
+        return Err(ContractError::EmptyExit {});
+    }
+    // every asset out ends up joined, directly or once swapped
+    transform.joins_left = msgs.len() as u64;
+    TRANSFORM.save(deps.storage, &transform)?;
+    Ok(Response::new().add_submessages(msgs))
+}
+
+pub fn reply_swap(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let res: MsgSwapExactAmountInResponse = msg.result.try_into()?;
+    let transform = TRANSFORM.load(deps.storage)?;
+    let token_in = Coin {
+        denom: transform.swap_denom.clone(),
+        amount: res.token_out_amount,
+    };
+    Ok(Response::new().add_submessage(join_msg(&env, &transform, token_in)))
+}
+
+pub fn reply_join(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let res: MsgJoinSwapExternAmountInResponse = msg.result.try_into()?;
+    let mut transform = TRANSFORM.load(deps.storage)?;
+    transform.shares_out += Uint128::from_str(&res.share_out_amount)?;
+    transform.joins_left -= 1;
+    if transform.joins_left > 0 {
+        TRANSFORM.save(deps.storage, &transform)?;
+        return Ok(Response::new());
+    }
+
+    TRANSFORM.remove(deps.storage);
+    if transform.shares_out < transform.min_shares_out {
+        return Err(ContractError::MinSharesOut {
+            min: transform.min_shares_out,
+        });
+    }
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: transform.sender.to_string(),
+            amount: coins(
+                transform.shares_out.u128(),
+                pool_denom(transform.target_pool_id),
+            ),
+        })
+        .add_attribute("action", "transform_complete")
+        .add_attribute("to", transform.sender)
+        .add_attribute("shares_out", transform.shares_out))
+}
@@ -0,0 +1,71 @@
+use cosmwasm_std::{
+    coins, to_binary, Addr, BankMsg, CosmosMsg, MessageInfo, StdResult, Uint128, WasmMsg,
+};
+use cw_utils::must_pay;
+
+use crate::StakingError;
+
+/// The asset a staking contract takes in and pays out.
+pub trait StakingAsset {
+    /// Returns the amount of the asset paid in with `info`. Cw20 tokens are
+    /// paid in through `Receive`, so `info.sender` is the token contract and
+    /// `received` the amount sent along.
+    fn amount_in(
+        &self,
+        info: &MessageInfo,
+        received: Option<Uint128>,
+    ) -> Result<Uint128, StakingError>;
+
+    /// Message paying `amount` of the asset out to `recipient`.
+    fn transfer_msg(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg>;
+}
+
+/// A native denom paid in through `funds`.
+pub struct NativeAsset(pub String);
+
+impl StakingAsset for NativeAsset {
+    fn amount_in(
+        &self,
+        info: &MessageInfo,
+        _received: Option<Uint128>,
+    ) -> Result<Uint128, StakingError> {
+        Ok(must_pay(info, &self.0)?)
+    }
+
+    fn transfer_msg(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), &self.0),
+        }))
+    }
+}
+
+/// A cw20 token contract.
+pub struct Cw20Asset(pub Addr);
+
+impl StakingAsset for Cw20Asset {
+    fn amount_in(
+        &self,
+        info: &MessageInfo,
+        received: Option<Uint128>,
+    ) -> Result<Uint128, StakingError> {
+        if info.sender != self.0 {
+            return Err(StakingError::InvalidToken {
+                received: info.sender.clone(),
+                expected: self.0.clone(),
+            });
+        }
+        Ok(received.unwrap_or_default())
+    }
+
+    fn transfer_msg(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: self.0.to_string(),
+            msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }))
+    }
+}
@@ -0,0 +1,103 @@
+use cosmwasm_std::{Addr, BlockInfo, Deps, StdError, StdResult, Storage, Uint128};
+use cw_controllers::{Claim, ClaimsResponse};
+use cw_utils::Expiration;
+
+use crate::state::CLAIMS;
+use crate::StakingError;
+
+/// Adds a claim of `amount` released at `release_at` to `addr`'s claims.
+/// Claims released at the same height or time are merged, so only distinct
+/// release times count towards `max_claims`.
+pub fn create_claim(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    amount: Uint128,
+    release_at: Expiration,
+    max_claims: u64,
+) -> Result<(), StakingError> {
+    let mut claims = CLAIMS.may_load(storage, addr)?.unwrap_or_default();
+    match claims
+        .iter_mut()
+        .find(|claim| claim.release_at == release_at)
+    {
+        Some(claim) => {
+            claim.amount = claim
+                .amount
+                .checked_add(amount)
+                .map_err(StdError::overflow)?
+        }
+        None => {
+            if claims.len() as u64 >= max_claims {
+                return Err(StakingError::TooManyClaims {});
+            }
+            claims.push(Claim { amount, release_at });
+        }
+    }
+    CLAIMS.save(storage, addr, &claims)?;
+    Ok(())
+}
+
+/// Releases up to `limit` of `addr`'s matured claims, oldest first, and
+/// returns their total. Matured claims past the limit stay queued.
+pub fn claim_tokens(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    block: &BlockInfo,
+    limit: Option<u32>,
+) -> StdResult<Uint128> {
+    let mut remaining = limit.map(|limit| limit as usize).unwrap_or(usize::MAX);
+    let mut released = Uint128::zero();
+    let mut pending = vec![];
+    for claim in CLAIMS.may_load(storage, addr)?.unwrap_or_default() {
+        if remaining > 0 && claim.release_at.is_expired(block) {
+            released = released.checked_add(claim.amount)?;
+            remaining -= 1;
+        } else {
+            pending.push(claim);
+        }
+    }
+    if pending.is_empty() {
+        CLAIMS.remove(storage, addr);
+    } else {
+        CLAIMS.save(storage, addr, &pending)?;
+    }
+    Ok(released)
+}
+
+/// Takes `amount` out of `addr`'s claims which have not matured yet, newest
+/// first, so that it can be restaked.
+pub fn cancel_claims(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    block: &BlockInfo,
+    amount: Uint128,
+) -> Result<(), StakingError> {
+    let mut claims = CLAIMS.may_load(storage, addr)?.unwrap_or_default();
+    let mut remaining = amount;
+    for claim in claims.iter_mut().rev() {
+        if remaining.is_zero() {
+            break;
+        }
+        if claim.release_at.is_expired(block) {
+            continue;
+        }
+        let cancelled = claim.amount.min(remaining);
+        claim.amount -= cancelled;
+        remaining -= cancelled;
+    }
+    if amount.is_zero() || !remaining.is_zero() {
+        return Err(StakingError::InvalidCancelAmount {});
+    }
+    claims.retain(|claim| !claim.amount.is_zero());
+    if claims.is_empty() {
+        CLAIMS.remove(storage, addr);
+    } else {
+        CLAIMS.save(storage, addr, &claims)?;
+    }
+    Ok(())
+}
+
+pub fn query_claims(deps: Deps, addr: &Addr) -> StdResult<ClaimsResponse> {
+    let claims = CLAIMS.may_load(deps.storage, addr)?.unwrap_or_default();
+    Ok(ClaimsResponse { claims })
+}
@@ -0,0 +1,96 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, Response, StdResult, Storage, Uint128};
+
+use crate::msg::DelegationResponse;
+use crate::state::{DELEGATED_POWER, DELEGATIONS, STAKED_BALANCES};
+use crate::StakingError;
+
+/// Adds `amount` to the power of `staker`'s delegate, if they have one.
+/// Called whenever `staker`'s staked balance grows.
+pub fn add_delegated_power(
+    storage: &mut dyn Storage,
+    height: u64,
+    staker: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    if let Some(delegate) = DELEGATIONS.may_load(storage, staker)? {
+        DELEGATED_POWER.update(storage, &delegate, height, |power| -> StdResult<_> {
+            Ok(power.unwrap_or_default().checked_add(amount)?)
+        })?;
+    }
+    Ok(())
+}
+
+/// Removes `amount` from the power of `staker`'s delegate, if they have one.
+/// Called whenever `staker`'s staked balance shrinks.
+pub fn remove_delegated_power(
+    storage: &mut dyn Storage,
+    height: u64,
+    staker: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    if let Some(delegate) = DELEGATIONS.may_load(storage, staker)? {
+        DELEGATED_POWER.update(storage, &delegate, height, |power| -> StdResult<_> {
+            Ok(power.unwrap_or_default().checked_sub(amount)?)
+        })?;
+    }
+    Ok(())
+}
+
+/// Delegates `staker`'s voting power, including stake added later, to
+/// `delegate`. Delegation is not transitive: power delegated to `staker`
+/// stays with `staker`.
+pub fn execute_delegate(
+    deps: DepsMut,
+    env: &Env,
+    staker: Addr,
+    delegate: Addr,
+) -> Result<Response, StakingError> {
+    if staker == delegate {
+        return Err(StakingError::SelfDelegation {});
+    }
+    let power = STAKED_BALANCES
+        .may_load(deps.storage, &staker)?
+        .unwrap_or_default();
+    remove_delegated_power(deps.storage, env.block.height, &staker, power)?;
+    DELEGATIONS.save(deps.storage, &staker, &delegate, env.block.height)?;
+    add_delegated_power(deps.storage, env.block.height, &staker, power)?;
+    Ok(Response::new()
+        .add_attribute("action", "delegate")
+        .add_attribute("from", staker)
+        .add_attribute("to", delegate)
+        .add_attribute("power", power))
+}
+
+pub fn execute_undelegate(
+    deps: DepsMut,
+    env: &Env,
+    staker: Addr,
+) -> Result<Response, StakingError> {
+    let delegate = DELEGATIONS
+        .may_load(deps.storage, &staker)?
+        .ok_or(StakingError::NotDelegating {})?;
+    let power = STAKED_BALANCES
+        .may_load(deps.storage, &staker)?
+        .unwrap_or_default();
+    remove_delegated_power(deps.storage, env.block.height, &staker, power)?;
+    DELEGATIONS.remove(deps.storage, &staker, env.block.height)?;
+    Ok(Response::new()
+        .add_attribute("action", "undelegate")
+        .add_attribute("from", staker)
+        .add_attribute("to", delegate)
+        .add_attribute("power", power))
+}
+
+pub fn query_delegation(deps: Deps, address: String) -> StdResult<DelegationResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let delegate = DELEGATIONS
+        .may_load(deps.storage, &address)?
+        .map(|delegate| delegate.into_string());
+    let delegated_power = DELEGATED_POWER
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(DelegationResponse {
+        delegate,
+        delegated_power,
+    })
+}
@@ -0,0 +1,39 @@
+use cosmwasm_std::{Addr, StdError};
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum StakingError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+    #[error("{0}")]
+    PaymentError(#[from] PaymentError),
+    #[error("{0}")]
+    HookError(#[from] cw_controllers::HookError),
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+    #[error("Invalid token")]
+    InvalidToken { received: Addr, expected: Addr },
+    #[error("Too many outstanding claims. Claim some tokens before unstaking more.")]
+    TooManyClaims {},
+    #[error("Invalid unstaking duration, unstaking duration cannot be 0")]
+    InvalidUnstakingDuration {},
+    #[error("Invalid max claims, max claims cannot be 0")]
+    InvalidMaxClaims {},
+    #[error("Can only unstake less than or equal to the amount you have staked")]
+    InvalidUnstakeAmount {},
+    #[error(
+        "Can only transfer a non-zero amount less than or equal to the amount you have staked"
+    )]
+    InvalidTransferAmount {},
+    #[error("Can only cancel a non-zero amount less than or equal to the amount unbonding")]
+    InvalidCancelAmount {},
+    #[error("Recipient does not accept stakes from other addresses")]
+    RecipientOptedOut {},
+    #[error("No failed hook with id {id}")]
+    FailedHookNotFound { id: u64 },
+    #[error("Can not delegate voting power to yourself")]
+    SelfDelegation {},
+    #[error("Voting power is not delegated")]
+    NotDelegating {},
+}
@@ -0,0 +1,135 @@
+use crate::state::{
+    FailedHook, FAILED_HOOKS, FAILED_HOOK_COUNT, HOOKS, HOOK_GAS_LIMIT, HOOK_REPLY_COUNT,
+    PENDING_HOOKS,
+};
+use cosmwasm_std::{
+    to_binary, Addr, Binary, CosmosMsg, DepsMut, Env, ReplyOn, StdError, StdResult, Storage,
+    SubMsg, SubMsgResult, Uint128, WasmMsg,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Hook submessages reply with this offset plus a running count, under which
+/// the hook and its message are kept in `PENDING_HOOKS` until the reply.
+pub const HOOK_REPLY_ID_OFFSET: u64 = 1000;
+
+// This is just a helper to properly serialize the above message
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeChangedHookMsg {
+    Stake { addr: Addr, amount: Uint128 },
+    Unstake { addr: Addr, amount: Uint128 },
+}
+
+pub fn stake_hook_msgs(
+    storage: &mut dyn Storage,
+    addr: Addr,
+    amount: Uint128,
+) -> StdResult<Vec<SubMsg>> {
+    let msg = to_binary(&StakeChangedExecuteMsg::StakeChangeHook(
+        StakeChangedHookMsg::Stake { addr, amount },
+    ))?;
+    prepare_hook_msgs(storage, vec![msg])
+}
+
+pub fn unstake_hook_msgs(
+    storage: &mut dyn Storage,
+    addr: Addr,
+    amount: Uint128,
+) -> StdResult<Vec<SubMsg>> {
+    let msg = to_binary(&StakeChangedExecuteMsg::StakeChangeHook(
+        StakeChangedHookMsg::Unstake { addr, amount },
+    ))?;
+    prepare_hook_msgs(storage, vec![msg])
+}
+
+/// Hooks for `amount` of staked balance moving from `from` to `to`: an
+/// unstake for the sender followed by a stake for the recipient.
+pub fn transfer_hook_msgs(
+    storage: &mut dyn Storage,
+    from: Addr,
+    to: Addr,
+    amount: Uint128,
+) -> StdResult<Vec<SubMsg>> {
+    let msgs = vec![
+        to_binary(&StakeChangedExecuteMsg::StakeChangeHook(
+            StakeChangedHookMsg::Unstake { addr: from, amount },
+        ))?,
+        to_binary(&StakeChangedExecuteMsg::StakeChangeHook(
+            StakeChangedHookMsg::Stake { addr: to, amount },
+        ))?,
+    ];
+    prepare_hook_msgs(storage, msgs)
+}
+
+/// Dispatches each of `msgs` to every hook. Each submessage replies under its
+/// own id, so a failing hook is recorded instead of reverting the stake
+/// change, and a successful one clears its pending entry.
+fn prepare_hook_msgs(storage: &mut dyn Storage, msgs: Vec<Binary>) -> StdResult<Vec<SubMsg>> {
+    let gas_limit = HOOK_GAS_LIMIT.may_load(storage)?;
+    let mut count = HOOK_REPLY_COUNT.may_load(storage)?.unwrap_or_default();
+    let mut submsgs = vec![];
+    for msg in msgs {
+        let batch_msgs = HOOKS.prepare_hooks(storage, |a| {
+            let execute = WasmMsg::Execute {
+                contract_addr: a.to_string(),
+                msg: msg.clone(),
+                funds: vec![],
+            };
+            Ok(SubMsg::new(execute))
+        })?;
+        for submsg in batch_msgs {
+            if let CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) = &submsg.msg {
+                let hook = (Addr::unchecked(contract_addr), msg.clone());
+                PENDING_HOOKS.save(storage, count, &hook)?;
+            }
+            submsgs.push(SubMsg {
+                id: HOOK_REPLY_ID_OFFSET + count,
+                gas_limit,
+                reply_on: ReplyOn::Always,
+                ..submsg
+            });
+            count += 1;
+        }
+    }
+    HOOK_REPLY_COUNT.save(storage, &count)?;
+    Ok(submsgs)
+}
+
+/// Clears the pending hook of a hook submessage reply. If the hook failed it
+/// is recorded in `FAILED_HOOKS` and returned.
+pub fn record_failed_hook(
+    deps: DepsMut,
+    env: &Env,
+    id: u64,
+    result: SubMsgResult,
+) -> StdResult<Option<(u64, FailedHook)>> {
+    let key = id - HOOK_REPLY_ID_OFFSET;
+    let (hook, msg) = PENDING_HOOKS
+        .may_load(deps.storage, key)?
+        .ok_or_else(|| StdError::not_found("hook"))?;
+    PENDING_HOOKS.remove(deps.storage, key);
+    let error = match result {
+        SubMsgResult::Ok(_) => return Ok(None),
+        SubMsgResult::Err(error) => error,
+    };
+    let failed_hook = FailedHook {
+        hook,
+        msg,
+        error,
+        height: env.block.height,
+    };
+    let id = FAILED_HOOK_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    FAILED_HOOKS.save(deps.storage, id, &failed_hook)?;
+    FAILED_HOOK_COUNT.save(deps.storage, &(id + 1))?;
+    Ok(Some((id, failed_hook)))
+}
+
+// This is just a helper to properly serialize the above message
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+enum StakeChangedExecuteMsg {
+    StakeChangeHook(StakeChangedHookMsg),
+}
@@ -0,0 +1,12 @@
+pub mod asset;
+pub mod claims;
+pub mod delegation;
+mod error;
+pub mod hooks;
+pub mod msg;
+pub mod score;
+pub mod staking;
+pub mod state;
+
+pub use crate::asset::{Cw20Asset, NativeAsset, StakingAsset};
+pub use crate::error::StakingError;
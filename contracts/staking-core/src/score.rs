@@ -0,0 +1,75 @@
+use cosmwasm_std::{Addr, Env, StdError, StdResult, Storage, Uint128};
+
+use crate::state::{Score, SCORES, SCORE_END, STAKED_BALANCES, STAKED_TOTAL, TOTAL_SCORE};
+
+fn score_time(storage: &dyn Storage, env: &Env) -> StdResult<u64> {
+    let now = env.block.time.seconds();
+    Ok(match SCORE_END.may_load(storage)? {
+        Some(end) => now.min(end),
+        None => now,
+    })
+}
+
+fn accrue(score: Option<Score>, balance: Uint128, now: u64) -> StdResult<Score> {
+    let score = match score {
+        Some(score) => score,
+        None => {
+            return Ok(Score {
+                score: Uint128::zero(),
+                last_updated: now,
+            })
+        }
+    };
+    let elapsed = now.saturating_sub(score.last_updated);
+    Ok(Score {
+        score: balance
+            .checked_mul(Uint128::from(elapsed))
+            .and_then(|accrued| score.score.checked_add(accrued))
+            .map_err(StdError::overflow)?,
+        last_updated: now.max(score.last_updated),
+    })
+}
+
+/// Accrues `staker`'s score on its current staked balance. Called before
+/// every change to the balance, so that the score integrates it over time.
+pub fn update_score(storage: &mut dyn Storage, env: &Env, staker: &Addr) -> StdResult<()> {
+    let now = score_time(storage, env)?;
+    let balance = STAKED_BALANCES
+        .may_load(storage, staker)?
+        .unwrap_or_default();
+    let score = accrue(SCORES.may_load(storage, staker)?, balance, now)?;
+    SCORES.save(storage, staker, &score)
+}
+
+/// Accrues the total score on the current staked total. Called before every
+/// change to the total.
+pub fn update_total_score(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let now = score_time(storage, env)?;
+    let total = STAKED_TOTAL.may_load(storage)?.unwrap_or_default();
+    let score = accrue(TOTAL_SCORE.may_load(storage)?, total, now)?;
+    TOTAL_SCORE.save(storage, &score)
+}
+
+/// Stops scores from accumulating past the current block. Scores are final
+/// from then on.
+pub fn close_scores(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    if SCORE_END.may_load(storage)?.is_none() {
+        SCORE_END.save(storage, &env.block.time.seconds())?;
+    }
+    Ok(())
+}
+
+/// `staker`'s score up to the current block, or to the end of scoring.
+pub fn score_of(storage: &dyn Storage, env: &Env, staker: &Addr) -> StdResult<Uint128> {
+    let now = score_time(storage, env)?;
+    let balance = STAKED_BALANCES
+        .may_load(storage, staker)?
+        .unwrap_or_default();
+    Ok(accrue(SCORES.may_load(storage, staker)?, balance, now)?.score)
+}
+
+pub fn total_score(storage: &dyn Storage, env: &Env) -> StdResult<Uint128> {
+    let now = score_time(storage, env)?;
+    let total = STAKED_TOTAL.may_load(storage)?.unwrap_or_default();
+    Ok(accrue(TOTAL_SCORE.may_load(storage)?, total, now)?.score)
+}
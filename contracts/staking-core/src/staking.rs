@@ -0,0 +1,667 @@
+use cosmwasm_std::{
+    Addr, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, Order, Reply, Response, StdError,
+    StdResult, Storage, SubMsg, Uint128, WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20ReceiveMsg};
+use cw_controllers::ClaimsResponse;
+use cw_storage_plus::Bound;
+use cw_utils::Duration;
+
+use crate::asset::StakingAsset;
+use crate::claims;
+use crate::delegation::{add_delegated_power, remove_delegated_power};
+use crate::hooks::{record_failed_hook, stake_hook_msgs, transfer_hook_msgs, unstake_hook_msgs};
+use crate::msg::{
+    AcceptsStakesResponse, FailedHookResponse, FailedHooksResponse, GetHooksResponse, InfoResponse,
+    ListStakersResponse, StakedBalanceAtHeightResponse, StakedValueResponse, StakerBalanceResponse,
+    TotalPowerAtHeightResponse, TotalStakedAtHeightResponse, TotalValueResponse,
+    VotingPowerAtHeightResponse,
+};
+use crate::score::{update_score, update_total_score};
+use crate::state::{
+    BALANCE, DAO, DELEGATED_POWER, DELEGATIONS, FAILED_HOOKS, HOOKS, HOOK_GAS_LIMIT,
+    STAKED_BALANCES, STAKED_TOTAL, STAKE_OPT_OUTS,
+};
+use crate::StakingError;
+
+/// Page size of paginated queries when none is given.
+pub const DEFAULT_LIMIT: u32 = 10;
+/// Largest page size of paginated queries.
+pub const MAX_LIMIT: u32 = 30;
+
+pub fn validate_duration(duration: Option<Duration>) -> Result<(), StakingError> {
+    if let Some(unstaking_duration) = duration {
+        match unstaking_duration {
+            Duration::Height(height) => {
+                if height == 0 {
+                    return Err(StakingError::InvalidUnstakingDuration {});
+                }
+            }
+            Duration::Time(time) => {
+                if time == 0 {
+                    return Err(StakingError::InvalidUnstakingDuration {});
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_max_claims(max_claims: Option<u64>) -> Result<(), StakingError> {
+    if max_claims == Some(0) {
+        return Err(StakingError::InvalidMaxClaims {});
+    }
+    Ok(())
+}
+
+pub fn instantiate(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    // Initialize state to zero. We do this instead of using
+    // `unwrap_or_default` where this is used as it protects us
+    // against a scenerio where state is cleared by a bad actor and
+    // `unwrap_or_default` carries on.
+    STAKED_TOTAL.save(storage, &Uint128::zero(), env.block.height)?;
+    BALANCE.save(storage, &Uint128::zero())?;
+    Ok(())
+}
+
+/// Credits `shares` to `staker`, accruing scores on the balances they
+/// change, and returns the stake hook messages. Shared by `stake` and
+/// contracts which value shares by more than `BALANCE`.
+pub fn mint_shares(
+    storage: &mut dyn Storage,
+    env: &Env,
+    staker: &Addr,
+    shares: Uint128,
+) -> Result<Vec<SubMsg>, StakingError> {
+    update_score(storage, env, staker)?;
+    update_total_score(storage, env)?;
+    STAKED_BALANCES.update(
+        storage,
+        staker,
+        env.block.height,
+        |balance| -> StdResult<Uint128> { Ok(balance.unwrap_or_default().checked_add(shares)?) },
+    )?;
+    STAKED_TOTAL.update(storage, env.block.height, |total| -> StdResult<Uint128> {
+        Ok(total.unwrap_or_default().checked_add(shares)?)
+    })?;
+    add_delegated_power(storage, env.block.height, staker, shares)?;
+    Ok(stake_hook_msgs(storage, staker.clone(), shares)?)
+}
+
+/// Burns `shares` of `staker`, accruing scores on the balances they change,
+/// and returns the unstake hook messages.
+pub fn burn_shares(
+    storage: &mut dyn Storage,
+    env: &Env,
+    staker: &Addr,
+    shares: Uint128,
+) -> Result<Vec<SubMsg>, StakingError> {
+    update_score(storage, env, staker)?;
+    update_total_score(storage, env)?;
+    STAKED_BALANCES.update(
+        storage,
+        staker,
+        env.block.height,
+        |balance| -> Result<Uint128, StakingError> {
+            balance
+                .unwrap_or_default()
+                .checked_sub(shares)
+                .map_err(|_e| StakingError::InvalidUnstakeAmount {})
+        },
+    )?;
+    STAKED_TOTAL.update(
+        storage,
+        env.block.height,
+        |total| -> Result<Uint128, StakingError> {
+            total
+                .unwrap_or_default()
+                .checked_sub(shares)
+                .map_err(|_e| StakingError::InvalidUnstakeAmount {})
+        },
+    )?;
+    remove_delegated_power(storage, env.block.height, staker, shares)?;
+    Ok(unstake_hook_msgs(storage, staker.clone(), shares)?)
+}
+
+/// Credits `amount` of the staked asset, already held by the contract, to
+/// `staker` and returns the stake hook messages.
+pub fn stake(
+    storage: &mut dyn Storage,
+    env: &Env,
+    staker: &Addr,
+    amount: Uint128,
+) -> Result<Vec<SubMsg>, StakingError> {
+    let balance = BALANCE.load(storage)?;
+    let staked_total = STAKED_TOTAL.load(storage)?;
+    let amount_to_stake = if staked_total == Uint128::zero() || balance == Uint128::zero() {
+        amount
+    } else {
+        staked_total
+            .checked_mul(amount)
+            .map_err(StdError::overflow)?
+            .checked_div(balance)
+            .map_err(StdError::divide_by_zero)?
+    };
+    BALANCE.save(
+        storage,
+        &balance.checked_add(amount).map_err(StdError::overflow)?,
+    )?;
+    mint_shares(storage, env, staker, amount_to_stake)
+}
+
+/// Errors if `recipient` opted out of being staked for by addresses other
+/// than itself.
+pub fn ensure_accepts_stakes(
+    storage: &dyn Storage,
+    sender: &Addr,
+    recipient: &Addr,
+) -> Result<(), StakingError> {
+    if sender != recipient && STAKE_OPT_OUTS.has(storage, recipient) {
+        return Err(StakingError::RecipientOptedOut {});
+    }
+    Ok(())
+}
+
+/// Stakes `amount` paid in by `sender` for `recipient`, or for `sender`
+/// itself if no recipient is given.
+pub fn execute_stake(
+    deps: DepsMut,
+    env: &Env,
+    sender: Addr,
+    recipient: Option<Addr>,
+    amount: Uint128,
+) -> Result<Response, StakingError> {
+    let recipient = recipient.unwrap_or_else(|| sender.clone());
+    ensure_accepts_stakes(deps.storage, &sender, &recipient)?;
+    let hook_msgs = stake(deps.storage, env, &recipient, amount)?;
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_attribute("action", "stake")
+        .add_attribute("from", sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_set_accept_stakes(
+    storage: &mut dyn Storage,
+    addr: Addr,
+    enabled: bool,
+) -> Result<Response, StakingError> {
+    if enabled {
+        STAKE_OPT_OUTS.remove(storage, &addr);
+    } else {
+        STAKE_OPT_OUTS.save(storage, &addr, &Empty {})?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "set_accept_stakes")
+        .add_attribute("from", addr)
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+/// Burns `amount` of `staker`'s staked balance and returns the amount of the
+/// staked asset it is worth, along with the unstake hook messages.
+pub fn unstake(
+    storage: &mut dyn Storage,
+    env: &Env,
+    staker: &Addr,
+    amount: Uint128,
+) -> Result<(Uint128, Vec<SubMsg>), StakingError> {
+    let balance = BALANCE.load(storage)?;
+    let staked_total = STAKED_TOTAL.load(storage)?;
+    let amount_to_claim = amount
+        .checked_mul(balance)
+        .map_err(StdError::overflow)?
+        .checked_div(staked_total)
+        .map_err(|_e| StakingError::InvalidUnstakeAmount {})?;
+    let hook_msgs = burn_shares(storage, env, staker, amount)?;
+    BALANCE.save(
+        storage,
+        &balance
+            .checked_sub(amount_to_claim)
+            .map_err(|_e| StakingError::InvalidUnstakeAmount {})?,
+    )?;
+    Ok((amount_to_claim, hook_msgs))
+}
+
+/// Burns `amount` of `staker`'s staked balance. The asset it is worth is
+/// sent straight away without an unstaking duration, or claimable once it
+/// has passed.
+pub fn execute_unstake(
+    deps: DepsMut,
+    env: &Env,
+    asset: &impl StakingAsset,
+    staker: Addr,
+    amount: Uint128,
+    unstaking_duration: Option<Duration>,
+    max_claims: u64,
+) -> Result<Response, StakingError> {
+    let (amount_to_claim, hook_msgs) = unstake(deps.storage, env, &staker, amount)?;
+    match unstaking_duration {
+        None => Ok(Response::new()
+            .add_message(asset.transfer_msg(&staker, amount_to_claim)?)
+            .add_submessages(hook_msgs)
+            .add_attribute("action", "unstake")
+            .add_attribute("from", staker)
+            .add_attribute("amount", amount)
+            .add_attribute("claim_duration", "None")),
+        Some(duration) => {
+            claims::create_claim(
+                deps.storage,
+                &staker,
+                amount_to_claim,
+                duration.after(&env.block),
+                max_claims,
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "unstake")
+                .add_submessages(hook_msgs)
+                .add_attribute("from", staker)
+                .add_attribute("amount", amount)
+                .add_attribute("claim_duration", format!("{}", duration)))
+        }
+    }
+}
+
+pub fn execute_claim(
+    deps: DepsMut,
+    env: &Env,
+    asset: &impl StakingAsset,
+    staker: Addr,
+    limit: Option<u32>,
+) -> Result<Response, StakingError> {
+    let release = claims::claim_tokens(deps.storage, &staker, &env.block, limit)?;
+    if release.is_zero() {
+        return Err(StakingError::NothingToClaim {});
+    }
+    Ok(Response::new()
+        .add_message(asset.transfer_msg(&staker, release)?)
+        .add_attribute("action", "claim")
+        .add_attribute("from", staker)
+        .add_attribute("amount", release))
+}
+
+/// Restakes `amount` of `staker`'s claims which have not matured yet at the
+/// current exchange rate, so they earn rewards again.
+pub fn execute_cancel_unbonding(
+    deps: DepsMut,
+    env: &Env,
+    staker: Addr,
+    amount: Uint128,
+) -> Result<Response, StakingError> {
+    claims::cancel_claims(deps.storage, &staker, &env.block, amount)?;
+    let hook_msgs = stake(deps.storage, env, &staker, amount)?;
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_attribute("action", "cancel_unbonding")
+        .add_attribute("from", staker)
+        .add_attribute("amount", amount))
+}
+
+/// Moves `amount` of `from`'s staked balance, and the voting power it
+/// carries, to `recipient` and returns the hook messages for both sides.
+/// Claims `from` already unstaked stay with `from`.
+pub fn transfer(
+    storage: &mut dyn Storage,
+    env: &Env,
+    from: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<Vec<SubMsg>, StakingError> {
+    if amount.is_zero() {
+        return Err(StakingError::InvalidTransferAmount {});
+    }
+    update_score(storage, env, from)?;
+    update_score(storage, env, recipient)?;
+    STAKED_BALANCES.update(
+        storage,
+        from,
+        env.block.height,
+        |balance| -> Result<Uint128, StakingError> {
+            balance
+                .unwrap_or_default()
+                .checked_sub(amount)
+                .map_err(|_e| StakingError::InvalidTransferAmount {})
+        },
+    )?;
+    STAKED_BALANCES.update(
+        storage,
+        recipient,
+        env.block.height,
+        |balance| -> StdResult<Uint128> { Ok(balance.unwrap_or_default().checked_add(amount)?) },
+    )?;
+    remove_delegated_power(storage, env.block.height, from, amount)?;
+    add_delegated_power(storage, env.block.height, recipient, amount)?;
+    Ok(transfer_hook_msgs(
+        storage,
+        from.clone(),
+        recipient.clone(),
+        amount,
+    )?)
+}
+
+pub fn execute_transfer(
+    deps: DepsMut,
+    env: &Env,
+    from: Addr,
+    recipient: Addr,
+    amount: Uint128,
+) -> Result<Response, StakingError> {
+    let hook_msgs = transfer(deps.storage, env, &from, &recipient, amount)?;
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_attribute("action", "transfer")
+        .add_attribute("from", from)
+        .add_attribute("to", recipient)
+        .add_attribute("amount", amount))
+}
+
+/// Transfers `amount` of staked balance to `contract` and notifies it with a
+/// cw20 `Receive` message carrying `msg`.
+pub fn execute_send(
+    deps: DepsMut,
+    env: &Env,
+    from: Addr,
+    contract: Addr,
+    amount: Uint128,
+    msg: Binary,
+) -> Result<Response, StakingError> {
+    let hook_msgs = transfer(deps.storage, env, &from, &contract, amount)?;
+    let receive_msg: CosmosMsg = Cw20ReceiveMsg {
+        sender: from.to_string(),
+        amount,
+        msg,
+    }
+    .into_cosmos_msg(&contract)?;
+    Ok(Response::new()
+        .add_submessages(hook_msgs)
+        .add_message(receive_msg)
+        .add_attribute("action", "send")
+        .add_attribute("from", from)
+        .add_attribute("to", contract)
+        .add_attribute("amount", amount))
+}
+
+/// Adds `amount` to the balance backing staked tokens without minting any,
+/// raising the value of every staked token.
+pub fn execute_fund(
+    storage: &mut dyn Storage,
+    sender: Addr,
+    amount: Uint128,
+) -> Result<Response, StakingError> {
+    BALANCE.update(storage, |balance| -> StdResult<_> {
+        balance.checked_add(amount).map_err(StdError::overflow)
+    })?;
+    Ok(Response::new()
+        .add_attribute("action", "fund")
+        .add_attribute("from", sender)
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_add_hook(storage: &mut dyn Storage, addr: Addr) -> Result<Response, StakingError> {
+    HOOKS.add_hook(storage, addr.clone())?;
+    Ok(Response::new()
+        .add_attribute("action", "add_hook")
+        .add_attribute("hook", addr))
+}
+
+pub fn execute_remove_hook(
+    storage: &mut dyn Storage,
+    addr: Addr,
+) -> Result<Response, StakingError> {
+    HOOKS.remove_hook(storage, addr.clone())?;
+    Ok(Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("hook", addr))
+}
+
+pub fn execute_update_hook_gas_limit(
+    storage: &mut dyn Storage,
+    gas_limit: Option<u64>,
+) -> Result<Response, StakingError> {
+    match gas_limit {
+        Some(gas_limit) => HOOK_GAS_LIMIT.save(storage, &gas_limit)?,
+        None => HOOK_GAS_LIMIT.remove(storage),
+    }
+    Ok(Response::new()
+        .add_attribute("action", "update_hook_gas_limit")
+        .add_attribute(
+            "gas_limit",
+            gas_limit
+                .map(|g| g.to_string())
+                .unwrap_or_else(|| "None".to_string()),
+        ))
+}
+
+pub fn execute_replay_failed_hook(
+    storage: &mut dyn Storage,
+    id: u64,
+) -> Result<Response, StakingError> {
+    let failed_hook = FAILED_HOOKS
+        .may_load(storage, id)?
+        .ok_or(StakingError::FailedHookNotFound { id })?;
+    FAILED_HOOKS.remove(storage, id);
+
+    // Sent as a plain message so the replay reverts, keeping the failure
+    // logged, if the hook fails again.
+    let msg = WasmMsg::Execute {
+        contract_addr: failed_hook.hook.to_string(),
+        msg: failed_hook.msg,
+        funds: vec![],
+    };
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "replay_failed_hook")
+        .add_attribute("id", id.to_string())
+        .add_attribute("hook", failed_hook.hook))
+}
+
+pub fn execute_clear_failed_hook(
+    storage: &mut dyn Storage,
+    id: u64,
+) -> Result<Response, StakingError> {
+    if !FAILED_HOOKS.has(storage, id) {
+        return Err(StakingError::FailedHookNotFound { id });
+    }
+    FAILED_HOOKS.remove(storage, id);
+    Ok(Response::new()
+        .add_attribute("action", "clear_failed_hook")
+        .add_attribute("id", id.to_string()))
+}
+
+pub fn reply_hook(deps: DepsMut, env: &Env, msg: Reply) -> Result<Response, StakingError> {
+    let (id, failed_hook) = match record_failed_hook(deps, env, msg.id, msg.result)? {
+        Some(failed) => failed,
+        None => return Ok(Response::new()),
+    };
+    Ok(Response::new()
+        .add_attribute("action", "hook_failed")
+        .add_attribute("id", id.to_string())
+        .add_attribute("hook", failed_hook.hook)
+        .add_attribute("error", failed_hook.error))
+}
+
+pub fn query_staked_balance_at_height(
+    deps: Deps,
+    env: &Env,
+    address: String,
+    height: Option<u64>,
+) -> StdResult<StakedBalanceAtHeightResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let height = height.unwrap_or(env.block.height);
+    let balance = STAKED_BALANCES
+        .may_load_at_height(deps.storage, &address, height)?
+        .unwrap_or_default();
+    Ok(StakedBalanceAtHeightResponse { balance, height })
+}
+
+pub fn query_total_staked_at_height(
+    deps: Deps,
+    env: &Env,
+    height: Option<u64>,
+) -> StdResult<TotalStakedAtHeightResponse> {
+    let height = height.unwrap_or(env.block.height);
+    let total = STAKED_TOTAL
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+    Ok(TotalStakedAtHeightResponse { total, height })
+}
+
+/// The cw20 balance of a staking receipt, which is the current staked balance.
+pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = STAKED_BALANCES
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(BalanceResponse { balance })
+}
+
+pub fn query_staked_value(deps: Deps, address: String) -> StdResult<StakedValueResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCE.load(deps.storage).unwrap_or_default();
+    let staked = STAKED_BALANCES
+        .load(deps.storage, &address)
+        .unwrap_or_default();
+    let total = STAKED_TOTAL.load(deps.storage)?;
+    if balance == Uint128::zero() || staked == Uint128::zero() || total == Uint128::zero() {
+        Ok(StakedValueResponse {
+            value: Uint128::zero(),
+        })
+    } else {
+        let value = staked
+            .checked_mul(balance)
+            .map_err(StdError::overflow)?
+            .checked_div(total)
+            .map_err(StdError::divide_by_zero)?;
+        Ok(StakedValueResponse { value })
+    }
+}
+
+pub fn query_total_value(deps: Deps) -> StdResult<TotalValueResponse> {
+    let balance = BALANCE.load(deps.storage)?;
+    Ok(TotalValueResponse { total: balance })
+}
+
+pub fn query_accepts_stakes(deps: Deps, address: String) -> StdResult<AcceptsStakesResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(AcceptsStakesResponse {
+        enabled: !STAKE_OPT_OUTS.has(deps.storage, &address),
+    })
+}
+
+pub fn query_claims(deps: Deps, address: String) -> StdResult<ClaimsResponse> {
+    claims::query_claims(deps, &deps.api.addr_validate(&address)?)
+}
+
+pub fn query_hooks(deps: Deps) -> StdResult<GetHooksResponse> {
+    Ok(GetHooksResponse {
+        hooks: HOOKS.query_hooks(deps)?.hooks,
+        gas_limit: HOOK_GAS_LIMIT.may_load(deps.storage)?,
+    })
+}
+
+pub fn query_failed_hooks(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<FailedHooksResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let failed_hooks = FAILED_HOOKS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (id, failed_hook) = item?;
+            Ok(FailedHookResponse {
+                id,
+                hook: failed_hook.hook.into_string(),
+                msg: failed_hook.msg,
+                error: failed_hook.error,
+                height: failed_hook.height,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(FailedHooksResponse { failed_hooks })
+}
+
+pub fn query_list_stakers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListStakersResponse> {
+    let start_at = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+
+    let stakers = cw_paginate::paginate_snapshot_map(
+        deps,
+        &STAKED_BALANCES,
+        start_at.as_ref(),
+        limit,
+        Order::Ascending,
+    )?;
+
+    let stakers = stakers
+        .into_iter()
+        .map(|(address, balance)| StakerBalanceResponse {
+            address: address.into_string(),
+            balance,
+        })
+        .collect();
+
+    Ok(ListStakersResponse { stakers })
+}
+
+/// Voting power of `address`: its own staked balance, unless it delegated
+/// it, plus the power delegated to it.
+pub fn query_voting_power_at_height(
+    deps: Deps,
+    env: &Env,
+    address: String,
+    height: Option<u64>,
+) -> StdResult<VotingPowerAtHeightResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let height = height.unwrap_or(env.block.height);
+    let own = if DELEGATIONS
+        .may_load_at_height(deps.storage, &address, height)?
+        .is_some()
+    {
+        Uint128::zero()
+    } else {
+        STAKED_BALANCES
+            .may_load_at_height(deps.storage, &address, height)?
+            .unwrap_or_default()
+    };
+    let delegated = DELEGATED_POWER
+        .may_load_at_height(deps.storage, &address, height)?
+        .unwrap_or_default();
+    Ok(VotingPowerAtHeightResponse {
+        power: own.checked_add(delegated)?,
+        height,
+    })
+}
+
+pub fn query_total_power_at_height(
+    deps: Deps,
+    env: &Env,
+    height: Option<u64>,
+) -> StdResult<TotalPowerAtHeightResponse> {
+    let TotalStakedAtHeightResponse { total, height } =
+        query_total_staked_at_height(deps, env, height)?;
+    Ok(TotalPowerAtHeightResponse {
+        power: total,
+        height,
+    })
+}
+
+pub fn query_dao(deps: Deps) -> StdResult<Addr> {
+    DAO.load(deps.storage)
+}
+
+pub fn query_info(deps: Deps) -> StdResult<InfoResponse> {
+    let info = cw2::get_contract_version(deps.storage)?;
+    Ok(InfoResponse { info })
+}
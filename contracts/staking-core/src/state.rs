@@ -0,0 +1,92 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Binary, Empty, Uint128};
+use cw_controllers::Claim;
+use cw_controllers::Hooks;
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
+
+pub const STAKED_BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "staked_balances",
+    "staked_balance__checkpoints",
+    "staked_balance__changelog",
+    Strategy::EveryBlock,
+);
+
+pub const STAKED_TOTAL: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_staked",
+    "total_staked__checkpoints",
+    "total_staked__changelog",
+    Strategy::EveryBlock,
+);
+
+/// The default maximum number of claims that may be outstanding, unless the
+/// contract configures its own.
+pub const MAX_CLAIMS: u64 = 100;
+
+/// Outstanding claims of each staker. Stored like `cw_controllers::Claims`,
+/// which this replaces so that claims can be merged.
+pub const CLAIMS: Map<&Addr, Vec<Claim>> = Map::new("claims");
+
+pub const BALANCE: Item<Uint128> = Item::new("balance");
+
+// Hooks to contracts that will receive staking and unstaking messages
+pub const HOOKS: Hooks = Hooks::new("hooks");
+
+/// Gas limit for each hook submessage. Without one a hook running out of gas
+/// reverts the whole stake change.
+pub const HOOK_GAS_LIMIT: Item<u64> = Item::new("hook_gas_limit");
+
+/// The hook and message of each hook submessage awaiting its reply, keyed by
+/// reply id. Saved as a failed hook if the submessage fails.
+pub const PENDING_HOOKS: Map<u64, (Addr, Binary)> = Map::new("pending_hooks");
+/// Reply id of the next hook submessage, less `HOOK_REPLY_ID_OFFSET`.
+pub const HOOK_REPLY_COUNT: Item<u64> = Item::new("hook_reply_count");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct FailedHook {
+    pub hook: Addr,
+    pub msg: Binary,
+    pub error: String,
+    pub height: u64,
+}
+
+pub const FAILED_HOOKS: Map<u64, FailedHook> = Map::new("failed_hooks");
+pub const FAILED_HOOK_COUNT: Item<u64> = Item::new("failed_hook_count");
+
+/// The DAO using this contract as its voting module, which is the address
+/// that instantiated it.
+pub const DAO: Item<Addr> = Item::new("dao");
+
+/// The address each staker delegated their voting power to.
+pub const DELEGATIONS: SnapshotMap<&Addr, Addr> = SnapshotMap::new(
+    "delegations",
+    "delegations__checkpoints",
+    "delegations__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Voting power delegated to each address by other stakers.
+pub const DELEGATED_POWER: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "delegated_power",
+    "delegated_power__checkpoints",
+    "delegated_power__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Addresses which opted out of being staked for by other addresses.
+pub const STAKE_OPT_OUTS: Map<&Addr, Empty> = Map::new("stake_opt_outs");
+
+/// Staked balance × seconds accumulated up to `last_updated`.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct Score {
+    pub score: Uint128,
+    pub last_updated: u64,
+}
+
+pub const SCORES: Map<&Addr, Score> = Map::new("scores");
+
+pub const TOTAL_SCORE: Item<Score> = Item::new("total_score");
+
+/// Time in seconds after which scores stop accumulating, once set.
+pub const SCORE_END: Item<u64> = Item::new("score_end");